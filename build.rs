@@ -1,4 +1,9 @@
-use std::{env, fs::File, io::Write, path::PathBuf};
+use std::{
+    env, fs,
+    fs::File,
+    io::Write,
+    path::{Path, PathBuf},
+};
 
 #[derive(Clone, Copy, Debug)]
 enum GetOneError {
@@ -39,7 +44,9 @@ fn main() {
         .unwrap()
         .write_all(include_bytes!("memory.x"))
         .unwrap();
-    println!("cargo:rustc-link-search={}", out.display());
+    // Only the examples are linked here; as a library, this crate mustn't put its memory.x
+    // or linker scripts on the firmware's link line.
+    println!("cargo:rustc-link-arg-examples=-L{}", out.display());
 
     // By default, Cargo will re-run a build script whenever
     // any file in the project changes. By specifying `memory.x`
@@ -52,8 +59,95 @@ fn main() {
     // `--nmagic` is required if memory section addresses are not aligned to 0x10000,
     // for example the FLASH and RAM sections in your `memory.x`.
     // See https://github.com/rust-embedded/cortex-m-quickstart/pull/95
-    println!("cargo:rustc-link-arg=--nmagic");
+    println!("cargo:rustc-link-arg-examples=--nmagic");
 
     // Set the linker script to the one provided by cortex-m-rt.
-    println!("cargo:rustc-link-arg=-Tlink.x");
+    println!("cargo:rustc-link-arg-examples=-Tlink.x");
+
+    convert_assets(Path::new("assets"), &out.join("assets"));
+}
+
+/// Converts every BMP in `assets/` to an `Image` expression in `$OUT_DIR/assets/<file>.rs`,
+/// picked up by `static_image!("<file>")`. Each image is stored as RLE when that is smaller
+/// than raw RGB565. Only uncompressed 24/32-bit BMPs are read; export PNGs as such.
+fn convert_assets(dir: &Path, out: &Path) {
+    println!("cargo:rerun-if-changed={}", dir.display());
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    fs::create_dir_all(out).unwrap();
+
+    for entry in entries {
+        let path = entry.unwrap().path();
+        if !path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("bmp")) {
+            continue;
+        }
+        println!("cargo:rerun-if-changed={}", path.display());
+
+        let bytes = fs::read(&path).unwrap();
+        let (width, height, pixels) =
+            decode_bmp(&bytes).unwrap_or_else(|e| panic!("{}: {}", path.display(), e));
+
+        let raw: Vec<u8> = pixels.iter().flat_map(|p| p.to_be_bytes()).collect();
+        let rle = encode_rle(&pixels);
+        let (format, data) = if rle.len() < raw.len() { ("Rle", rle) } else { ("Raw565", raw) };
+
+        let name = path.file_name().unwrap().to_str().unwrap();
+        let mut file = File::create(out.join(format!("{name}.rs"))).unwrap();
+        write!(file, "Image::new({width}, {height}, ImageFormat::{format}, &{data:?})").unwrap();
+    }
+}
+
+/// Reads an uncompressed 24 or 32-bit BMP into RGB565 pixels, top row first.
+fn decode_bmp(bytes: &[u8]) -> Result<(u16, u16, Vec<u16>), &'static str> {
+    let u16_at = |i: usize| u16::from_le_bytes([bytes[i], bytes[i + 1]]);
+    let u32_at = |i: usize| u32::from_le_bytes([bytes[i], bytes[i + 1], bytes[i + 2], bytes[i + 3]]);
+
+    if bytes.len() < 54 || &bytes[..2] != b"BM" {
+        return Err("not a BMP file");
+    }
+    let data_offset = u32_at(10) as usize;
+    let width = u32_at(18) as i32;
+    let height = u32_at(22) as i32;
+    let bpp = u16_at(28) as usize;
+    let compression = u32_at(30);
+    // 3 is BI_BITFIELDS, which 32-bit BMPs use with the usual BGRA masks
+    if (bpp != 24 && bpp != 32) || (compression != 0 && compression != 3) {
+        return Err("only uncompressed 24/32-bit BMPs are supported");
+    }
+
+    let (w, h) = (width.unsigned_abs() as usize, height.unsigned_abs() as usize);
+    if w > u16::MAX as usize || h > u16::MAX as usize {
+        return Err("image too large");
+    }
+    let stride = (w * bpp / 8).div_ceil(4) * 4;
+    if bytes.len() < data_offset + stride * h {
+        return Err("truncated pixel data");
+    }
+
+    let mut pixels = Vec::with_capacity(w * h);
+    for row in 0..h {
+        // positive heights are stored bottom-up
+        let stored = if height > 0 { h - 1 - row } else { row };
+        let start = data_offset + stored * stride;
+        for px in bytes[start..start + w * bpp / 8].chunks_exact(bpp / 8) {
+            let (b, g, r) = (px[0] as u16, px[1] as u16, px[2] as u16);
+            pixels.push(((r >> 3) << 11) | ((g >> 2) << 5) | (b >> 3));
+        }
+    }
+    Ok((w as u16, h as u16, pixels))
+}
+
+/// Encodes pixels as `count, color MSB, color LSB` runs, the crate's `ImageFormat::Rle`.
+fn encode_rle(pixels: &[u16]) -> Vec<u8> {
+    let mut out = Vec::new();
+    let mut i = 0;
+    while i < pixels.len() {
+        let color = pixels[i];
+        let run = pixels[i..].iter().take(255).take_while(|&&p| p == color).count();
+        out.push(run as u8);
+        out.extend_from_slice(&color.to_be_bytes());
+        i += run;
+    }
+    out
 }
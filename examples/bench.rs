@@ -0,0 +1,127 @@
+#![no_std]
+#![no_main]
+
+//! Benchmarks the DMA driver's main drawing paths and prints the timings via defmt.
+//! Run with `cargo run --release --example bench` and compare the `bench:` lines across commits.
+
+use cortex_m::delay::Delay;
+use cortex_m::singleton;
+use cortex_m_rt::entry;
+
+use defmt_rtt as _;
+use embedded_graphics::mono_font::MonoTextStyleBuilder;
+use embedded_graphics::mono_font::ascii::FONT_10X20;
+use embedded_graphics::pixelcolor::Rgb565;
+use embedded_graphics::prelude::*;
+use embedded_graphics::primitives::{Circle, PrimitiveStyle};
+use embedded_graphics::text::Text;
+use panic_probe as _;
+use stm32f4xx_hal::dma::StreamsTuple;
+use stm32f4xx_hal::dwt::DwtExt;
+use stm32f4xx_hal::gpio::{self, Speed};
+use stm32f4xx_hal::hal::spi::{self, Phase, Polarity};
+use stm32f4xx_hal::prelude::*;
+use stm32f4xx_hal::spi::Spi;
+use stm32f4xx_hal::{self, rcc::RccExt};
+
+use waveshare_f401::st7789v2::bench::BenchReport;
+use waveshare_f401::st7789v2::dma::st7789v2dma::{CHUNK_SIZE, ST7789V2DMA};
+
+const W: usize = 240; // Display width
+const H: usize = 280; // Display height
+const OFFSET: usize = 20; // Non-visible rows at the top
+
+#[entry]
+fn main() -> ! {
+    let dp = stm32f4xx_hal::pac::Peripherals::take().unwrap();
+    let cp = cortex_m::Peripherals::take().unwrap();
+
+    let rcc = dp.RCC.constrain();
+    let clocks = rcc
+        .cfgr
+        .use_hse(25.MHz())
+        .sysclk(32.MHz())
+        .hclk(32.MHz())
+        .freeze();
+
+    let pa = dp.GPIOA.split();
+    let pa7_mosi = pa
+        .pa7
+        .into_push_pull_output()
+        .speed(Speed::VeryHigh)
+        .into_alternate();
+    let pa5_sck = pa
+        .pa5
+        .into_push_pull_output()
+        .speed(Speed::VeryHigh)
+        .into_alternate();
+
+    let mode = spi::Mode {
+        polarity: Polarity::IdleHigh,
+        phase: Phase::CaptureOnSecondTransition,
+    };
+    let spi = Spi::new(
+        dp.SPI1,
+        (pa5_sck, gpio::NoPin::new(), pa7_mosi),
+        mode,
+        12.MHz(),
+        &clocks,
+    );
+    let dc = pa.pa4.into_push_pull_output().speed(Speed::VeryHigh);
+    let cs = pa.pa3.into_push_pull_output().speed(Speed::VeryHigh);
+    let rst = pa.pa2.into_push_pull_output().speed(Speed::VeryHigh);
+    let mut d = Delay::new(cp.SYST, clocks.hclk().raw());
+
+    let dwt = cp.DWT.constrain(cp.DCB, &clocks);
+
+    let stream = StreamsTuple::new(dp.DMA2).3;
+    let tx = spi.use_dma().tx();
+    let cmd_buf = singleton!(: [u8; 1] = [0; 1]).unwrap();
+    let data_buf = singleton!(: [u8; 1] = [0; 1]).unwrap();
+    let caset_buf = singleton!(: [u8; 4] = [0; 4]).unwrap();
+    let raset_buf = singleton!(: [u8; 4] = [0; 4]).unwrap();
+    let chunk_buffer = singleton!(: [u8; CHUNK_SIZE] = [0; CHUNK_SIZE]).unwrap();
+
+    let mut display: ST7789V2DMA<'_, _, _, _, _, _, 3, 3, W, H, OFFSET> =
+        ST7789V2DMA::new(cs, dc, rst, tx, stream, &mut d, cmd_buf, data_buf, caset_buf, raset_buf, chunk_buffer);
+    display.init();
+
+    let mut report = BenchReport::<8>::new();
+    let full = (W * H) as u32;
+
+    report.measure(&dwt, "clear", full, || {
+        display.clear(Rgb565::BLACK).ok();
+    });
+
+    report.measure(&dwt, "blit_full_frame", full, || {
+        let bounds = display.bounding_box();
+        let pattern = (0..full).map(|i| {
+            let x = i % W as u32;
+            let y = i / W as u32;
+            Rgb565::new((x >> 3) as u8, (y >> 2) as u8, ((x + y) >> 4) as u8)
+        });
+        display.fill_contiguous(&bounds, pattern).ok();
+    });
+
+    let style = MonoTextStyleBuilder::new()
+        .font(&FONT_10X20)
+        .text_color(Rgb565::WHITE)
+        .background_color(Rgb565::BLACK)
+        .build();
+    report.measure(&dwt, "text_line", 20 * 10 * 20, || {
+        Text::new("0123456789ABCDEFGHIJ", Point::new(0, 40), style)
+            .draw(&mut display)
+            .ok();
+    });
+
+    report.measure(&dwt, "draw_iter_circle", 0, || {
+        Circle::new(Point::new(70, 90), 100)
+            .into_styled(PrimitiveStyle::with_stroke(Rgb565::GREEN, 1))
+            .draw(&mut display)
+            .ok();
+    });
+
+    report.log();
+
+    loop {}
+}
@@ -1,6 +1,9 @@
 #![no_std]
 #![no_main]
 
+//! Board bring-up for the Waveshare 1.69" panel on an F401 (SPI1, DMA2 stream 3):
+//! clocks, link check, init, then a timed full-screen fill.
+//! Run with `cargo run --example demo`.
 
 use cortex_m::delay::Delay;
 use cortex_m::peripheral::syst::SystClkSource;
@@ -21,17 +24,15 @@ use stm32f4xx_hal::prelude::*;
 use stm32f4xx_hal::spi::Spi;
 use stm32f4xx_hal::{self, rcc::RccExt};
 
-use crate::st7789v2::dma::st7789v2dma::{CHUNK_SIZE, ST7789V2DMA};
-
-mod st7789v2;
+use waveshare_f401::st7789v2::dma::st7789v2dma::{CHUNK_SIZE, ST7789V2DMA};
+use waveshare_f401::st7789v2::link::LinkConfig;
 
 const W: usize = 240; // Display width
 const H: usize = 280; // Display height
 const OFFSET: usize = 20; // Non-visible rows at the top
 
-// static BUFFER: &[u8] = include_bytes!("../output.rgb"); // RGB565 data for the display
+// static LOGO: Image = static_image!("logo.bmp"); // assets/logo.bmp, converted by build.rs
 // static BUFFER: [u8; 100 * 100 * 2] = [0xE8; 100 * 100 * 2]; // Red pattern in RGB565 format for testing
-// static BUFFER: &[u8] = include_bytes!("../output.rgb"); // RGB565 data for the display
 
 #[entry]
 fn main() -> ! {
@@ -65,18 +66,21 @@ fn main() -> ! {
     info!("sysclk:{}\thclk:{}", sfreq, hfreq);
     info!("pclk1:{}\tpclk2:{}", p1freq, p2freq);
 
+    // SPI1 runs off pclk2; checks the clock and picks the pin speed before anything is set up
+    let link = LinkConfig::new(p2freq).spi_clock(12_000_000).check().unwrap();
+
     let pa = dp.GPIOA.split();
 
     let pa7_mosi = pa
         .pa7
         .into_push_pull_output()
-        .speed(Speed::VeryHigh)
+        .speed(link.pin_speed)
         .into_alternate();
     let false_pin = gpio::NoPin::new();
     let pa5_sck = pa
         .pa5
         .into_push_pull_output()
-        .speed(Speed::VeryHigh)
+        .speed(link.pin_speed)
         .into_alternate();
 
     let mode = spi::Mode {
@@ -87,11 +91,11 @@ fn main() -> ! {
         dp.SPI1,
         (pa5_sck, false_pin, pa7_mosi),
         mode,
-        12.MHz(),
+        link.sck_hz.Hz(),
         &clocks,
     );
-    let dc = pa.pa4.into_push_pull_output().speed(Speed::VeryHigh); // high for data and low for command
-    let cs = pa.pa3.into_push_pull_output().speed(Speed::VeryHigh);
+    let dc = pa.pa4.into_push_pull_output().speed(link.pin_speed); // high for data and low for command
+    let cs = pa.pa3.into_push_pull_output().speed(link.pin_speed);
     let rst = pa.pa2.into_push_pull_output().speed(Speed::VeryHigh);
     let mut d = Delay::new(syst, clocks.hclk().raw());
 
@@ -108,7 +112,7 @@ fn main() -> ! {
 
     let mut dma_st: ST7789V2DMA<'_, _, _, _, _, _, 3, 3, W, H, OFFSET> =
         ST7789V2DMA::new(cs, dc, rst, tx, stream, &mut d, cmd_buf, data_buf, caset_buf, raset_buf, chunk_buffer);
-    
+    defmt::unwrap!(dma_st.check_buffers());
     dma_st.init();
 
     let r = Rectangle::new(dma_st.bounding_box().top_left, Size::new(W as u32, H as u32));
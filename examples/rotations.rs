@@ -0,0 +1,143 @@
+#![no_std]
+#![no_main]
+
+//! Checks that the visible area lands on the glass in all four rotations.
+//! Run with `cargo run --example rotations`: every rotation asserts that `bounding_box()` maps
+//! onto RAM columns 0..W and rows OFFSET..OFFSET+H, then draws a white frame along the edges
+//! with a red square in the top left corner. A frame edge missing on the screen, or garbage
+//! showing outside it, means the offsets are off for that rotation.
+
+use cortex_m::delay::Delay;
+use cortex_m::singleton;
+use cortex_m_rt::entry;
+
+use defmt_rtt as _;
+use embedded_graphics::pixelcolor::Rgb565;
+use embedded_graphics::prelude::*;
+use embedded_graphics::primitives::{PrimitiveStyle, Rectangle};
+use panic_probe as _;
+use stm32f4xx_hal::dma::StreamsTuple;
+use stm32f4xx_hal::gpio::{self, Speed};
+use stm32f4xx_hal::hal::spi::{self, Phase, Polarity};
+use stm32f4xx_hal::prelude::*;
+use stm32f4xx_hal::spi::Spi;
+use stm32f4xx_hal::{self, rcc::RccExt};
+
+use waveshare_f401::st7789v2::common::{Madctl, Orientation, RAM_HEIGHT, RAM_WIDTH};
+use waveshare_f401::st7789v2::dma::st7789v2dma::{CHUNK_SIZE, ST7789V2DMA};
+use waveshare_f401::st7789v2::transform::Transform;
+
+const W: usize = 240; // Display width
+const H: usize = 280; // Display height
+const OFFSET: usize = 20; // Non-visible rows at the top
+
+const ROTATIONS: [Orientation; 4] = [
+    Orientation::Portrait,
+    Orientation::Landscape,
+    Orientation::PortraitFlipped,
+    Orientation::LandscapeFlipped,
+];
+
+/// Maps a window in MADCTL address space back to native portrait RAM coordinates.
+fn native_window(transform: Transform, window: Rectangle) -> Rectangle {
+    let madctl = transform.madctl();
+    let (mut x, mut y) = (window.top_left.x, window.top_left.y);
+    let (mut w, mut h) = (window.size.width as i32, window.size.height as i32);
+    if madctl.contains(Madctl::MV) {
+        (x, y, w, h) = (y, x, h, w);
+    }
+    if madctl.contains(Madctl::MX) {
+        x = RAM_WIDTH as i32 - w - x;
+    }
+    if madctl.contains(Madctl::MY) {
+        y = RAM_HEIGHT as i32 - h - y;
+    }
+    Rectangle::new(Point::new(x, y), Size::new(w as u32, h as u32))
+}
+
+#[entry]
+fn main() -> ! {
+    let dp = stm32f4xx_hal::pac::Peripherals::take().unwrap();
+    let cp = cortex_m::Peripherals::take().unwrap();
+
+    let rcc = dp.RCC.constrain();
+    let clocks = rcc
+        .cfgr
+        .use_hse(25.MHz())
+        .sysclk(32.MHz())
+        .hclk(32.MHz())
+        .freeze();
+
+    let pa = dp.GPIOA.split();
+    let pa7_mosi = pa
+        .pa7
+        .into_push_pull_output()
+        .speed(Speed::VeryHigh)
+        .into_alternate();
+    let pa5_sck = pa
+        .pa5
+        .into_push_pull_output()
+        .speed(Speed::VeryHigh)
+        .into_alternate();
+
+    let mode = spi::Mode {
+        polarity: Polarity::IdleHigh,
+        phase: Phase::CaptureOnSecondTransition,
+    };
+    let spi = Spi::new(
+        dp.SPI1,
+        (pa5_sck, gpio::NoPin::new(), pa7_mosi),
+        mode,
+        12.MHz(),
+        &clocks,
+    );
+    let dc = pa.pa4.into_push_pull_output().speed(Speed::VeryHigh);
+    let cs = pa.pa3.into_push_pull_output().speed(Speed::VeryHigh);
+    let rst = pa.pa2.into_push_pull_output().speed(Speed::VeryHigh);
+    let mut d = Delay::new(cp.SYST, clocks.hclk().raw());
+
+    let stream = StreamsTuple::new(dp.DMA2).3;
+    let tx = spi.use_dma().tx();
+    let cmd_buf = singleton!(: [u8; 1] = [0; 1]).unwrap();
+    let data_buf = singleton!(: [u8; 1] = [0; 1]).unwrap();
+    let caset_buf = singleton!(: [u8; 4] = [0; 4]).unwrap();
+    let raset_buf = singleton!(: [u8; 4] = [0; 4]).unwrap();
+    let chunk_buffer = singleton!(: [u8; CHUNK_SIZE] = [0; CHUNK_SIZE]).unwrap();
+
+    let mut display: ST7789V2DMA<'_, _, _, _, _, _, 3, 3, W, H, OFFSET> =
+        ST7789V2DMA::new(cs, dc, rst, tx, stream, &mut d, cmd_buf, data_buf, caset_buf, raset_buf, chunk_buffer);
+    display.init();
+
+    let glass = Rectangle::new(Point::new(0, OFFSET as i32), Size::new(W as u32, H as u32));
+    let frame = PrimitiveStyle::with_stroke(Rgb565::WHITE, 1);
+    let marker = PrimitiveStyle::with_fill(Rgb565::RED);
+
+    loop {
+        for (i, &rotation) in ROTATIONS.iter().enumerate() {
+            display.set_orientation(rotation);
+
+            let bounds = display.bounding_box();
+            let (width, height) = display.transform().size::<W, H>();
+            defmt::assert!(
+                bounds.size == Size::new(width as u32, height as u32),
+                "rotation {}: bounding box doesn't match the rotated size",
+                i
+            );
+            defmt::assert!(
+                native_window(display.transform(), display.visible_window()) == glass,
+                "rotation {}: visible window is off the glass",
+                i
+            );
+
+            display.clear(Rgb565::BLACK).ok();
+            bounds.into_styled(frame).draw(&mut display).ok();
+            Rectangle::new(Point::zero(), Size::new(20, 20))
+                .into_styled(marker)
+                .draw(&mut display)
+                .ok();
+            defmt::info!("rotation {}: {}x{} ok", i, width, height);
+
+            display.d.delay_ms(2000);
+        }
+    }
+}
@@ -0,0 +1,79 @@
+use defmt::info;
+use stm32f4xx_hal::dwt::{ClockDuration, Dwt};
+
+/// A single timed benchmark case.
+#[derive(Debug, Clone, Copy)]
+pub struct BenchEntry {
+    pub name: &'static str,
+    pub micros: u32,
+    pub pixels: u32,
+}
+
+impl BenchEntry {
+    /// Returns the throughput in pixels per second, 0 if the case didn't record a pixel count.
+    pub fn pixels_per_sec(&self) -> u32 {
+        if self.micros == 0 {
+            return 0;
+        }
+        ((self.pixels as u64 * 1_000_000) / self.micros as u64) as u32
+    }
+}
+
+/// Collects DWT timings for up to N benchmark cases and prints them via defmt,
+/// so performance regressions can be compared across commits on real hardware.
+pub struct BenchReport<const N: usize> {
+    entries: [Option<BenchEntry>; N],
+    len: usize,
+}
+
+impl<const N: usize> BenchReport<N> {
+    pub const fn new() -> Self {
+        Self {
+            entries: [None; N],
+            len: 0,
+        }
+    }
+
+    /// Records a duration measured elsewhere. Entries past N are dropped.
+    pub fn record(&mut self, name: &'static str, pixels: u32, duration: ClockDuration) {
+        if self.len == N {
+            return;
+        }
+        self.entries[self.len] = Some(BenchEntry {
+            name,
+            micros: duration.as_micros() as u32,
+            pixels,
+        });
+        self.len += 1;
+    }
+
+    /// Runs `f` under the DWT cycle counter and records it as `name`.
+    pub fn measure<F: FnOnce()>(&mut self, dwt: &Dwt, name: &'static str, pixels: u32, f: F) {
+        let duration = dwt.measure(f);
+        self.record(name, pixels, duration);
+    }
+
+    pub fn entries(&self) -> impl Iterator<Item = &BenchEntry> {
+        self.entries[..self.len].iter().flatten()
+    }
+
+    /// Prints one line per case in a fixed format, so runs can be diffed.
+    pub fn log(&self) {
+        info!("bench: {} cases", self.len);
+        for entry in self.entries() {
+            info!(
+                "bench: {=str} {} us {} px {} px/s",
+                entry.name,
+                entry.micros,
+                entry.pixels,
+                entry.pixels_per_sec()
+            );
+        }
+    }
+}
+
+impl<const N: usize> Default for BenchReport<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
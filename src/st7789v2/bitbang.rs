@@ -0,0 +1,248 @@
+use core::convert::Infallible;
+
+use cortex_m::delay::Delay;
+use embedded_graphics::pixelcolor::{raw::ToBytes, Rgb565};
+use embedded_hal::digital::{self, OutputPin};
+use embedded_hal::spi::{self, ErrorKind, ErrorType, Mode, Phase, Polarity, SpiBus};
+use embedded_hal_bus::spi::{ExclusiveDevice, NoDelay};
+
+use crate::st7789v2::common::{Commands, Error, Madctl};
+use crate::st7789v2::spi::ST7789V2;
+use crate::st7789v2::encode;
+
+/// A pin write of the software SPI failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BitBangError<SCKE, MOSIE> {
+    SCK(SCKE),
+    MOSI(MOSIE),
+}
+
+impl<SCKE: core::fmt::Debug, MOSIE: core::fmt::Debug> spi::Error for BitBangError<SCKE, MOSIE> {
+    fn kind(&self) -> ErrorKind {
+        ErrorKind::Other
+    }
+}
+
+/// Write-only software SPI on any two output pins, in any SPI mode; the panel works in
+/// mode 3 (`MODE_3`, SCK idles high, data sampled on the rising edge) like the hardware SPI
+/// setup in `examples/demo.rs`, and in mode 0.
+/// There are no delays: every bit is three pin writes, so the clock runs as fast as the pins
+/// switch, a few MHz on MCU pins and a few kHz behind an I2C expander, both well within the
+/// controller's limits. Reads return zeros, there is no MISO.
+pub struct BitBangSpi<SCK, MOSI> {
+    sck: SCK,
+    mosi: MOSI,
+    mode: Mode,
+}
+
+impl<SCK: OutputPin, MOSI: OutputPin> BitBangSpi<SCK, MOSI> {
+    pub const fn new(sck: SCK, mosi: MOSI, mode: Mode) -> Self {
+        Self { sck, mosi, mode }
+    }
+
+    pub fn mode(&self) -> Mode {
+        self.mode
+    }
+
+    pub fn release(self) -> (SCK, MOSI) {
+        (self.sck, self.mosi)
+    }
+
+    /// Drives SCK to its idle level (`idle`) or away from it.
+    fn set_sck(&mut self, idle: bool) -> Result<(), BitBangError<SCK::Error, MOSI::Error>> {
+        let high = idle == (self.mode.polarity == Polarity::IdleHigh);
+        if high { self.sck.set_high() } else { self.sck.set_low() }.map_err(BitBangError::SCK)
+    }
+
+    fn set_mosi(&mut self, high: bool) -> Result<(), BitBangError<SCK::Error, MOSI::Error>> {
+        if high { self.mosi.set_high() } else { self.mosi.set_low() }.map_err(BitBangError::MOSI)
+    }
+
+    fn write_byte(&mut self, byte: u8) -> Result<(), BitBangError<SCK::Error, MOSI::Error>> {
+        for bit in (0..8).rev() {
+            let high = byte & (1 << bit) != 0;
+            match self.mode.phase {
+                // data valid before the first edge, which samples it
+                Phase::CaptureOnFirstTransition => {
+                    self.set_mosi(high)?;
+                    self.set_sck(false)?;
+                    self.set_sck(true)?;
+                }
+                // the first edge shifts, the second samples
+                Phase::CaptureOnSecondTransition => {
+                    self.set_sck(false)?;
+                    self.set_mosi(high)?;
+                    self.set_sck(true)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<SCK: OutputPin, MOSI: OutputPin> ErrorType for BitBangSpi<SCK, MOSI> {
+    type Error = BitBangError<SCK::Error, MOSI::Error>;
+}
+
+impl<SCK: OutputPin, MOSI: OutputPin> SpiBus for BitBangSpi<SCK, MOSI> {
+    fn read(&mut self, words: &mut [u8]) -> Result<(), Self::Error> {
+        words.fill(0);
+        Ok(())
+    }
+
+    fn write(&mut self, words: &[u8]) -> Result<(), Self::Error> {
+        self.set_sck(true)?;
+        words.iter().try_for_each(|&byte| self.write_byte(byte))
+    }
+
+    fn transfer(&mut self, read: &mut [u8], write: &[u8]) -> Result<(), Self::Error> {
+        self.write(write)?;
+        read.fill(0);
+        Ok(())
+    }
+
+    fn transfer_in_place(&mut self, words: &mut [u8]) -> Result<(), Self::Error> {
+        self.write(words)?;
+        words.fill(0);
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+/// Stand-in for a CS or RST line that is hard-wired (CS to GND, RST to VCC), so the panel
+/// runs on three GPIOs: SCK, MOSI and DC.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Unwired;
+
+impl digital::ErrorType for Unwired {
+    type Error = Infallible;
+}
+
+impl OutputPin for Unwired {
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+/// Size of the row `fill()` builds once and repeats: one RAM row of 240 pixels.
+const FILL_ROW_BYTES: usize = 2 * 240;
+
+/// Errors of `DebugPanel`, one variant per bus or pin.
+pub type PanelError<SPI, CS, DC, RST> = Error<
+    <SPI as ErrorType>::Error,
+    <CS as digital::ErrorType>::Error,
+    <DC as digital::ErrorType>::Error,
+    <RST as digital::ErrorType>::Error,
+>;
+
+/// Deliberately small blocking driver over any `SpiBus`, for board bring-up: with a
+/// `BitBangSpi` on spare GPIOs (or pins of an I2C expander) it validates the panel, its
+/// wiring and the W x H / OFFSET geometry before the SPI and DMA configuration is sorted out.
+/// Portrait only, RGB565, no DrawTarget; switch to `ST7789V2DMA` once the link works.
+pub struct DebugPanel<SPI, CS, DC, RST, const W: usize, const H: usize, const OFFSET: usize> {
+    spi: SPI,
+    cs: CS,
+    dc: DC,
+    rst: RST,
+}
+
+impl<SPI, CS, DC, RST, const W: usize, const H: usize, const OFFSET: usize> DebugPanel<SPI, CS, DC, RST, W, H, OFFSET>
+where
+    SPI: SpiBus,
+    CS: OutputPin,
+    DC: OutputPin,
+    RST: OutputPin,
+{
+    pub const fn new(spi: SPI, cs: CS, dc: DC, rst: RST) -> Self {
+        Self { spi, cs, dc, rst }
+    }
+
+    /// Resets the panel and brings it up in portrait RGB565, display on.
+    pub fn init(&mut self, delay: &mut Delay) -> Result<(), PanelError<SPI, CS, DC, RST>> {
+        self.rst.set_low().map_err(Error::RST)?;
+        delay.delay_ms(120);
+        self.rst.set_high().map_err(Error::RST)?;
+        delay.delay_ms(150);
+
+        self.command(Commands::SoftwareReset, &[])?;
+        delay.delay_ms(150);
+        self.command(Commands::SleepOut, &[])?;
+        delay.delay_ms(120);
+        self.command(Commands::SetColorMode, &[0x55])?;
+        self.command(Commands::MemoryDataAccessControl, &[Madctl::empty().bits()])?;
+        self.command(Commands::InversionOn, &[])?; // the panel shows inverted colors without INVON
+        self.command(Commands::DisplayOn, &[])?;
+        delay.delay_ms(50);
+        Ok(())
+    }
+
+    /// Sends `cmd` with `params` in one CS cycle.
+    pub fn command(&mut self, cmd: Commands, params: &[u8]) -> Result<(), PanelError<SPI, CS, DC, RST>> {
+        self.cs.set_low().map_err(Error::CS)?;
+        self.dc.set_low().map_err(Error::DC)?;
+        self.spi.write(&[cmd as u8]).map_err(Error::Spi)?;
+        if !params.is_empty() {
+            self.dc.set_high().map_err(Error::DC)?;
+            self.spi.write(params).map_err(Error::Spi)?;
+        }
+        self.spi.flush().map_err(Error::Spi)?;
+        self.cs.set_high().map_err(Error::CS)
+    }
+
+    /// Fills the visible area with `color`. At bit-bang speeds this takes a while
+    /// (W x H x 16 bits), which is fine for checking the geometry: every edge should be colored.
+    pub fn fill(&mut self, color: Rgb565) -> Result<(), PanelError<SPI, CS, DC, RST>> {
+        let mut row = [0u8; FILL_ROW_BYTES];
+        for pixel in row.chunks_exact_mut(2) {
+            pixel.copy_from_slice(&color.to_be_bytes());
+        }
+        let row = &row[..(W * 2).min(FILL_ROW_BYTES)];
+        self.write_frame(|spi| (0..H).try_for_each(|_| spi.write(row)))
+    }
+
+    /// Draws a whole frame of big-endian RGB565 bytes (W x H x 2).
+    pub fn draw_screen(&mut self, buffer: &[u8]) -> Result<(), PanelError<SPI, CS, DC, RST>> {
+        let len = buffer.len().min(W * H * 2);
+        self.write_frame(|spi| spi.write(&buffer[..len]))
+    }
+
+    fn write_frame<F>(&mut self, write: F) -> Result<(), PanelError<SPI, CS, DC, RST>>
+    where
+        F: FnOnce(&mut SPI) -> Result<(), SPI::Error>,
+    {
+        self.command(Commands::CASET, &encode::caset(0, W as u16 - 1))?;
+        self.command(Commands::RASET, &encode::raset(OFFSET as u16, (OFFSET + H) as u16 - 1))?;
+
+        self.cs.set_low().map_err(Error::CS)?;
+        self.dc.set_low().map_err(Error::DC)?;
+        self.spi.write(&[Commands::RAMWR as u8]).map_err(Error::Spi)?;
+        self.dc.set_high().map_err(Error::DC)?;
+        write(&mut self.spi).map_err(Error::Spi)?;
+        self.spi.flush().map_err(Error::Spi)?;
+        self.cs.set_high().map_err(Error::CS)
+    }
+
+    pub fn release(self) -> (SPI, CS, DC, RST) {
+        (self.spi, self.cs, self.dc, self.rst)
+    }
+}
+
+/// The blocking `ST7789V2` on a `BitBangSpi`, for boards where the hardware SPI pins are
+/// taken by other peripherals. It is the same driver, so code written against `ST7789V2`
+/// moves over by swapping the type; only the stm32f4xx `send_data_async()`/`finish_async()`
+/// add-on is missing, there is no SPI interrupt.
+///
+/// ```ignore
+/// let spi = BitBangSpi::new(sck, mosi, MODE_3);
+/// let mut display: ST7789V2Soft<_, _, _, _, _, _, 240, 280> =
+///     ST7789V2::new(ExclusiveDevice::new_no_delay(spi, cs).unwrap(), dc, rst, delay);
+/// ```
+pub type ST7789V2Soft<SCK, MOSI, DC, RST, CS, DELAY, const W: usize, const H: usize> =
+    ST7789V2<ExclusiveDevice<BitBangSpi<SCK, MOSI>, CS, NoDelay>, DC, RST, DELAY, W, H>;
@@ -0,0 +1,102 @@
+use embedded_graphics::{prelude::*, primitives::Rectangle};
+
+/// The visible part of an area, as the inclusive corners a CASET/RASET window takes.
+/// Every DrawTarget clips through here, so an area partly or entirely off-screen (negative
+/// coordinates, sizes past `i32::MAX`) draws its visible part instead of wrapping around
+/// when cast to the panel's `u16` coordinates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, defmt::Format)]
+pub struct Window {
+    pub xs: u16,
+    pub xe: u16,
+    pub ys: u16,
+    pub ye: u16,
+}
+
+impl Window {
+    /// Clips `area` to `bounds` (the screen, at or right of and below the origin).
+    /// # Returns
+    /// `None` if no pixel of `area` is visible.
+    pub fn clip(area: &Rectangle, bounds: &Rectangle) -> Option<Self> {
+        let (xs, xe) = clip_span(area.top_left.x, area.size.width, bounds.top_left.x, bounds.size.width)?;
+        let (ys, ye) = clip_span(area.top_left.y, area.size.height, bounds.top_left.y, bounds.size.height)?;
+        Some(Self { xs, xe, ys, ye })
+    }
+
+    pub fn width(&self) -> usize {
+        (self.xe - self.xs) as usize + 1
+    }
+
+    pub fn height(&self) -> usize {
+        (self.ye - self.ys) as usize + 1
+    }
+
+    pub fn rectangle(&self) -> Rectangle {
+        Rectangle::new(
+            Point::new(self.xs as i32, self.ys as i32),
+            Size::new(self.width() as u32, self.height() as u32),
+        )
+    }
+
+    /// Takes the colors of the whole `area` in row-major order, as `fill_contiguous()` gets
+    /// them, and yields only those of the pixels inside this window.
+    pub fn visible_colors<I: IntoIterator>(&self, area: &Rectangle, colors: I) -> VisibleColors<I::IntoIter> {
+        let left = (self.xs as i64 - area.top_left.x as i64) as usize;
+        let top = (self.ys as i64 - area.top_left.y as i64) as usize;
+        let area_width = area.size.width as usize;
+        VisibleColors {
+            colors: colors.into_iter(),
+            skip: top.saturating_mul(area_width).saturating_add(left),
+            row_gap: area_width - self.width(),
+            width: self.width(),
+            column: 0,
+            remaining: self.width() * self.height(),
+        }
+    }
+}
+
+/// Clips `start..start + len` to `bounds_start..bounds_start + bounds_len` in 64-bit math.
+fn clip_span(start: i32, len: u32, bounds_start: i32, bounds_len: u32) -> Option<(u16, u16)> {
+    let first = (start as i64).max(bounds_start as i64).max(0);
+    let end = (start as i64 + len as i64).min(bounds_start as i64 + bounds_len as i64);
+    if end <= first {
+        return None;
+    }
+    Some((first.min(u16::MAX as i64) as u16, (end - 1).min(u16::MAX as i64) as u16))
+}
+
+/// Iterator returned by `Window::visible_colors()`.
+/// Skips whole off-screen rows with `nth()` instead of testing every pixel.
+pub struct VisibleColors<I> {
+    colors: I,
+    /// Colors to drop before the next one, either up to the first visible pixel or between rows.
+    skip: usize,
+    row_gap: usize,
+    width: usize,
+    column: usize,
+    remaining: usize,
+}
+
+impl<I: Iterator> Iterator for VisibleColors<I> {
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        if self.column == self.width {
+            self.column = 0;
+            self.skip = self.row_gap;
+        }
+        let color = match core::mem::take(&mut self.skip) {
+            0 => self.colors.next(),
+            skip => self.colors.nth(skip),
+        }?;
+        self.column += 1;
+        self.remaining -= 1;
+        Some(color)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.remaining))
+    }
+}
@@ -0,0 +1,71 @@
+use embedded_graphics::pixelcolor::{Rgb565, RgbColor};
+
+/// Blends `fg` over `bg` with `alpha` (0 = all `bg`, 255 = all `fg`), per channel.
+pub fn blend(fg: Rgb565, bg: Rgb565, alpha: u8) -> Rgb565 {
+    let a = alpha as u16;
+    let mix = |f: u8, b: u8| -> u8 { ((f as u16 * a + b as u16 * (255 - a) + 127) / 255) as u8 };
+    Rgb565::new(mix(fg.r(), bg.r()), mix(fg.g(), bg.g()), mix(fg.b(), bg.b()))
+}
+
+// Rec. 601 luma weights out of 256 (0.299, 0.587, 0.114), per level of each channel,
+// so `luma()` is three lookups and two adds. The weights sum to 256, the result stays <= 255.
+const LUMA_R: [u8; 32] = luma_table::<32>(77);
+const LUMA_G: [u8; 64] = luma_table::<64>(150);
+const LUMA_B: [u8; 32] = luma_table::<32>(29);
+
+const fn luma_table<const N: usize>(weight: u32) -> [u8; N] {
+    let mut table = [0; N];
+    let mut i = 0;
+    while i < N {
+        table[i] = (i as u32 * 255 / (N as u32 - 1) * weight / 256) as u8;
+        i += 1;
+    }
+    table
+}
+
+/// Returns the perceived brightness of `color`, 0 to 255.
+pub fn luma(color: Rgb565) -> u8 {
+    LUMA_R[color.r() as usize] + LUMA_G[color.g() as usize] + LUMA_B[color.b() as usize]
+}
+
+/// Returns the gray with the same perceived brightness as `color`.
+pub fn grayscale(color: Rgb565) -> Rgb565 {
+    let y = luma(color);
+    Rgb565::new(y >> 3, y >> 2, y >> 3)
+}
+
+/// 4x4 Bayer threshold matrix, indexed [y & 3][x & 3].
+const BAYER_4X4: [[u8; 4]; 4] = [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
+
+/// Quantizes `color` to one of 4 grays (0, 85, 170, 255 luma) with ordered dithering,
+/// `x`, `y` being the pixel's position so the pattern stays put across separate draws.
+pub fn gray4_dithered(color: Rgb565, x: u16, y: u16) -> Rgb565 {
+    let threshold = (BAYER_4X4[(y & 3) as usize][(x & 3) as usize] as u16 * 2 + 1) * 255 / 32;
+    let level = ((luma(color) as u16 * 3 + threshold) / 255).min(3) as u8;
+    let gray = level * 85;
+    Rgb565::new(gray >> 3, gray >> 2, gray >> 3)
+}
+
+/// Conversion applied to every pixel as the driver packs it into a DMA chunk,
+/// so the whole screen changes look without touching the application's drawing code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColorFilter {
+    #[default]
+    None,
+    /// Luma only, e.g. for a dimmed or locked screen or as an accessibility mode.
+    Grayscale,
+    /// E-reader style output: 4 dithered gray levels. Text and flat fills stay calm, and the
+    /// reduced palette suits the panel's low-color idle mode.
+    Gray4Dithered,
+}
+
+impl ColorFilter {
+    /// Converts `color`, drawn at `x`, `y` (only the dithered modes look at the position).
+    pub fn apply(self, color: Rgb565, x: u16, y: u16) -> Rgb565 {
+        match self {
+            ColorFilter::None => color,
+            ColorFilter::Grayscale => grayscale(color),
+            ColorFilter::Gray4Dithered => gray4_dithered(color, x, y),
+        }
+    }
+}
@@ -1,8 +1,11 @@
+use cortex_m::delay::Delay;
+
 
 /// Error type for the ST7789V2 driver.
 /// It is a generic error type that can be used to handle errors from the SPI, CS and DC pins.
+/// The DMA driver reports failed pin writes as `Error<Infallible, ..>`, see `DmaPinError`.
 #[allow(dead_code)]
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Error<SpiE, CSE, DCE, RSE> {
     Spi(SpiE),
     CS(CSE),
@@ -10,32 +13,266 @@ pub enum Error<SpiE, CSE, DCE, RSE> {
     RST(RSE),
 }
 
+/// The drivers' pins and delay, borrowed through `parts()` without releasing the driver,
+/// e.g. to reuse the delay or toggle an LED on the same port.
+/// Leave CS high (deselected) when done; the next driver call expects the bus idle.
+pub struct Parts<'p, CS, DC, RST> {
+    pub cs: &'p mut CS,
+    pub dc: &'p mut DC,
+    pub rst: &'p mut RST,
+    pub delay: &'p mut Delay,
+}
+
+/// The blocking `ST7789V2`'s SPI device, pins and delay, borrowed through its `parts()`.
+/// Chip select belongs to the device there; the bus is idle when the parts are handed out.
+pub struct DeviceParts<'p, SPI, DC, RST, DELAY> {
+    pub spi: &'p mut SPI,
+    pub dc: &'p mut DC,
+    pub rst: &'p mut RST,
+    pub delay: &'p mut DELAY,
+}
+
 /// Color mode for the ST7789V2 display.
 /// This enum defines the color mode used by the display.
 /// Currently, only RGB565 (16-bit color mode) is supported.
 #[repr(u8)]
 #[allow(dead_code)]
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ColorMode {
     RGB565 = 0x55, // 16-bit color mode
+    RGB666 = 0x66, // 18-bit color mode, 3 bytes per pixel
+}
+
+/// Size of the controller's frame memory in pixels.
+/// The ST7789V2 always has a 240x320 RAM, regardless of how much of it the glass shows.
+pub const RAM_WIDTH: usize = 240;
+pub const RAM_HEIGHT: usize = 320;
+
+/// MADCTL (0x36) parameter, the memory data access control bits.
+/// Orientation, mirroring and color order each set their own bits on one value,
+/// so they compose instead of overwriting each other.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Madctl(u8);
+
+impl Madctl {
+    /// Page (row) address order: bottom to top.
+    pub const MY: Madctl = Madctl(0b1000_0000);
+    /// Column address order: right to left.
+    pub const MX: Madctl = Madctl(0b0100_0000);
+    /// Page/column exchange: rows and columns are swapped.
+    pub const MV: Madctl = Madctl(0b0010_0000);
+    /// Line address order: vertical refresh bottom to top.
+    pub const ML: Madctl = Madctl(0b0001_0000);
+    /// Sub-pixel order BGR instead of RGB.
+    pub const BGR: Madctl = Madctl(0b0000_1000);
+    /// Display data latch order: horizontal refresh right to left.
+    pub const MH: Madctl = Madctl(0b0000_0100);
+
+    pub const fn empty() -> Self {
+        Madctl(0)
+    }
+
+    pub const fn from_bits(bits: u8) -> Self {
+        Madctl(bits)
+    }
+
+    /// Returns the raw MADCTL parameter byte.
+    pub const fn bits(self) -> u8 {
+        self.0
+    }
+
+    pub const fn contains(self, other: Madctl) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    pub const fn union(self, other: Madctl) -> Self {
+        Madctl(self.0 | other.0)
+    }
+
+    /// Flips the bits in `other`.
+    pub const fn toggle(self, other: Madctl) -> Self {
+        Madctl(self.0 ^ other.0)
+    }
+
+    /// Sets or clears the bits in `other`.
+    pub const fn set(self, other: Madctl, enable: bool) -> Self {
+        if enable { Madctl(self.0 | other.0) } else { Madctl(self.0 & !other.0) }
+    }
+
+    pub const fn with_my(self, enable: bool) -> Self {
+        self.set(Self::MY, enable)
+    }
+
+    pub const fn with_mx(self, enable: bool) -> Self {
+        self.set(Self::MX, enable)
+    }
+
+    pub const fn with_mv(self, enable: bool) -> Self {
+        self.set(Self::MV, enable)
+    }
+
+    pub const fn with_ml(self, enable: bool) -> Self {
+        self.set(Self::ML, enable)
+    }
+
+    pub const fn with_bgr(self, enable: bool) -> Self {
+        self.set(Self::BGR, enable)
+    }
+
+    pub const fn with_mh(self, enable: bool) -> Self {
+        self.set(Self::MH, enable)
+    }
+}
+
+impl core::ops::BitOr for Madctl {
+    type Output = Madctl;
+
+    fn bitor(self, rhs: Madctl) -> Madctl {
+        self.union(rhs)
+    }
+}
+
+/// Color order of the panel's sub-pixels.
+/// Panels wired BGR show red and blue swapped unless the MADCTL BGR bit is set,
+/// so the driver folds this into MADCTL and the DrawTarget can keep packing plain RGB565.
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "config-loader", derive(serde::Serialize, serde::Deserialize))]
+pub enum ColorOrder {
+    #[default]
+    Rgb,
+    Bgr,
+}
+
+impl ColorOrder {
+    /// Returns the MADCTL bits for this color order.
+    pub const fn madctl_bits(self) -> Madctl {
+        Madctl::empty().with_bgr(matches!(self, ColorOrder::Bgr))
+    }
+}
+
+/// Orientation of the display, as rotation clockwise from the panel's native portrait.
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "config-loader", derive(serde::Serialize, serde::Deserialize))]
+pub enum Orientation {
+    #[default]
+    Portrait,
+    Landscape,
+    PortraitFlipped,
+    LandscapeFlipped,
+}
+
+impl Orientation {
+    /// Returns the MADCTL bits (MY, MX, MV) for this orientation.
+    pub const fn madctl_bits(self) -> Madctl {
+        match self {
+            Orientation::Portrait => Madctl::empty(),
+            Orientation::Landscape => Madctl::MX.union(Madctl::MV),
+            Orientation::PortraitFlipped => Madctl::MY.union(Madctl::MX),
+            Orientation::LandscapeFlipped => Madctl::MY.union(Madctl::MV),
+        }
+    }
+
+    /// Returns true if rows and columns are exchanged (MADCTL MV).
+    pub const fn is_landscape(self) -> bool {
+        matches!(self, Orientation::Landscape | Orientation::LandscapeFlipped)
+    }
 }
 
 /// Commands for the ST7789V2 display.
-/// This enum defines the commands used to control the display.
-/// TODO: Add more commands as needed.
+/// The full documented command set, with the datasheet mnemonic next to each opcode.
+/// Wrapper methods and `ST7789V2DMA::command()` both take this enum.
 #[repr(u8)]
 #[allow(dead_code)]
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Commands {
-    SoftwareReset = 0x01,
-    SleepOut = 0x11,
-    SetColorMode = 0x3A,
-    MemoryDataAccessControl = 0x36,
-    DisplayOn = 0x29,
-    DisplayOff = 0x28,
-    CASET = 0x2A,
-    RASET = 0x2B,
-    RAMWR = 0x2C,
-    InversionOn = 0x21,
-    InversionOff = 0x20,
+    // System function commands (command table 1)
+    Nop = 0x00,                         // NOP
+    SoftwareReset = 0x01,               // SWRESET
+    ReadDisplayId = 0x04,               // RDDID
+    ReadDisplayStatus = 0x09,           // RDDST
+    ReadDisplayPowerMode = 0x0A,        // RDDPM
+    ReadDisplayMadctl = 0x0B,           // RDDMADCTL
+    ReadDisplayPixelFormat = 0x0C,      // RDDCOLMOD
+    ReadDisplayImageMode = 0x0D,        // RDDIM
+    ReadDisplaySignalMode = 0x0E,       // RDDSM
+    ReadDisplaySelfDiagnostic = 0x0F,   // RDDSDR
+    SleepIn = 0x10,                     // SLPIN
+    SleepOut = 0x11,                    // SLPOUT
+    PartialModeOn = 0x12,               // PTLON
+    NormalModeOn = 0x13,                // NORON
+    InversionOff = 0x20,                // INVOFF
+    InversionOn = 0x21,                 // INVON
+    GammaSet = 0x26,                    // GAMSET
+    DisplayOff = 0x28,                  // DISPOFF
+    DisplayOn = 0x29,                   // DISPON
+    CASET = 0x2A,                       // CASET
+    RASET = 0x2B,                       // RASET
+    RAMWR = 0x2C,                       // RAMWR
+    RAMRD = 0x2E,                       // RAMRD
+    PartialArea = 0x30,                 // PTLAR
+    VerticalScrollDefinition = 0x33,    // VSCRDEF
+    TearingEffectOff = 0x34,            // TEOFF
+    TearingEffectOn = 0x35,             // TEON
+    MemoryDataAccessControl = 0x36,     // MADCTL
+    VerticalScrollStartAddress = 0x37,  // VSCSAD
+    IdleModeOff = 0x38,                 // IDMOFF
+    IdleModeOn = 0x39,                  // IDMON
+    SetColorMode = 0x3A,                // COLMOD
+    WriteMemoryContinue = 0x3C,         // WRMEMC
+    ReadMemoryContinue = 0x3E,          // RDMEMC
+    SetTearScanline = 0x44,             // STE
+    GetScanline = 0x45,                 // GSCAN
+    WriteDisplayBrightness = 0x51,      // WRDISBV
+    ReadDisplayBrightness = 0x52,       // RDDISBV
+    WriteCtrlDisplay = 0x53,            // WRCTRLD
+    ReadCtrlDisplay = 0x54,             // RDCTRLD
+    WriteContentAdaptiveBrightness = 0x55, // WRCACE
+    ReadContentAdaptiveBrightness = 0x56,  // RDCABC
+    WriteCabcMinimumBrightness = 0x5E,  // WRCABCMB
+    ReadCabcMinimumBrightness = 0x5F,   // RDCABCMB
+    ReadAbcSelfDiagnostic = 0x68,       // RDABCSDR
+    ReadId1 = 0xDA,                     // RDID1
+    ReadId2 = 0xDB,                     // RDID2
+    ReadId3 = 0xDC,                     // RDID3
+
+    // Panel function commands (command table 2)
+    RamControl = 0xB0,                  // RAMCTRL
+    RgbInterfaceControl = 0xB1,         // RGBCTRL
+    PorchControl = 0xB2,                // PORCTRL
+    FrameRateControl1 = 0xB3,           // FRCTRL1
+    PartialControl = 0xB5,              // PARCTRL
+    GateControl = 0xB7,                 // GCTRL
+    GateOnTimingAdjustment = 0xB8,      // GTADJ
+    DigitalGammaEnable = 0xBA,          // DGMEN
+    VcomSetting = 0xBB,                 // VCOMS
+    PowerSaving = 0xBC,                 // POWSAVE
+    DisplayOffPowerSave = 0xBD,         // DLPOFFSAVE
+    LcmControl = 0xC0,                  // LCMCTRL
+    IdSetting = 0xC1,                   // IDSET
+    VdvVrhEnable = 0xC2,                // VDVVRHEN
+    VrhSet = 0xC3,                      // VRHS
+    VdvSet = 0xC4,                      // VDVS
+    VcomOffsetSet = 0xC5,               // VCMOFSET
+    FrameRateControl2 = 0xC6,           // FRCTRL2
+    CabcControl = 0xC7,                 // CABCCTRL
+    RegisterValueSelection1 = 0xC8,     // REGSEL1
+    RegisterValueSelection2 = 0xCA,     // REGSEL2
+    PwmFrequencySelection = 0xCC,       // PWMFRSEL
+    PowerControl1 = 0xD0,               // PWCTRL1
+    VapVanEnable = 0xD2,                // VAPVANEN
+    Command2Enable = 0xDF,              // CMD2EN
+    PositiveGammaControl = 0xE0,        // PVGAMCTRL
+    NegativeGammaControl = 0xE1,        // NVGAMCTRL
+    DigitalGammaLutRed = 0xE2,          // DGMLUTR
+    DigitalGammaLutBlue = 0xE3,         // DGMLUTB
+    GateControlExtended = 0xE4,         // GATECTRL
+    SpiTwoDataLaneControl = 0xE7,       // SPI2EN
+    PowerControl2 = 0xE8,               // PWCTRL2
+    EqualizeTimeControl = 0xE9,         // EQCTRL
+    ProgramModeControl = 0xEC,          // PROMCTRL
+    ProgramModeEnable = 0xFA,           // PROMEN
+    NvmSetting = 0xFC,                  // NVMSET
+    ProgramAction = 0xFE,               // PROMACT
 }
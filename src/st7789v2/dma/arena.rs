@@ -0,0 +1,44 @@
+use crate::st7789v2::dma::st7789v2dma::{check_chunk_size, CHUNK_SIZE};
+
+/// Static arena holding every buffer the DMA driver needs, with a CHUNK byte chunk buffer.
+/// Replaces the five separate `singleton!` buffers: take the arena once and hand it to
+/// `ST7789V2DMA::new_with_arena`, which carves out the cmd/data/caset/raset/chunk buffers itself,
+/// so they can't be passed in the wrong order.
+pub struct DmaArena<const CHUNK: usize = CHUNK_SIZE> {
+    pub(super) cmd_buf: [u8; 1],
+    pub(super) data_buf: [u8; 1],
+    pub(super) caset_buf: [u8; 4],
+    pub(super) raset_buf: [u8; 4],
+    pub(super) chunk_buffer: [u8; CHUNK],
+}
+
+impl<const CHUNK: usize> DmaArena<CHUNK> {
+    /// Creates an arena, for a chunk size other than `CHUNK_SIZE`:
+    /// `singleton!(: DmaArena<8192> = DmaArena::new()).unwrap()`.
+    /// CHUNK must be a multiple of 4 and at least `MIN_CHUNK_SIZE`.
+    pub const fn new() -> Self {
+        const { check_chunk_size(CHUNK) };
+        Self {
+            cmd_buf: [0; 1],
+            data_buf: [0; 1],
+            caset_buf: [0; 4],
+            raset_buf: [0; 4],
+            chunk_buffer: [0; CHUNK],
+        }
+    }
+}
+
+impl<const CHUNK: usize> Default for DmaArena<CHUNK> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DmaArena {
+    /// Takes the crate's static arena, with the default `CHUNK_SIZE`.
+    /// # Returns
+    /// `Some` the first time it is called, `None` afterwards.
+    pub fn take() -> Option<&'static mut DmaArena> {
+        cortex_m::singleton!(: DmaArena = DmaArena::new())
+    }
+}
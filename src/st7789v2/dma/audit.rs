@@ -0,0 +1,153 @@
+use crate::st7789v2::{
+    common::Commands,
+    dma::{command_stream::CommandStream, st7789v2dma::{ST7789V2DMA, TxDmaRoute}},
+};
+use embedded_graphics::{pixelcolor::Rgb565, prelude::Dimensions, primitives::Rectangle};
+use stm32f4xx_hal::{
+    dma::{
+        ChannelX, StreamX,
+        traits::{Channel, Stream},
+    },
+    hal::digital::OutputPin,
+    rcc,
+    spi::Instance,
+};
+
+/// RDDPM bits expected after `init()`: booster on, idle mode, sleep out, normal mode, display on/off.
+const POWER_MODE_BOOSTER_ON: u8 = 0b1000_0000;
+const POWER_MODE_IDLE: u8 = 0b0100_0000;
+const POWER_MODE_SLEEP_OUT: u8 = 0b0001_0000;
+const POWER_MODE_NORMAL: u8 = 0b0000_1000;
+const POWER_MODE_DISPLAY_ON: u8 = 0b0000_0100;
+
+/// Which settings read back from the panel differ from what the driver wrote.
+/// A mirrored or color-shifted screen after a supply glitch usually shows up here.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, defmt::Format)]
+pub struct ConfigDrift {
+    pub madctl: bool,
+    pub colmod: bool,
+    pub power_mode: bool,
+}
+
+impl ConfigDrift {
+    /// Returns true if any setting drifted.
+    pub fn any(&self) -> bool {
+        self.madctl || self.colmod || self.power_mode
+    }
+}
+
+impl<'a, SPI, DMA, CS, DC, RST, const CHANNEL: u8, const S: u8, const W: usize, const H: usize, const OFFSET: usize, CMD>
+    ST7789V2DMA<'a, SPI, DMA, CS, DC, RST, CHANNEL, S, W, H, OFFSET, CMD>
+where
+    SPI: Instance + TxDmaRoute<StreamX<DMA, S>, CHANNEL>,
+    CS: OutputPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DMA: rcc::Enable + rcc::Reset + stm32f4xx_hal::dma::traits::Instance,
+    StreamX<DMA, S>: Stream,
+    ChannelX<CHANNEL>: Channel,
+    CMD: CommandStream,
+{
+    /// Reads back MADCTL, COLMOD and the power mode register and compares them
+    /// with the driver's shadow state.
+    /// Requires the panel's SDO (or MISO) line to be wired to the SPI's MISO pin,
+    /// without it every read returns garbage and everything reports as drifted.
+    pub fn audit_config(&mut self) -> ConfigDrift {
+        let madctl = self.read_register(Commands::ReadDisplayMadctl);
+        let colmod = self.read_register(Commands::ReadDisplayPixelFormat);
+        let power_mode = self.read_register(Commands::ReadDisplayPowerMode);
+
+        let mut expected_power_mode = POWER_MODE_BOOSTER_ON | POWER_MODE_SLEEP_OUT;
+        if self.shadow.partial_area.is_none() {
+            expected_power_mode |= POWER_MODE_NORMAL;
+        }
+        if self.shadow.display_on {
+            expected_power_mode |= POWER_MODE_DISPLAY_ON;
+        }
+        if self.shadow.idle_mode {
+            expected_power_mode |= POWER_MODE_IDLE;
+        }
+
+        ConfigDrift {
+            madctl: madctl != self.shadow.madctl().bits(),
+            colmod: colmod != self.shadow.color_mode as u8,
+            power_mode: power_mode & 0b1101_1100 != expected_power_mode,
+        }
+    }
+
+    /// Reads a single-byte status register.
+    pub(super) fn read_register(&mut self, cmd: Commands) -> u8 {
+        // 8-bit reads are preceded by a single dummy clock, so the value straddles two bytes
+        let mut bytes = [0u8; 2];
+        let mut i = 0;
+        self.read_raw(cmd, bytes.len(), |byte| {
+            bytes[i] = byte;
+            i += 1;
+        });
+        (bytes[0] << 1) | (bytes[1] >> 7)
+    }
+
+    /// Reads the pixels of `area` (display coordinates) back from frame memory with RAMRD,
+    /// row-major into `out`, e.g. to save what an overlay is about to cover. Stops at the end
+    /// of `out`; returns the number of pixels read, 0 if `area` isn't on the screen.
+    /// The panel sends 18-bit pixels after a dummy byte whatever COLMOD says, so reads are
+    /// 3 bytes per pixel and the low bits of each channel are dropped on the way to RGB565.
+    /// Needs SDO/MISO wired like `audit_config()`; without it `out` fills with garbage.
+    pub fn read_pixels(&mut self, area: &Rectangle, out: &mut [Rgb565]) -> usize {
+        let area = area.intersection(&self.bounding_box());
+        let Some(bottom_right) = area.bottom_right() else {
+            return 0;
+        };
+        let count = out.len().min(area.size.width as usize * area.size.height as usize);
+        if count == 0 {
+            return 0;
+        }
+
+        self.set_window_inclusive(area.top_left.x as u16, bottom_right.x as u16, area.top_left.y as u16, bottom_right.y as u16);
+
+        let mut pixel = [0u8; 3];
+        let mut received = 0;
+        self.read_raw(Commands::RAMRD, 1 + count * 3, |byte| {
+            if received > 0 {
+                let i = received - 1;
+                pixel[i % 3] = byte;
+                if i % 3 == 2 {
+                    out[i / 3] = Rgb565::new(pixel[0] >> 3, pixel[1] >> 2, pixel[2] >> 3);
+                }
+            }
+            received += 1;
+        });
+        count
+    }
+
+    /// Sends `cmd` and clocks in `len` bytes, passing each to `sink`.
+    /// The SPI data register is driven by hand for the read, with TX DMA requests
+    /// disabled in the meantime.
+    fn read_raw<F: FnMut(u8)>(&mut self, cmd: Commands, len: usize, mut sink: F) {
+        // SAFETY: the driver owns the SPI's TX DMA path and no transfer is in flight here,
+        // the register block is only touched for the duration of this read.
+        let spi = unsafe { &*SPI::ptr() };
+
+        self.select();
+        self.send_command(cmd);
+        // waits for the command byte to leave the SPI, or it would be read as data
+        self.set_dc(true);
+
+        spi.cr2().modify(|_, w| w.txdmaen().clear_bit());
+        while spi.sr().read().bsy().bit_is_set() {}
+        // drop whatever the command transfer clocked in, and the overrun it caused
+        let _ = spi.dr8().read();
+        let _ = spi.sr().read();
+
+        for _ in 0..len {
+            while spi.sr().read().txe().bit_is_clear() {}
+            spi.dr8().write(|w| unsafe { w.dr().bits(0) });
+            while spi.sr().read().rxne().bit_is_clear() {}
+            sink(spi.dr8().read().dr().bits());
+        }
+
+        while spi.sr().read().bsy().bit_is_set() {}
+        spi.cr2().modify(|_, w| w.txdmaen().set_bit());
+        self.deselect();
+    }
+}
@@ -0,0 +1,109 @@
+use crate::st7789v2::{
+    dma::{command_stream::CommandStream, st7789v2dma::{ST7789V2DMA, TxDmaRoute}},
+    link::{LinkConfig, LinkConfigError, LinkPlan, PANEL_MAX_WRITE_HZ},
+};
+use defmt::info;
+use embedded_graphics::{pixelcolor::Rgb565, prelude::*};
+use stm32f4xx_hal::{
+    dma::{
+        ChannelX, StreamX,
+        traits::{Channel, Stream},
+    },
+    hal::digital::OutputPin,
+    rcc,
+    spi::Instance,
+};
+
+/// CR1.BR value of the slowest SCK (pclk / 256), used for the read-backs.
+const SLOWEST_BR: u8 = 0b111;
+
+/// Alternating bit patterns on every data line, filled in turn while tuning.
+const TEST_COLORS: [Rgb565; 2] = [Rgb565::new(0b10101, 0b010101, 0b10101), Rgb565::new(0b01010, 0b101010, 0b01010)];
+
+/// Why `tune_spi_clock()` couldn't find a clock.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, defmt::Format)]
+pub enum TuneError {
+    /// The registers don't read back even at the slowest clock, e.g. SDO/MISO isn't wired.
+    NoReadback,
+    /// Writes fail even at the slowest clock.
+    Unreliable,
+    /// The fastest working clock doesn't make a valid link, see `LinkConfig::check()`.
+    Link(LinkConfigError),
+}
+
+impl<'a, SPI, DMA, CS, DC, RST, const CHANNEL: u8, const S: u8, const W: usize, const H: usize, const OFFSET: usize, CMD>
+    ST7789V2DMA<'a, SPI, DMA, CS, DC, RST, CHANNEL, S, W, H, OFFSET, CMD>
+where
+    SPI: Instance + TxDmaRoute<StreamX<DMA, S>, CHANNEL>,
+    CS: OutputPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DMA: rcc::Enable + rcc::Reset + stm32f4xx_hal::dma::traits::Instance,
+    StreamX<DMA, S>: Stream,
+    ChannelX<CHANNEL>: Channel,
+    CMD: CommandStream,
+{
+    /// Finds the fastest SPI clock the wiring handles, by walking the prescaler down from
+    /// pclk / 256 towards pclk / 2. At each step the screen is filled with a test pattern and
+    /// the configuration rewritten at the candidate clock `rounds` times; after each round
+    /// the registers are read back at the slowest clock with `audit_config()`. The walk stops
+    /// at the first clock with a mismatch or a DMA link error.
+    /// `pclk_hz` is the clock of the SPI's APB bus. The SPI is left at its original clock and
+    /// the screen content is lost; configure the SPI with the returned plan afterwards.
+    /// Needs the panel's SDO wired to MISO, like `audit_config()`.
+    pub fn tune_spi_clock(&mut self, pclk_hz: u32, rounds: u32) -> Result<LinkPlan, TuneError> {
+        // SAFETY: no transfer is in flight between driver calls, and the SPI is only
+        // disabled while its prescaler changes.
+        let spi = unsafe { &*SPI::ptr() };
+        let original_br = spi.cr1().read().br().bits();
+
+        let set_br = |br: u8| {
+            while spi.sr().read().bsy().bit_is_set() {}
+            spi.cr1().modify(|_, w| w.spe().clear_bit());
+            spi.cr1().modify(|_, w| w.br().set(br));
+            spi.cr1().modify(|_, w| w.spe().set_bit());
+        };
+
+        set_br(SLOWEST_BR);
+        if self.audit_config().any() {
+            set_br(original_br);
+            return Err(TuneError::NoReadback);
+        }
+
+        let mut fastest = None;
+        for br in (0..=SLOWEST_BR).rev() {
+            let sck_hz = pclk_hz >> (br + 1);
+            if sck_hz > PANEL_MAX_WRITE_HZ {
+                break;
+            }
+
+            set_br(br);
+            let errors_before = self.error_stats();
+            let mut passed = true;
+            for round in 0..rounds {
+                self.clear(TEST_COLORS[round as usize % 2]).ok();
+                self.write_config();
+
+                set_br(SLOWEST_BR);
+                passed = !self.audit_config().any() && self.error_stats() == errors_before;
+                set_br(br);
+                if !passed {
+                    break;
+                }
+            }
+
+            info!("spi tune: {} Hz {=str}", sck_hz, if passed { "ok" } else { "failed" });
+            if !passed {
+                break;
+            }
+            fastest = Some(sck_hz);
+        }
+
+        set_br(original_br);
+        // leave the panel configured correctly even if the last round garbled it
+        self.write_config();
+
+        let sck_hz = fastest.ok_or(TuneError::Unreliable)?;
+        LinkConfig::new(pclk_hz).spi_clock(sck_hz).check().map_err(TuneError::Link)
+    }
+}
@@ -0,0 +1,169 @@
+use defmt::info;
+
+/// Receives a copy of everything the driver writes to display RAM.
+/// Set one with `ST7789V2DMA::set_capture()` to rebuild frames off the bus, e.g. to compare
+/// against golden images in tests or to dump the screen from a CI-on-hardware rig.
+pub trait FrameSink {
+    /// Called on every window update, with the logical (display space) inclusive bounds
+    /// that the following pixel bytes fill row by row.
+    fn window(&mut self, _xs: u16, _xe: u16, _ys: u16, _ye: u16) {}
+
+    /// Called with each chunk of RAMWR pixel bytes, exactly as sent (big-endian RGB565).
+    fn pixels(&mut self, bytes: &[u8]);
+}
+
+const FNV_OFFSET: u32 = 0x811C_9DC5;
+const FNV_PRIME: u32 = 0x0100_0193;
+
+/// `FrameSink` that folds windows and pixel bytes into a 32-bit FNV-1a hash, so on-target
+/// UI tests can assert a screen renders identically to a golden hash without a camera or a
+/// frame-sized buffer. `reset()` before drawing the screen, compare `frame_hash()` after the
+/// flush. Windows are hashed too: the same pixels drawn elsewhere give another hash.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FrameHasher {
+    hash: u32,
+    bytes: u32,
+}
+
+impl FrameHasher {
+    pub const fn new() -> Self {
+        Self { hash: FNV_OFFSET, bytes: 0 }
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::new();
+    }
+
+    /// Hash of everything sent since `new()` or the last `reset()`.
+    pub fn frame_hash(&self) -> u32 {
+        self.hash
+    }
+
+    /// Number of pixel bytes hashed, to tell "drew something else" from "drew nothing".
+    pub fn bytes(&self) -> u32 {
+        self.bytes
+    }
+
+    fn fold(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.hash = (self.hash ^ byte as u32).wrapping_mul(FNV_PRIME);
+        }
+    }
+}
+
+impl Default for FrameHasher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FrameSink for FrameHasher {
+    fn window(&mut self, xs: u16, xe: u16, ys: u16, ye: u16) {
+        for bound in [xs, xe, ys, ye] {
+            self.fold(&bound.to_be_bytes());
+        }
+    }
+
+    fn pixels(&mut self, bytes: &[u8]) {
+        self.fold(bytes);
+        self.bytes = self.bytes.wrapping_add(bytes.len() as u32);
+    }
+}
+
+/// `FrameSink` that measures how well the pixels being sent would compress, without
+/// changing what is sent, to judge whether compressed assets are worth it for the content.
+/// Two schemes are estimated per window:
+/// - RLE as `ImageFormat::Rle` stores it: 3 bytes per run of up to 255 equal pixels.
+/// - Delta to the previous pixel: 1 byte when red and blue move by at most 1 and green by at
+///   most 3, 3 bytes (escape and color) otherwise.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, defmt::Format)]
+pub struct CompressionStats {
+    pub raw_bytes: u32,
+    pub rle_bytes: u32,
+    pub delta_bytes: u32,
+    /// Color and length of the current run, `None` at the start of a window.
+    run: Option<(u16, u8)>,
+    /// High byte of a pixel split across two chunks.
+    pending: Option<u8>,
+}
+
+impl CompressionStats {
+    pub const fn new() -> Self {
+        Self { raw_bytes: 0, rle_bytes: 0, delta_bytes: 0, run: None, pending: None }
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::new();
+    }
+
+    /// RLE size as a percentage of the raw size (lower is better).
+    pub fn rle_percent(&self) -> u32 {
+        percent(self.rle_bytes, self.raw_bytes)
+    }
+
+    /// Delta size as a percentage of the raw size (lower is better).
+    pub fn delta_percent(&self) -> u32 {
+        percent(self.delta_bytes, self.raw_bytes)
+    }
+
+    /// Prints the totals and ratios via defmt.
+    pub fn log(&self) {
+        info!(
+            "compression: raw {=u32} B, rle {=u32} B ({=u32}%), delta {=u32} B ({=u32}%)",
+            self.raw_bytes,
+            self.rle_bytes,
+            self.rle_percent(),
+            self.delta_bytes,
+            self.delta_percent()
+        );
+    }
+
+    fn pixel(&mut self, color: u16) {
+        self.delta_bytes += match self.run {
+            Some((previous, _)) if small_step(previous, color) => 1,
+            _ => 3,
+        };
+        match self.run {
+            Some((previous, len)) if previous == color && len < u8::MAX => self.run = Some((color, len + 1)),
+            _ => {
+                self.rle_bytes += 3;
+                self.run = Some((color, 1));
+            }
+        }
+    }
+}
+
+impl FrameSink for CompressionStats {
+    fn window(&mut self, _xs: u16, _xe: u16, _ys: u16, _ye: u16) {
+        self.run = None;
+        self.pending = None;
+    }
+
+    fn pixels(&mut self, bytes: &[u8]) {
+        self.raw_bytes = self.raw_bytes.wrapping_add(bytes.len() as u32);
+        let mut bytes = bytes;
+        if let Some(high) = self.pending.take()
+            && let Some((&low, rest)) = bytes.split_first()
+        {
+            self.pixel(u16::from_be_bytes([high, low]));
+            bytes = rest;
+        }
+        let mut pixels = bytes.chunks_exact(2);
+        for pixel in &mut pixels {
+            self.pixel(u16::from_be_bytes([pixel[0], pixel[1]]));
+        }
+        self.pending = pixels.remainder().first().copied();
+    }
+}
+
+/// Whether `b` is within the 1-byte delta of `a`, see `CompressionStats`.
+fn small_step(a: u16, b: u16) -> bool {
+    let channel = |color: u16, shift: u16, mask: u16| ((color >> shift) & mask) as i16;
+    (channel(a, 11, 0x1F) - channel(b, 11, 0x1F)).abs() <= 1
+        && (channel(a, 5, 0x3F) - channel(b, 5, 0x3F)).abs() <= 3
+        && (channel(a, 0, 0x1F) - channel(b, 0, 0x1F)).abs() <= 1
+}
+
+fn percent(part: u32, whole: u32) -> u32 {
+    if whole == 0 { 100 } else { (part as u64 * 100 / whole as u64) as u32 }
+}
@@ -0,0 +1,81 @@
+/// Odds, per transfer, of each fault `Chaos` injects, in thousandths.
+/// The checks run in order (error, truncation, delay) and at most one fault hits a transfer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, defmt::Format)]
+pub struct ChaosConfig {
+    /// Transfer goes out but is counted as a DMA transfer error in `ErrorStats`.
+    pub error_per_mille: u16,
+    /// Transfer is cut short: pixel chunks lose their tail, command and parameter bytes are dropped.
+    pub truncate_per_mille: u16,
+    /// Transfer starts after a busy wait of up to `max_delay_us`.
+    pub delay_per_mille: u16,
+    pub max_delay_us: u32,
+}
+
+/// What happens to the next transfer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, defmt::Format)]
+pub enum Fault {
+    None,
+    Error,
+    /// Send only this many bytes; 0 drops the transfer.
+    Truncate(usize),
+    Delay(u32),
+}
+
+/// Seeded fault injector for the DMA driver's transfers, to exercise an application's
+/// recovery logic (reinit, `audit_config()`, retries) on hardware. The same seed and the same
+/// drawing produce the same faults, so a failing run can be repeated.
+/// Set one with `ST7789V2DMA::set_chaos()`. Test builds only, behind the `fault-injection` feature.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, defmt::Format)]
+pub struct Chaos {
+    config: ChaosConfig,
+    state: u32,
+    injected: u32,
+}
+
+impl Chaos {
+    pub const fn new(seed: u32, config: ChaosConfig) -> Self {
+        // xorshift never leaves 0
+        let state = if seed == 0 { 0x9E37_79B9 } else { seed };
+        Self { config, state, injected: 0 }
+    }
+
+    pub fn config(&self) -> ChaosConfig {
+        self.config
+    }
+
+    /// Number of faults injected so far.
+    pub fn injected(&self) -> u32 {
+        self.injected
+    }
+
+    /// Draws the fault for the next transfer of `len` bytes.
+    pub fn next_fault(&mut self, len: usize) -> Fault {
+        let fault = if self.roll(self.config.error_per_mille) {
+            Fault::Error
+        } else if self.roll(self.config.truncate_per_mille) {
+            Fault::Truncate(self.next() as usize % len.max(1))
+        } else if self.roll(self.config.delay_per_mille) {
+            Fault::Delay(self.next() % self.config.max_delay_us.max(1))
+        } else {
+            Fault::None
+        };
+
+        if fault != Fault::None {
+            self.injected = self.injected.wrapping_add(1);
+        }
+        fault
+    }
+
+    fn roll(&mut self, per_mille: u16) -> bool {
+        per_mille > 0 && self.next() % 1000 < per_mille as u32
+    }
+
+    fn next(&mut self) -> u32 {
+        let mut x = self.state;
+        x ^= x << 13;
+        x ^= x >> 17;
+        x ^= x << 5;
+        self.state = x;
+        x
+    }
+}
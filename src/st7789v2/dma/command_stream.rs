@@ -0,0 +1,137 @@
+use core::marker::PhantomData;
+use core::sync::atomic::{compiler_fence, Ordering};
+
+use embedded_dma::ReadBuffer;
+
+use crate::st7789v2::dma::{st7789v2dma::TxDmaRoute, stats::ErrorStats};
+use stm32f4xx_hal::{
+    dma::{
+        ChannelX, DmaDirection, DmaDataSize, StreamX,
+        config::Priority,
+        traits::{Channel, Stream},
+    },
+    rcc,
+    spi::Instance,
+    ClearFlags,
+};
+
+/// Transport for the small command and parameter transfers (cmd, data, CASET, RASET).
+/// Lets the driver keep those off the pixel stream, so the pixel stream's configuration
+/// doesn't have to be torn down and rebuilt between a window update and the following RAMWR.
+pub trait CommandStream {
+    /// Commands never use the pixel stream, so the driver keeps it set up between transfers.
+    const DEDICATED: bool = false;
+
+    /// Sends `buf` and hands it back along with the transfer error flag, recording errors in `stats`.
+    /// Returns `Err(buf)` untouched when there is no dedicated stream,
+    /// in which case the driver sends it on the pixel stream instead.
+    fn send<B>(&mut self, buf: B, stats: &mut ErrorStats) -> Result<(B, bool), B>
+    where
+        B: ReadBuffer<Word = u8> + 'static;
+}
+
+/// Default command transport: commands share the pixel stream.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SharedStream;
+
+impl CommandStream for SharedStream {
+    #[inline(always)]
+    fn send<B>(&mut self, buf: B, _stats: &mut ErrorStats) -> Result<(B, bool), B>
+    where
+        B: ReadBuffer<Word = u8> + 'static,
+    {
+        Err(buf)
+    }
+}
+
+/// A second DMA stream wired to the same SPI TX request, used only for commands and parameters.
+/// e.g. SPI1_TX is reachable from both DMA2 stream 3 and stream 5 on channel 3.
+/// The stream is set up once in `new()`; each send only loads the buffer address and length.
+pub struct DedicatedStream<SPI, DMA, const CHANNEL: u8, const S: u8>
+where
+    SPI: Instance + TxDmaRoute<StreamX<DMA, S>, CHANNEL>,
+    DMA: rcc::Enable + rcc::Reset,
+{
+    st: StreamX<DMA, S>,
+    _spi: PhantomData<SPI>,
+}
+
+impl<SPI, DMA, const CHANNEL: u8, const S: u8> DedicatedStream<SPI, DMA, CHANNEL, S>
+where
+    SPI: Instance + TxDmaRoute<StreamX<DMA, S>, CHANNEL>,
+    DMA: rcc::Enable + rcc::Reset + stm32f4xx_hal::dma::traits::Instance,
+    StreamX<DMA, S>: Stream,
+    ChannelX<CHANNEL>: Channel,
+{
+    /// Creates a command stream from a second DMA stream, byte-wide memory to SPI TX.
+    /// The SPI TX DMA request must already be enabled, i.e. the driver's `Tx<SPI>` was created;
+    /// `TxDmaRoute` checks at compile time that the stream and channel can reach it.
+    pub fn new(st: StreamX<DMA, S>) -> Self {
+        let mut stream = Self { st, _spi: PhantomData };
+        stream.stop();
+        let st = &mut stream.st;
+        st.clear_all_flags();
+        st.set_channel(ChannelX::<CHANNEL>::VALUE);
+        st.set_direction(DmaDirection::MemoryToPeripheral);
+        st.set_priority(Priority::Medium);
+        // SPI DR is accessed as a byte, the same size as the buffers
+        unsafe {
+            st.set_memory_size(DmaDataSize::Byte);
+            st.set_peripheral_size(DmaDataSize::Byte);
+        }
+        st.set_memory_increment(true);
+        st.set_peripheral_increment(false);
+        st.set_circular_mode(false);
+        st.set_double_buffer(false);
+        st.set_fifo_enable(false);
+        // SAFETY: only the address of the data register is taken
+        st.set_peripheral_address(unsafe { (*SPI::ptr()).dr().as_ptr() } as u32);
+
+        stream
+    }
+
+    /// Disables the stream and waits until it has stopped.
+    fn stop(&mut self) {
+        if self.st.is_enabled() {
+            // SAFETY: disabling only ends a transfer, the buffer isn't touched
+            unsafe { self.st.disable() };
+            while self.st.is_enabled() {}
+        }
+    }
+
+    /// Releases the DMA stream.
+    pub fn release(self) -> StreamX<DMA, S> {
+        self.st
+    }
+}
+
+impl<SPI, DMA, const CHANNEL: u8, const S: u8> CommandStream for DedicatedStream<SPI, DMA, CHANNEL, S>
+where
+    SPI: Instance + TxDmaRoute<StreamX<DMA, S>, CHANNEL>,
+    DMA: rcc::Enable + rcc::Reset + stm32f4xx_hal::dma::traits::Instance,
+    StreamX<DMA, S>: Stream,
+    ChannelX<CHANNEL>: Channel,
+{
+    const DEDICATED: bool = true;
+
+    fn send<B>(&mut self, buf: B, stats: &mut ErrorStats) -> Result<(B, bool), B>
+    where
+        B: ReadBuffer<Word = u8> + 'static,
+    {
+        // SAFETY: `buf` is owned here until the transfer is done, so the memory stays valid
+        let (ptr, len) = unsafe { buf.read_buffer() };
+        self.st.clear_all_flags();
+        self.st.set_memory_address(ptr as u32);
+        self.st.set_number_of_transfers(len as u16);
+
+        compiler_fence(Ordering::Release);
+        // SAFETY: the stream was set up in `new()` to feed SPI DR from `buf`, which outlives the transfer
+        unsafe { self.st.enable() };
+        let error = stats.wait(&self.st);
+        // a failed or timed out transfer may still be running, `buf` goes back only once it stopped
+        self.stop();
+        compiler_fence(Ordering::Acquire);
+
+        Ok((buf, error))
+    }
+}
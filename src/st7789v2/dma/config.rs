@@ -0,0 +1,292 @@
+use crate::st7789v2::common::{ColorOrder, Orientation, RAM_HEIGHT};
+use crate::st7789v2::dma::{command_stream::CommandStream, shadow::ShadowRegisters, st7789v2dma::{ST7789V2DMA, TxDmaRoute}};
+use crate::st7789v2::params::{check_max, ColorEnhancement, DigitalGamma, FrameRate, GammaCurves, ParamError, PartialArea, Porch, ScrollArea, DIGITAL_GAMMA_LEN};
+use crate::st7789v2::transform::Transform;
+#[cfg(feature = "config-loader")]
+use crate::st7789v2::loader::DisplayConfig;
+use stm32f4xx_hal::{
+    dma::{
+        traits::{Channel, Stream}, ChannelX, StreamX
+    },
+    hal::digital::OutputPin,
+    rcc,
+    spi::Instance,
+};
+
+pub const CONFIG_BLOB_LEN: usize = 184;
+const CONFIG_MAGIC: u8 = 0xC9;
+const CONFIG_VERSION: u8 = 3;
+/// Blob lengths of the earlier versions, still accepted by `ConfigBlob::from_bytes()`.
+const CONFIG_V1_LEN: usize = 23;
+const CONFIG_V2_LEN: usize = 24;
+
+// flag bits in byte 8
+const FLAG_MIRROR_X: u8 = 1 << 2;
+const FLAG_MIRROR_Y: u8 = 1 << 3;
+const FLAG_BGR: u8 = 1 << 4;
+const FLAG_INVERTED: u8 = 1 << 5;
+const FLAG_PARTIAL: u8 = 1 << 6;
+
+// flag bits in byte 23
+const FLAG_GAMMA: u8 = 1 << 0;
+const FLAG_DIGITAL_GAMMA: u8 = 1 << 1;
+
+// where the gamma tables start
+const GAMMA_AT: usize = 27;
+const DIGITAL_GAMMA_AT: usize = GAMMA_AT + 28;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, defmt::Format)]
+pub enum ConfigError {
+    /// Not a config blob, or one from an unknown format version or of the wrong length.
+    BadHeader,
+    /// The checksum doesn't match, e.g. a torn flash write.
+    Checksum,
+    /// The blob was exported from a panel with a different size or row offset.
+    PanelMismatch,
+    /// A stored parameter is out of range.
+    Param(ParamError),
+}
+
+impl From<ParamError> for ConfigError {
+    fn from(e: ParamError) -> Self {
+        ConfigError::Param(e)
+    }
+}
+
+/// Serialized driver configuration, a fixed-size byte blob to keep in EEPROM/flash.
+/// Layout (version 3, integers big-endian):
+/// magic, version, width (2), height (2), row offset (2), flags, brightness,
+/// scroll offset (2), scroll area top/scroll/bottom (6), partial start/end (4),
+/// color enhancement, gamma flags, FRCTRL2 rate, back/front porch (2),
+/// positive/negative gamma curves (28), red/blue digital gamma tables (128), checksum.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConfigBlob {
+    bytes: [u8; CONFIG_BLOB_LEN],
+}
+
+impl ConfigBlob {
+    /// Checks the header and checksum of bytes read back from storage.
+    /// Blobs of earlier versions are migrated: version 1 predates color enhancement, which
+    /// comes back off, and versions 1 and 2 carry no gamma, frame rate or porches, which come
+    /// back as the power-on defaults.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ConfigError> {
+        let len = match bytes {
+            [CONFIG_MAGIC, 1, ..] => CONFIG_V1_LEN,
+            [CONFIG_MAGIC, 2, ..] => CONFIG_V2_LEN,
+            [CONFIG_MAGIC, CONFIG_VERSION, ..] => CONFIG_BLOB_LEN,
+            _ => return Err(ConfigError::BadHeader),
+        };
+        if bytes.len() != len {
+            return Err(ConfigError::BadHeader);
+        }
+        if checksum(&bytes[..len - 1]) != bytes[len - 1] {
+            return Err(ConfigError::Checksum);
+        }
+        if len == CONFIG_BLOB_LEN {
+            return Ok(Self { bytes: bytes.try_into().unwrap() });
+        }
+
+        // the fields of earlier versions sit at the same offsets, the ones they lack follow
+        let mut migrated = [0u8; CONFIG_BLOB_LEN];
+        migrated[..len - 1].copy_from_slice(&bytes[..len - 1]);
+        migrated[1] = CONFIG_VERSION;
+        migrated[24] = FrameRate::HZ_60.param();
+        migrated[25] = Porch::DEFAULT.back();
+        migrated[26] = Porch::DEFAULT.front();
+        migrated[CONFIG_BLOB_LEN - 1] = checksum(&migrated[..CONFIG_BLOB_LEN - 1]);
+        Ok(Self { bytes: migrated })
+    }
+
+    pub fn as_bytes(&self) -> &[u8; CONFIG_BLOB_LEN] {
+        &self.bytes
+    }
+
+    fn u16_at(&self, i: usize) -> u16 {
+        u16::from_be_bytes([self.bytes[i], self.bytes[i + 1]])
+    }
+
+    fn array_at<const N: usize>(&self, i: usize) -> [u8; N] {
+        self.bytes[i..i + N].try_into().unwrap()
+    }
+}
+
+fn checksum(bytes: &[u8]) -> u8 {
+    // two's complement of the byte sum
+    bytes.iter().fold(0u8, |sum, b| sum.wrapping_add(*b)).wrapping_neg()
+}
+
+const fn enhancement_bits(enhancement: ColorEnhancement) -> u8 {
+    match enhancement {
+        ColorEnhancement::Off => 0,
+        ColorEnhancement::Low => 1,
+        ColorEnhancement::Medium => 2,
+        ColorEnhancement::High => 3,
+    }
+}
+
+const fn enhancement_from_bits(bits: u8) -> ColorEnhancement {
+    match bits & 0b11 {
+        0 => ColorEnhancement::Off,
+        1 => ColorEnhancement::Low,
+        2 => ColorEnhancement::Medium,
+        _ => ColorEnhancement::High,
+    }
+}
+
+const fn orientation_bits(orientation: Orientation) -> u8 {
+    match orientation {
+        Orientation::Portrait => 0,
+        Orientation::Landscape => 1,
+        Orientation::PortraitFlipped => 2,
+        Orientation::LandscapeFlipped => 3,
+    }
+}
+
+const fn orientation_from_bits(bits: u8) -> Orientation {
+    match bits & 0b11 {
+        0 => Orientation::Portrait,
+        1 => Orientation::Landscape,
+        2 => Orientation::PortraitFlipped,
+        _ => Orientation::LandscapeFlipped,
+    }
+}
+
+impl<'a, SPI, DMA, CS, DC, RST, const CHANNEL: u8, const S: u8, const W: usize, const H: usize, const OFFSET: usize, CMD>
+    ST7789V2DMA<'a, SPI, DMA, CS, DC, RST, CHANNEL, S, W, H, OFFSET, CMD>
+where
+    SPI: Instance + TxDmaRoute<StreamX<DMA, S>, CHANNEL>,
+    CS: OutputPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DMA: rcc::Enable + rcc::Reset + stm32f4xx_hal::dma::traits::Instance,
+    StreamX<DMA, S>: Stream,
+    ChannelX<CHANNEL>: Channel,
+    CMD: CommandStream,
+{
+    /// Serializes the current configuration (orientation, mirroring, color order, inversion,
+    /// brightness, scroll and partial areas, color enhancement, analog and digital gamma,
+    /// frame rate and porches) along with the panel geometry it applies to.
+    pub fn export_config(&self) -> ConfigBlob {
+        let shadow = &self.shadow;
+        let mut bytes = [0u8; CONFIG_BLOB_LEN];
+        bytes[0] = CONFIG_MAGIC;
+        bytes[1] = CONFIG_VERSION;
+        bytes[2..4].copy_from_slice(&(W as u16).to_be_bytes());
+        bytes[4..6].copy_from_slice(&(H as u16).to_be_bytes());
+        bytes[6..8].copy_from_slice(&(OFFSET as u16).to_be_bytes());
+
+        let mut flags = orientation_bits(shadow.transform.orientation);
+        if shadow.transform.mirror_x {
+            flags |= FLAG_MIRROR_X;
+        }
+        if shadow.transform.mirror_y {
+            flags |= FLAG_MIRROR_Y;
+        }
+        if shadow.color_order == ColorOrder::Bgr {
+            flags |= FLAG_BGR;
+        }
+        if shadow.inverted {
+            flags |= FLAG_INVERTED;
+        }
+        if shadow.partial_area.is_some() {
+            flags |= FLAG_PARTIAL;
+        }
+        bytes[8] = flags;
+        bytes[9] = shadow.brightness;
+        bytes[10..12].copy_from_slice(&shadow.scroll_offset.to_be_bytes());
+        bytes[12..18].copy_from_slice(&shadow.scroll_area.params());
+        if let Some(area) = shadow.partial_area {
+            bytes[18..22].copy_from_slice(&area.params());
+        }
+        bytes[22] = enhancement_bits(shadow.color_enhancement);
+
+        let mut gamma_flags = 0;
+        if let Some(gamma) = shadow.gamma {
+            gamma_flags |= FLAG_GAMMA;
+            bytes[GAMMA_AT..GAMMA_AT + 14].copy_from_slice(&gamma.positive);
+            bytes[GAMMA_AT + 14..DIGITAL_GAMMA_AT].copy_from_slice(&gamma.negative);
+        }
+        if let Some(gamma) = shadow.digital_gamma {
+            gamma_flags |= FLAG_DIGITAL_GAMMA;
+            bytes[DIGITAL_GAMMA_AT..DIGITAL_GAMMA_AT + DIGITAL_GAMMA_LEN].copy_from_slice(&gamma.red);
+            bytes[DIGITAL_GAMMA_AT + DIGITAL_GAMMA_LEN..CONFIG_BLOB_LEN - 1].copy_from_slice(&gamma.blue);
+        }
+        bytes[23] = gamma_flags;
+        bytes[24] = shadow.frame_rate.param();
+        bytes[25] = shadow.porch.back();
+        bytes[26] = shadow.porch.front();
+        bytes[CONFIG_BLOB_LEN - 1] = checksum(&bytes[..CONFIG_BLOB_LEN - 1]);
+
+        ConfigBlob { bytes }
+    }
+
+    /// Restores a configuration saved with `export_config()` and writes it to the panel.
+    /// Nothing changes if the blob belongs to a different panel geometry or holds invalid values.
+    pub fn apply_config(&mut self, blob: &ConfigBlob) -> Result<(), ConfigError> {
+        if blob.u16_at(2) as usize != W || blob.u16_at(4) as usize != H || blob.u16_at(6) as usize != OFFSET {
+            return Err(ConfigError::PanelMismatch);
+        }
+
+        let flags = blob.bytes[8];
+        let partial_area = if flags & FLAG_PARTIAL != 0 {
+            Some(PartialArea::new(blob.u16_at(18), blob.u16_at(20))?)
+        } else {
+            None
+        };
+        let gamma_flags = blob.bytes[23];
+        let gamma = (gamma_flags & FLAG_GAMMA != 0).then(|| GammaCurves {
+            positive: blob.array_at(GAMMA_AT),
+            negative: blob.array_at(GAMMA_AT + 14),
+        });
+        let digital_gamma = (gamma_flags & FLAG_DIGITAL_GAMMA != 0).then(|| DigitalGamma {
+            red: blob.array_at(DIGITAL_GAMMA_AT),
+            blue: blob.array_at(DIGITAL_GAMMA_AT + DIGITAL_GAMMA_LEN),
+        });
+
+        self.shadow = ShadowRegisters {
+            transform: Transform::new(
+                orientation_from_bits(flags),
+                flags & FLAG_MIRROR_X != 0,
+                flags & FLAG_MIRROR_Y != 0,
+            ),
+            color_order: if flags & FLAG_BGR != 0 { ColorOrder::Bgr } else { ColorOrder::Rgb },
+            inverted: flags & FLAG_INVERTED != 0,
+            brightness: blob.bytes[9],
+            scroll_offset: check_max(blob.u16_at(10), RAM_HEIGHT as u16 - 1)?,
+            scroll_area: ScrollArea::new(blob.u16_at(12), blob.u16_at(14), blob.u16_at(16))?,
+            partial_area,
+            color_enhancement: enhancement_from_bits(blob.bytes[22]),
+            gamma,
+            digital_gamma,
+            frame_rate: FrameRate::from_param(blob.bytes[24])?,
+            porch: Porch::new(blob.bytes[25], blob.bytes[26])?,
+            ..self.shadow
+        };
+        self.write_config();
+
+        Ok(())
+    }
+
+    /// Applies a `DisplayConfig` loaded from flash and writes it to the panel.
+    /// Scroll and partial areas are left as they are.
+    #[cfg(feature = "config-loader")]
+    pub fn apply_display_config(&mut self, config: &DisplayConfig) -> Result<(), ConfigError> {
+        if config.width as usize != W || config.height as usize != H || config.row_offset as usize != OFFSET {
+            return Err(ConfigError::PanelMismatch);
+        }
+
+        self.shadow = ShadowRegisters {
+            transform: Transform::new(config.orientation, config.mirror_x, config.mirror_y),
+            color_order: config.color_order,
+            inverted: config.inverted,
+            brightness: config.brightness,
+            gamma: config.gamma,
+            digital_gamma: config.digital_gamma,
+            color_enhancement: config.color_enhancement,
+            ..self.shadow
+        };
+        self.write_config();
+
+        Ok(())
+    }
+}
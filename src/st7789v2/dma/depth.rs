@@ -0,0 +1,133 @@
+use crate::st7789v2::clip::Window;
+use crate::st7789v2::common::ColorMode;
+use crate::st7789v2::dma::{command_stream::CommandStream, st7789v2dma::{ST7789V2DMA, TxDmaRoute}};
+use embedded_graphics::{pixelcolor::Rgb666, prelude::*, primitives::Rectangle};
+use stm32f4xx_hal::{
+    dma::{
+        traits::{Channel, Stream}, ChannelX, StreamX
+    },
+    hal::digital::OutputPin,
+    rcc,
+    spi::Instance,
+};
+
+/// DrawTarget with 18-bit color (262K colors), for gradients and photos that band in RGB565.
+/// Switches the panel to 18-bit COLMOD while it lives and back to RGB565 when dropped, so the
+/// pixel format sent always matches the one the panel expects: the RGB565 DrawTarget can't be
+/// used while this borrow is held. Every pixel takes 3 bytes on the wire instead of 2, so full
+/// frames are 50% slower. The row hook and color filter don't apply, and a `FrameSink` sees
+/// 3 bytes per pixel.
+pub struct Rgb666View<'d, 'a, SPI, DMA, CS, DC, RST, const CHANNEL: u8, const S: u8, const W: usize, const H: usize, const OFFSET: usize, CMD>
+where
+    SPI: Instance + TxDmaRoute<StreamX<DMA, S>, CHANNEL>,
+    CS: OutputPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DMA: rcc::Enable + rcc::Reset + stm32f4xx_hal::dma::traits::Instance,
+    StreamX<DMA, S>: Stream,
+    ChannelX<CHANNEL>: Channel,
+    CMD: CommandStream,
+{
+    display: &'d mut ST7789V2DMA<'a, SPI, DMA, CS, DC, RST, CHANNEL, S, W, H, OFFSET, CMD>,
+}
+
+impl<'a, SPI, DMA, CS, DC, RST, const CHANNEL: u8, const S: u8, const W: usize, const H: usize, const OFFSET: usize, CMD>
+    ST7789V2DMA<'a, SPI, DMA, CS, DC, RST, CHANNEL, S, W, H, OFFSET, CMD>
+where
+    SPI: Instance + TxDmaRoute<StreamX<DMA, S>, CHANNEL>,
+    CS: OutputPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DMA: rcc::Enable + rcc::Reset + stm32f4xx_hal::dma::traits::Instance,
+    StreamX<DMA, S>: Stream,
+    ChannelX<CHANNEL>: Channel,
+    CMD: CommandStream,
+{
+    /// Borrows the driver as an 18-bit DrawTarget, see `Rgb666View`.
+    pub fn rgb666(&mut self) -> Rgb666View<'_, 'a, SPI, DMA, CS, DC, RST, CHANNEL, S, W, H, OFFSET, CMD> {
+        self.set_color_mode(ColorMode::RGB666);
+        Rgb666View { display: self }
+    }
+}
+
+impl<SPI, DMA, CS, DC, RST, const CHANNEL: u8, const S: u8, const W: usize, const H: usize, const OFFSET: usize, CMD> Drop for
+    Rgb666View<'_, '_, SPI, DMA, CS, DC, RST, CHANNEL, S, W, H, OFFSET, CMD>
+where
+    SPI: Instance + TxDmaRoute<StreamX<DMA, S>, CHANNEL>,
+    CS: OutputPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DMA: rcc::Enable + rcc::Reset + stm32f4xx_hal::dma::traits::Instance,
+    StreamX<DMA, S>: Stream,
+    ChannelX<CHANNEL>: Channel,
+    CMD: CommandStream,
+{
+    fn drop(&mut self) {
+        self.display.set_color_mode(ColorMode::RGB565);
+    }
+}
+
+impl<SPI, DMA, CS, DC, RST, const CHANNEL: u8, const S: u8, const W: usize, const H: usize, const OFFSET: usize, CMD> Dimensions for
+    Rgb666View<'_, '_, SPI, DMA, CS, DC, RST, CHANNEL, S, W, H, OFFSET, CMD>
+where
+    SPI: Instance + TxDmaRoute<StreamX<DMA, S>, CHANNEL>,
+    CS: OutputPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DMA: rcc::Enable + rcc::Reset + stm32f4xx_hal::dma::traits::Instance,
+    StreamX<DMA, S>: Stream,
+    ChannelX<CHANNEL>: Channel,
+    CMD: CommandStream,
+{
+    fn bounding_box(&self) -> Rectangle {
+        self.display.bounding_box()
+    }
+}
+
+impl<SPI, DMA, CS, DC, RST, const CHANNEL: u8, const S: u8, const W: usize, const H: usize, const OFFSET: usize, CMD> DrawTarget for
+    Rgb666View<'_, '_, SPI, DMA, CS, DC, RST, CHANNEL, S, W, H, OFFSET, CMD>
+where
+    SPI: Instance + TxDmaRoute<StreamX<DMA, S>, CHANNEL>,
+    CS: OutputPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DMA: rcc::Enable + rcc::Reset + stm32f4xx_hal::dma::traits::Instance,
+    StreamX<DMA, S>: Stream,
+    ChannelX<CHANNEL>: Channel,
+    CMD: CommandStream,
+{
+    type Color = Rgb666;
+    type Error = core::convert::Infallible;
+
+    fn fill_contiguous<I>(&mut self, area: &Rectangle, colors: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Self::Color>,
+    {
+        let Some(window) = Window::clip(area, &self.bounding_box()) else {
+            return Ok(());
+        };
+
+        let colors = window.visible_colors(area, colors);
+        self.display.write_region_rgb666(window.xs, window.xe, window.ys, window.ye, colors);
+
+        Ok(())
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        self.fill_contiguous(area, core::iter::repeat(color))
+    }
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let bounds = self.bounding_box();
+        for Pixel(point, color) in pixels {
+            if bounds.contains(point) {
+                self.fill_solid(&Rectangle::new(point, Size::new(1, 1)), color)?;
+            }
+        }
+
+        Ok(())
+    }
+}
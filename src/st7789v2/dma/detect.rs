@@ -0,0 +1,86 @@
+use crate::st7789v2::{
+    common::{ColorOrder, Commands},
+    params::{ColorEnhancement, DigitalGamma},
+    dma::{command_stream::CommandStream, st7789v2dma::{ST7789V2DMA, TxDmaRoute}},
+};
+use stm32f4xx_hal::{
+    dma::{
+        ChannelX, StreamX,
+        traits::{Channel, Stream},
+    },
+    hal::digital::OutputPin,
+    rcc,
+    spi::Instance,
+};
+
+/// Known module settings, keyed by the RDID1/RDID2/RDID3 bytes the controller reports.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PanelPreset {
+    pub name: &'static str,
+    /// Manufacturer, version and driver ID (RDID1, RDID2, RDID3).
+    pub id: [u8; 3],
+    pub width: u16,
+    pub height: u16,
+    /// First visible RAM row, the driver's OFFSET.
+    pub row_offset: u16,
+    pub inverted: bool,
+    pub color_order: ColorOrder,
+    pub color_enhancement: ColorEnhancement,
+    /// Vendor calibration tables, uploaded and enabled when present.
+    pub digital_gamma: Option<DigitalGamma>,
+}
+
+/// Built-in presets checked by `detect_panel()`.
+/// The ID only names the controller, so each entry is the module most commonly sold with it.
+/// Only ST7789V2 modules belong here: the driver speaks this controller's command set.
+pub const PANEL_PRESETS: &[PanelPreset] = &[
+    PanelPreset {
+        name: "ST7789V2 240x280 (1.69\")",
+        id: [0x85, 0x85, 0x52],
+        width: 240,
+        height: 280,
+        row_offset: 20,
+        inverted: true,
+        color_order: ColorOrder::Rgb,
+        color_enhancement: ColorEnhancement::Off,
+        digital_gamma: None,
+    },
+];
+
+impl<'a, SPI, DMA, CS, DC, RST, const CHANNEL: u8, const S: u8, const W: usize, const H: usize, const OFFSET: usize, CMD>
+    ST7789V2DMA<'a, SPI, DMA, CS, DC, RST, CHANNEL, S, W, H, OFFSET, CMD>
+where
+    SPI: Instance + TxDmaRoute<StreamX<DMA, S>, CHANNEL>,
+    CS: OutputPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DMA: rcc::Enable + rcc::Reset + stm32f4xx_hal::dma::traits::Instance,
+    StreamX<DMA, S>: Stream,
+    ChannelX<CHANNEL>: Channel,
+    CMD: CommandStream,
+{
+    /// Reads the controller ID (RDID1-3) and returns the matching entry of `PANEL_PRESETS`,
+    /// or `default` when the ID is unknown or unreadable (no MISO wired reads as all 0x00/0xFF).
+    /// The preset's inversion, color order, color enhancement and digital gamma are applied; its size and row offset are only
+    /// reported, since they are fixed by the driver's W, H and OFFSET parameters.
+    pub fn detect_panel(&mut self, default: &'static PanelPreset) -> &'static PanelPreset {
+        let id = [
+            self.read_register(Commands::ReadId1),
+            self.read_register(Commands::ReadId2),
+            self.read_register(Commands::ReadId3),
+        ];
+
+        let readable = id != [0x00; 3] && id != [0xFF; 3];
+        let preset = PANEL_PRESETS
+            .iter()
+            .find(|preset| readable && preset.id == id)
+            .unwrap_or(default);
+
+        self.set_inversion(preset.inverted);
+        self.set_color_order(preset.color_order);
+        self.set_color_enhancement(preset.color_enhancement);
+        self.set_digital_gamma(preset.digital_gamma);
+
+        preset
+    }
+}
@@ -0,0 +1,163 @@
+use crate::st7789v2::clip::Window;
+use crate::st7789v2::common::RAM_HEIGHT;
+use crate::st7789v2::dma::{command_stream::CommandStream, st7789v2dma::{ST7789V2DMA, TxDmaRoute}};
+use embedded_graphics::{pixelcolor::Rgb565, prelude::*, primitives::Rectangle};
+use stm32f4xx_hal::{
+    dma::{
+        traits::{Channel, Stream}, ChannelX, StreamX
+    },
+    hal::digital::OutputPin,
+    rcc,
+    spi::Instance,
+};
+
+/// Widest logical row a `DoubledView` can have: half the panel RAM's long side.
+const MAX_LOGICAL_WIDTH: usize = RAM_HEIGHT / 2;
+
+/// DrawTarget at half the resolution in each direction (120 x 140 on the 240 x 280 glass),
+/// every logical pixel sent as a 2 x 2 block while streaming. Procedural content computes a
+/// quarter of the pixels and a `Framebuffer<120, 140>` takes a quarter of the RAM, while the
+/// whole panel is still filled; the wire carries the full pixel count. Follows the rotation,
+/// and odd glass sizes leave the last physical row or column untouched.
+pub struct DoubledView<'d, 'a, SPI, DMA, CS, DC, RST, const CHANNEL: u8, const S: u8, const W: usize, const H: usize, const OFFSET: usize, CMD>
+where
+    SPI: Instance + TxDmaRoute<StreamX<DMA, S>, CHANNEL>,
+    CS: OutputPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DMA: rcc::Enable + rcc::Reset + stm32f4xx_hal::dma::traits::Instance,
+    StreamX<DMA, S>: Stream,
+    ChannelX<CHANNEL>: Channel,
+    CMD: CommandStream,
+{
+    display: &'d mut ST7789V2DMA<'a, SPI, DMA, CS, DC, RST, CHANNEL, S, W, H, OFFSET, CMD>,
+}
+
+impl<'a, SPI, DMA, CS, DC, RST, const CHANNEL: u8, const S: u8, const W: usize, const H: usize, const OFFSET: usize, CMD>
+    ST7789V2DMA<'a, SPI, DMA, CS, DC, RST, CHANNEL, S, W, H, OFFSET, CMD>
+where
+    SPI: Instance + TxDmaRoute<StreamX<DMA, S>, CHANNEL>,
+    CS: OutputPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DMA: rcc::Enable + rcc::Reset + stm32f4xx_hal::dma::traits::Instance,
+    StreamX<DMA, S>: Stream,
+    ChannelX<CHANNEL>: Channel,
+    CMD: CommandStream,
+{
+    /// Borrows the driver as a half-resolution DrawTarget, see `DoubledView`.
+    pub fn doubled(&mut self) -> DoubledView<'_, 'a, SPI, DMA, CS, DC, RST, CHANNEL, S, W, H, OFFSET, CMD> {
+        DoubledView { display: self }
+    }
+}
+
+impl<SPI, DMA, CS, DC, RST, const CHANNEL: u8, const S: u8, const W: usize, const H: usize, const OFFSET: usize, CMD> Dimensions for
+    DoubledView<'_, '_, SPI, DMA, CS, DC, RST, CHANNEL, S, W, H, OFFSET, CMD>
+where
+    SPI: Instance + TxDmaRoute<StreamX<DMA, S>, CHANNEL>,
+    CS: OutputPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DMA: rcc::Enable + rcc::Reset + stm32f4xx_hal::dma::traits::Instance,
+    StreamX<DMA, S>: Stream,
+    ChannelX<CHANNEL>: Channel,
+    CMD: CommandStream,
+{
+    fn bounding_box(&self) -> Rectangle {
+        let size = self.display.bounding_box().size;
+        Rectangle::new(Point::zero(), size / 2)
+    }
+}
+
+impl<SPI, DMA, CS, DC, RST, const CHANNEL: u8, const S: u8, const W: usize, const H: usize, const OFFSET: usize, CMD> DrawTarget for
+    DoubledView<'_, '_, SPI, DMA, CS, DC, RST, CHANNEL, S, W, H, OFFSET, CMD>
+where
+    SPI: Instance + TxDmaRoute<StreamX<DMA, S>, CHANNEL>,
+    CS: OutputPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DMA: rcc::Enable + rcc::Reset + stm32f4xx_hal::dma::traits::Instance,
+    StreamX<DMA, S>: Stream,
+    ChannelX<CHANNEL>: Channel,
+    CMD: CommandStream,
+{
+    type Color = Rgb565;
+    type Error = core::convert::Infallible;
+
+    fn fill_contiguous<I>(&mut self, area: &Rectangle, colors: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Self::Color>,
+    {
+        let Some(window) = Window::clip(area, &self.bounding_box()) else {
+            return Ok(());
+        };
+
+        let colors = Doubled::new(window.visible_colors(area, colors), window.width());
+        let (xs, ys) = (window.xs * 2, window.ys * 2);
+        self.display.write_region(xs, xs + window.width() as u16 * 2 - 1, ys, ys + window.height() as u16 * 2 - 1, colors);
+
+        Ok(())
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        let Some(window) = Window::clip(area, &self.bounding_box()) else {
+            return Ok(());
+        };
+
+        // a solid block needs no row buffer, fill the physical area directly
+        let physical = Rectangle::new(window.rectangle().top_left * 2, window.rectangle().size * 2);
+        self.display.fill_solid(&physical, color)
+    }
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let bounds = self.bounding_box();
+        for Pixel(point, color) in pixels {
+            if bounds.contains(point) {
+                self.fill_solid(&Rectangle::new(point, Size::new(1, 1)), color)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Expands logical rows `width` pixels wide into physical ones: each pixel twice, each
+/// row twice. Holds one logical row.
+struct Doubled<I> {
+    colors: I,
+    row: [Rgb565; MAX_LOGICAL_WIDTH],
+    width: usize,
+    /// Position within the current pair of physical rows, `4 * width` when a new row is due.
+    pos: usize,
+}
+
+impl<I: Iterator<Item = Rgb565>> Doubled<I> {
+    fn new(colors: I, width: usize) -> Self {
+        let width = width.min(MAX_LOGICAL_WIDTH);
+        Self {
+            colors,
+            row: [Rgb565::BLACK; MAX_LOGICAL_WIDTH],
+            width,
+            pos: 4 * width,
+        }
+    }
+}
+
+impl<I: Iterator<Item = Rgb565>> Iterator for Doubled<I> {
+    type Item = Rgb565;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.pos == 4 * self.width {
+            for pixel in &mut self.row[..self.width] {
+                *pixel = self.colors.next()?;
+            }
+            self.pos = 0;
+        }
+        let column = self.pos % (2 * self.width) / 2;
+        self.pos += 1;
+        Some(self.row[column])
+    }
+}
@@ -1,41 +1,48 @@
-use crate::st7789v2::dma::st7789v2dma::ST7789V2DMA;
-use embedded_graphics::{pixelcolor::{raw::ToBytes, Rgb565}, prelude::{Dimensions, DrawTarget, OriginDimensions, Size}};
+use crate::st7789v2::clip::Window;
+use crate::st7789v2::common::{Madctl, RAM_HEIGHT};
+use crate::st7789v2::params::ScrollArea;
+use crate::st7789v2::ui::menu::VerticalScroll;
+use crate::st7789v2::dma::{command_stream::CommandStream, st7789v2dma::{ST7789V2DMA, TxDmaRoute}};
+use embedded_graphics::{pixelcolor::Rgb565, prelude::{Dimensions, DrawTarget, OriginDimensions, Point, Size}, primitives::Rectangle, Pixel};
 use stm32f4xx_hal::{
     dma::{
-        traits::{Channel, DMASet, Stream}, ChannelX, MemoryToPeripheral, StreamX
+        traits::{Channel, Stream}, ChannelX, StreamX
     },
     hal::digital::OutputPin,
     rcc,
     spi::Instance,
 };
 
-impl<'a, SPI, DMA, CS, DC, RST, const CHANNEL: u8, const S: u8, const W: usize, const H: usize, const OFFSET: usize> OriginDimensions for
-    ST7789V2DMA<'a, SPI, DMA, CS, DC, RST, CHANNEL, S, W, H, OFFSET>
+impl<'a, SPI, DMA, CS, DC, RST, const CHANNEL: u8, const S: u8, const W: usize, const H: usize, const OFFSET: usize, CMD> OriginDimensions for
+    ST7789V2DMA<'a, SPI, DMA, CS, DC, RST, CHANNEL, S, W, H, OFFSET, CMD>
 where
-    SPI: Instance + DMASet<StreamX<DMA, S>, CHANNEL, MemoryToPeripheral>,
+    SPI: Instance + TxDmaRoute<StreamX<DMA, S>, CHANNEL>,
     CS: OutputPin,
     DC: OutputPin,
     RST: OutputPin,
     DMA: rcc::Enable + rcc::Reset + stm32f4xx_hal::dma::traits::Instance,
     StreamX<DMA, S>: Stream,
-    ChannelX<CHANNEL>: Channel
+    ChannelX<CHANNEL>: Channel,
+    CMD: CommandStream,
 {
     fn size(&self) -> embedded_graphics::prelude::Size {
-        Size::new(W as u32, H as u32)
+        let (width, height) = self.shadow.transform.size::<W, H>();
+        Size::new(width as u32, height as u32)
     }
 }
 
 
-impl<'a, SPI, DMA, CS, DC, RST, const CHANNEL: u8, const S: u8, const W: usize, const H: usize, const OFFSET: usize> DrawTarget for
-    ST7789V2DMA<'a, SPI, DMA, CS, DC, RST, CHANNEL, S, W, H, OFFSET>
+impl<'a, SPI, DMA, CS, DC, RST, const CHANNEL: u8, const S: u8, const W: usize, const H: usize, const OFFSET: usize, CMD> DrawTarget for
+    ST7789V2DMA<'a, SPI, DMA, CS, DC, RST, CHANNEL, S, W, H, OFFSET, CMD>
 where
-    SPI: Instance + DMASet<StreamX<DMA, S>, CHANNEL, MemoryToPeripheral>,
+    SPI: Instance + TxDmaRoute<StreamX<DMA, S>, CHANNEL>,
     CS: OutputPin,
     DC: OutputPin,
     RST: OutputPin,
     DMA: rcc::Enable + rcc::Reset + stm32f4xx_hal::dma::traits::Instance,
     StreamX<DMA, S>: Stream,
     ChannelX<CHANNEL>: Channel,
+    CMD: CommandStream,
 {
 
     type Color = Rgb565;
@@ -49,48 +56,11 @@ where
     where
         I: IntoIterator<Item = Self::Color>,
     {
-        let drawable_area = area.intersection(&self.bounding_box());
-        let (startx, starty) = drawable_area.top_left.into();
-        let (width, height) = drawable_area.size.into();
-        let endx = startx + width as i32 - 1;
-        let endy = starty + height as i32 - 1;
-
-        // Take ownership of the buffer for this call
-        let mut chunk_buffer = self.chunk_buffer.take().unwrap();
-        let buf_len = chunk_buffer.len();
-
-        let mut idx = 0;
-
-        let mut clrs = colors.into_iter();
-
-        // Prepare LCD for drawing
-        self.set_size(startx as u16, endx as u16, starty as u16, endy as u16);
-        self.begin_draw();
-        self.dc.set_high().ok();
-        self.select();
-
-        for _ in 0..(width * height) {
-            if idx + 2 > buf_len {
-                chunk_buffer = self.send_data_chunk(chunk_buffer);
-                idx = 0;
-            }
-            let color_bytes = clrs.next().unwrap().to_be_bytes();
-            chunk_buffer[idx] = color_bytes[0];
-            chunk_buffer[idx + 1] = color_bytes[1];
-            idx += 2;
-        }
-
-        // Flush remaining bytes if needed
-        {
-            if idx > 0 {
-                chunk_buffer = self.send_data_chunk(chunk_buffer);
-            };
-        }
-
-        self.deselect();
+        let Some(window) = Window::clip(area, &self.bounding_box()) else {
+            return Ok(());
+        };
 
-        // Put the buffer back for reuse
-        self.chunk_buffer = Some(chunk_buffer);
+        self.write_region(window.xs, window.xe, window.ys, window.ye, window.visible_colors(area, colors));
 
         Ok(())
     }
@@ -99,6 +69,8 @@ where
         self.fill_contiguous(area, core::iter::repeat(color))
     }
 
+    /// Fills exactly the glass: `bounding_box()` follows the rotation and `begin_region()` maps it
+    /// past OFFSET and the column offset, so the rows hidden above the glass are never drawn.
     fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
         self.fill_solid(&self.bounding_box(), color)
     }
@@ -107,7 +79,146 @@ where
     where
         I: IntoIterator<Item = embedded_graphics::Pixel<Self::Color>> {
 
-        unimplemented!("DMA doesnt support drawing individual pixels")
+        // consecutive pixels are gathered into row runs and stacked runs of equal width
+        // into rectangles, so each window covers as many pixels as possible
+        let bounds = self.bounding_box();
+        let mut block = Block::new();
+        for Pixel(point, color) in pixels {
+            if !bounds.contains(point) {
+                continue;
+            }
+            if !block.push(point, color) {
+                block.flush(self)?;
+                block.push(point, color);
+            }
+        }
+
+        block.flush(self)
+    }
+}
+
+/// Scrolls through VSCRDEF/VSCSAD. Those work on the panel's gate lines, so the band only maps
+/// onto display rows while RAM rows run top to bottom: portrait, not flipped vertically.
+impl<SPI, DMA, CS, DC, RST, const CHANNEL: u8, const S: u8, const W: usize, const H: usize, const OFFSET: usize, CMD> VerticalScroll for
+    ST7789V2DMA<'_, SPI, DMA, CS, DC, RST, CHANNEL, S, W, H, OFFSET, CMD>
+where
+    SPI: Instance + TxDmaRoute<StreamX<DMA, S>, CHANNEL>,
+    CS: OutputPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DMA: rcc::Enable + rcc::Reset + stm32f4xx_hal::dma::traits::Instance,
+    StreamX<DMA, S>: Stream,
+    ChannelX<CHANNEL>: Channel,
+    CMD: CommandStream,
+{
+    fn set_scroll_band(&mut self, top: u32, height: u32) -> bool {
+        let madctl = self.shadow.transform.madctl();
+        if madctl.contains(Madctl::MV) || madctl.contains(Madctl::MY) || height == 0 || top + height > H as u32 {
+            return false;
+        }
 
+        let top_fixed = self.ram_offset().1 + top as u16;
+        let bottom_fixed = RAM_HEIGHT as u16 - top_fixed - height as u16;
+        match ScrollArea::new(top_fixed, height as u16, bottom_fixed) {
+            Ok(area) => {
+                self.set_scroll_area(area);
+                true
+            }
+            Err(_) => false,
+        }
     }
-}
\ No newline at end of file
+
+    fn scroll_band(&mut self, offset: u32) {
+        let area = self.scroll_area();
+        if area.scroll() == 0 {
+            return;
+        }
+        let start = area.top_fixed() + (offset % area.scroll() as u32) as u16;
+        if start != self.scroll_offset() {
+            // within the RAM lines, set_scroll_area() checked the band
+            self.set_scroll_offset(start).ok();
+        }
+    }
+
+    fn reset_scroll(&mut self) {
+        self.set_scroll_area(ScrollArea::FULL);
+        self.set_scroll_offset(0).ok();
+    }
+}
+
+/// Pixels buffered by `draw_iter()` before they're sent as one window.
+pub const COALESCE_PIXELS: usize = 256;
+
+/// A rectangle being filled in row-major order by `draw_iter()`.
+struct Block {
+    origin: Point,
+    /// Row width, known once the second row starts.
+    width: Option<u32>,
+    colors: [Rgb565; COALESCE_PIXELS],
+    len: usize,
+}
+
+impl Block {
+    fn new() -> Self {
+        Self {
+            origin: Point::zero(),
+            width: None,
+            colors: [Rgb565::new(0, 0, 0); COALESCE_PIXELS],
+            len: 0,
+        }
+    }
+
+    /// Appends the pixel if it's the next one of the block.
+    /// Returns false if it isn't, or the buffer is full; the block must be flushed then.
+    fn push(&mut self, point: Point, color: Rgb565) -> bool {
+        if self.len == 0 {
+            self.origin = point;
+            self.width = None;
+        } else if self.len == COALESCE_PIXELS {
+            return false;
+        } else {
+            let len = self.len as i32;
+            let next = match self.width {
+                Some(width) => self.origin + Point::new(len % width as i32, len / width as i32),
+                None if point == self.origin + Point::new(0, 1) => {
+                    // the first row ended, the block becomes a rectangle of its width
+                    self.width = Some(self.len as u32);
+                    point
+                }
+                None => self.origin + Point::new(len, 0),
+            };
+            if point != next {
+                return false;
+            }
+        }
+
+        self.colors[self.len] = color;
+        self.len += 1;
+        true
+    }
+
+    /// Sends the complete rows as one window and a trailing partial row as another.
+    fn flush<D>(&mut self, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Rgb565>,
+    {
+        let width = self.width.unwrap_or(self.len as u32) as usize;
+        if width == 0 {
+            return Ok(());
+        }
+        let rows = self.len / width;
+        let full = rows * width;
+
+        if rows > 0 {
+            let area = Rectangle::new(self.origin, Size::new(width as u32, rows as u32));
+            target.fill_contiguous(&area, self.colors[..full].iter().copied())?;
+        }
+        if full < self.len {
+            let area = Rectangle::new(self.origin + Point::new(0, rows as i32), Size::new((self.len - full) as u32, 1));
+            target.fill_contiguous(&area, self.colors[full..self.len].iter().copied())?;
+        }
+
+        self.len = 0;
+        Ok(())
+    }
+}
@@ -0,0 +1,212 @@
+use crate::st7789v2::dma::{
+    command_stream::CommandStream,
+    st7789v2dma::{ST7789V2DMA, MAX_TRANSFER, PixelRun, TxDmaRoute, WindowError},
+};
+use embedded_dma::ReadBuffer;
+use embedded_graphics::primitives::Rectangle;
+use stm32f4xx_hal::{
+    dma::{
+        traits::{Channel, Stream}, ChannelX, StreamX
+    },
+    hal::digital::OutputPin,
+    rcc,
+    spi::Instance,
+};
+
+/// A pixel transfer running in the background, returned by `start_frame_transfer()`.
+/// The DMA moves the buffer while the application does other work or sleeps in WFI; call
+/// `poll()` now and then (at least once per 64 KB piece, ~44 ms at 12 MHz SPI) to start
+/// the next piece, and `finish()` to get the buffer back. Dropping the guard blocks until
+/// the transfer is done. The driver is borrowed meanwhile, so nothing else can touch the bus.
+///
+/// `mem::forget()` on the guard skips that wait. It is memory safe: `buffer` is forgotten with
+/// the guard, so the DMA can't read memory that went back to the application. The display is
+/// left mid-frame, though: the piece in flight keeps running, later pieces are never started,
+/// and the panel stays selected. The next driver call stops the stream, cutting the frame
+/// short, and its command ends the RAMWR.
+pub struct FrameTransfer<'d, 'a, SPI, DMA, CS, DC, RST, const CHANNEL: u8, const S: u8, const W: usize, const H: usize, const OFFSET: usize, CMD, B>
+where
+    SPI: Instance + TxDmaRoute<StreamX<DMA, S>, CHANNEL>,
+    CS: OutputPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DMA: rcc::Enable + rcc::Reset + stm32f4xx_hal::dma::traits::Instance,
+    StreamX<DMA, S>: Stream,
+    ChannelX<CHANNEL>: Channel,
+    CMD: CommandStream,
+    B: ReadBuffer<Word = u8> + 'static,
+{
+    display: &'d mut ST7789V2DMA<'a, SPI, DMA, CS, DC, RST, CHANNEL, S, W, H, OFFSET, CMD>,
+    buffer: Option<B>,
+    data: &'static [u8],
+    /// Bytes handed to the DMA so far, including the piece in flight.
+    started: usize,
+    /// The piece in flight has completed and its errors are counted.
+    piece_done: bool,
+    /// `complete()` has run, the pixel stream is back with the driver.
+    done: bool,
+}
+
+/// What `start_frame_transfer()` returns: the running transfer, or the error and the buffer.
+pub type FrameTransferResult<'d, 'a, SPI, DMA, CS, DC, RST, const CHANNEL: u8, const S: u8, const W: usize, const H: usize, const OFFSET: usize, CMD, B> =
+    Result<FrameTransfer<'d, 'a, SPI, DMA, CS, DC, RST, CHANNEL, S, W, H, OFFSET, CMD, B>, (WindowError, B)>;
+
+impl<'a, SPI, DMA, CS, DC, RST, const CHANNEL: u8, const S: u8, const W: usize, const H: usize, const OFFSET: usize, CMD>
+    ST7789V2DMA<'a, SPI, DMA, CS, DC, RST, CHANNEL, S, W, H, OFFSET, CMD>
+where
+    SPI: Instance + TxDmaRoute<StreamX<DMA, S>, CHANNEL>,
+    CS: OutputPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DMA: rcc::Enable + rcc::Reset + stm32f4xx_hal::dma::traits::Instance,
+    StreamX<DMA, S>: Stream,
+    ChannelX<CHANNEL>: Channel,
+    CMD: CommandStream,
+{
+    /// Starts sending `buffer`, big-endian RGB565 pixels filling `area` exactly, and returns
+    /// right away, see `FrameTransfer`. `buffer` is anything the DMA can own, e.g. a
+    /// `&'static mut [u8]` framebuffer or a `&'static [u8]` image in flash. The bytes go out
+    /// as they are: the color filter, row hook and chunk gap don't apply.
+    /// # Returns
+    /// The error and `buffer` back if the area or the length is wrong, see `draw_window_static()`.
+    pub fn start_frame_transfer<B>(
+        &mut self,
+        area: &Rectangle,
+        buffer: B,
+    ) -> FrameTransferResult<'_, 'a, SPI, DMA, CS, DC, RST, CHANNEL, S, W, H, OFFSET, CMD, B>
+    where
+        B: ReadBuffer<Word = u8> + 'static,
+    {
+        // SAFETY: the guard owns `buffer` until the DMA is done with it, and ReadBuffer
+        // promises the memory doesn't move or get written meanwhile
+        let data: &'static [u8] = unsafe {
+            let (ptr, len) = buffer.read_buffer();
+            core::slice::from_raw_parts(ptr, len)
+        };
+        let window = match self.check_dma_window(area, data) {
+            Ok(window) => window,
+            Err(error) => return Err((error, buffer)),
+        };
+
+        #[cfg(feature = "frame-capture")]
+        if let Some(sink) = self.capture.as_deref_mut() {
+            sink.pixels(data);
+        }
+        #[cfg(feature = "record-commands")]
+        self.recorder.pixels(data.len());
+
+        self.begin_region(window.xs, window.xe, window.ys, window.ye);
+
+        let len = data.len().min(MAX_TRANSFER);
+        self.start_pixels(PixelRun::new(&data[..len]));
+
+        Ok(FrameTransfer {
+            display: self,
+            buffer: Some(buffer),
+            data,
+            started: len,
+            piece_done: false,
+            done: false,
+        })
+    }
+}
+
+impl<SPI, DMA, CS, DC, RST, const CHANNEL: u8, const S: u8, const W: usize, const H: usize, const OFFSET: usize, CMD, B>
+    FrameTransfer<'_, '_, SPI, DMA, CS, DC, RST, CHANNEL, S, W, H, OFFSET, CMD, B>
+where
+    SPI: Instance + TxDmaRoute<StreamX<DMA, S>, CHANNEL>,
+    CS: OutputPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DMA: rcc::Enable + rcc::Reset + stm32f4xx_hal::dma::traits::Instance,
+    StreamX<DMA, S>: Stream,
+    ChannelX<CHANNEL>: Channel,
+    CMD: CommandStream,
+    B: ReadBuffer<Word = u8> + 'static,
+{
+    /// Starts the next piece if the current one is done. Never blocks; while a
+    /// `set_pause_while()` condition holds, the next piece waits for a later poll.
+    /// # Returns
+    /// `true` once every byte has been sent, `finish()` returns right away then.
+    pub fn poll(&mut self) -> bool {
+        if self.done {
+            return true;
+        }
+        if !self.piece_done {
+            if self.display.check_pixels().is_none() {
+                return false;
+            }
+            self.piece_done = true;
+        }
+        if self.started == self.data.len() {
+            return true;
+        }
+        if self.display.pause_while.is_some_and(|critical| critical()) {
+            return false;
+        }
+
+        let (data, start) = (self.data, self.started);
+        let len = (data.len() - start).min(MAX_TRANSFER);
+        self.display.start_pixels(PixelRun::new(&data[start..start + len]));
+        self.started += len;
+        self.piece_done = false;
+        false
+    }
+
+    /// Bytes the DMA has been handed so far, out of the whole buffer.
+    pub fn progress(&self) -> (usize, usize) {
+        (self.started, self.data.len())
+    }
+
+    /// Blocks until the rest is sent, deselects the panel and returns the buffer.
+    pub fn finish(mut self) -> B {
+        self.complete();
+        self.buffer.take().unwrap()
+    }
+
+    /// Sends the remaining pieces like `stream_static()` and gives the stream back.
+    fn complete(&mut self) {
+        if self.done {
+            return;
+        }
+        loop {
+            if !self.piece_done {
+                self.display.wait_pixels();
+            }
+            if self.started == self.data.len() {
+                break;
+            }
+            if let Some(critical) = self.display.pause_while {
+                while critical() {}
+            }
+
+            let (data, start) = (self.data, self.started);
+            let len = (data.len() - start).min(MAX_TRANSFER);
+            self.display.start_pixels(PixelRun::new(&data[start..start + len]));
+            self.started += len;
+            self.piece_done = false;
+        }
+
+        self.display.end_pixels();
+        self.display.deselect();
+        self.done = true;
+    }
+}
+
+impl<SPI, DMA, CS, DC, RST, const CHANNEL: u8, const S: u8, const W: usize, const H: usize, const OFFSET: usize, CMD, B> Drop
+    for FrameTransfer<'_, '_, SPI, DMA, CS, DC, RST, CHANNEL, S, W, H, OFFSET, CMD, B>
+where
+    SPI: Instance + TxDmaRoute<StreamX<DMA, S>, CHANNEL>,
+    CS: OutputPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DMA: rcc::Enable + rcc::Reset + stm32f4xx_hal::dma::traits::Instance,
+    StreamX<DMA, S>: Stream,
+    ChannelX<CHANNEL>: Channel,
+    CMD: CommandStream,
+    B: ReadBuffer<Word = u8> + 'static,
+{
+    fn drop(&mut self) {
+        self.complete();
+    }
+}
@@ -0,0 +1,269 @@
+use crate::cs_command;
+use crate::st7789v2::{
+    common::{Commands, Error},
+    dma::{command_stream::CommandStream, st7789v2dma::{ST7789V2DMA, TxDmaRoute}},
+    encode::{ConfigRegister, Packet},
+};
+use defmt::debug;
+use embedded_graphics::prelude::DrawTarget;
+use stm32f4xx_hal::{
+    dma::{
+        ChannelX, StreamX,
+        traits::{Channel, Stream},
+    },
+    hal::digital::OutputPin,
+    rcc,
+    spi::Instance,
+};
+
+/// One step of the bring-up `init()` performs, see `ST7789V2DMA::init_steps()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, defmt::Format)]
+pub enum InitStep {
+    /// Pulls RST low.
+    ResetLow,
+    /// Releases RST.
+    ResetHigh,
+    SoftwareReset,
+    SleepOut,
+    /// Writes every shadowed configuration register; a few short waits are done inline.
+    Configure,
+    /// Fills the frame memory if `set_init_fill()` asked for it.
+    Fill,
+    DisplayOn,
+}
+
+/// The steps of `init()`, in order.
+pub const INIT_STEPS: [InitStep; 7] = [
+    InitStep::ResetLow,
+    InitStep::ResetHigh,
+    InitStep::SoftwareReset,
+    InitStep::SleepOut,
+    InitStep::Configure,
+    InitStep::Fill,
+    InitStep::DisplayOn,
+];
+
+/// The steps of the fast `init()`, see `ST7789V2DMA::set_fast_init()`: the hardware reset
+/// already puts every register in its default state, so SWRESET and its 150 ms are skipped.
+pub const FAST_INIT_STEPS: [InitStep; 6] = [
+    InitStep::ResetLow,
+    InitStep::ResetHigh,
+    InitStep::SleepOut,
+    InitStep::Configure,
+    InitStep::Fill,
+    InitStep::DisplayOn,
+];
+
+/// Progress of an `InitSm`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, defmt::Format)]
+pub enum InitState {
+    /// Waiting for the panel after `step`; poll again in `remaining_ms` or later.
+    Busy { step: InitStep, remaining_ms: u32 },
+    /// The panel is on and ready to draw.
+    Done,
+}
+
+/// `init()` as a poll-driven state machine for cooperative superloops, see
+/// `ST7789V2DMA::init_sm()`. Each `poll()` runs the steps that are due and returns right
+/// away; no step waits, not even the short per-command ones. The `Fill` step still streams
+/// the whole frame in one go if `set_init_fill()` asked for it.
+pub struct InitSm<'d, 'a, SPI, DMA, CS, DC, RST, const CHANNEL: u8, const S: u8, const W: usize, const H: usize, const OFFSET: usize, CMD>
+where
+    SPI: Instance + TxDmaRoute<StreamX<DMA, S>, CHANNEL>,
+    CS: OutputPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DMA: rcc::Enable + rcc::Reset + stm32f4xx_hal::dma::traits::Instance,
+    StreamX<DMA, S>: Stream,
+    ChannelX<CHANNEL>: Channel,
+    CMD: CommandStream,
+{
+    display: &'d mut ST7789V2DMA<'a, SPI, DMA, CS, DC, RST, CHANNEL, S, W, H, OFFSET, CMD>,
+    steps: &'static [InitStep],
+    next: usize,
+    /// When the last step run has settled.
+    ready_at: Option<u32>,
+}
+
+impl<SPI, DMA, CS, DC, RST, const CHANNEL: u8, const S: u8, const W: usize, const H: usize, const OFFSET: usize, CMD>
+    InitSm<'_, '_, SPI, DMA, CS, DC, RST, CHANNEL, S, W, H, OFFSET, CMD>
+where
+    SPI: Instance + TxDmaRoute<StreamX<DMA, S>, CHANNEL>,
+    CS: OutputPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DMA: rcc::Enable + rcc::Reset + stm32f4xx_hal::dma::traits::Instance,
+    StreamX<DMA, S>: Stream,
+    ChannelX<CHANNEL>: Channel,
+    CMD: CommandStream,
+{
+    /// Runs every step that is due at `now_ms` (any wrapping millisecond counter).
+    pub fn poll(&mut self, now_ms: u32) -> InitState {
+        loop {
+            if let Some(ready_at) = self.ready_at {
+                let remaining = ready_at.wrapping_sub(now_ms) as i32;
+                if remaining > 0 {
+                    let step = self.steps[self.next - 1];
+                    return InitState::Busy { step, remaining_ms: remaining as u32 };
+                }
+            }
+            let Some(&step) = self.steps.get(self.next) else {
+                return InitState::Done;
+            };
+
+            self.display.skip_waits = true;
+            let wait = self.display.run_init_step(step);
+            self.display.skip_waits = false;
+
+            self.next += 1;
+            self.ready_at = Some(now_ms.wrapping_add(wait));
+        }
+    }
+}
+
+impl InitStep {
+    /// Time the panel needs after this step before the next one, with generous margins.
+    pub const fn delay_ms(&self) -> u32 {
+        match self {
+            InitStep::ResetLow => 120,
+            InitStep::ResetHigh => 150,
+            InitStep::SoftwareReset => 150,
+            InitStep::SleepOut => 120,
+            InitStep::Configure | InitStep::Fill => 0,
+            InitStep::DisplayOn => 50,
+        }
+    }
+
+    /// The datasheet minimum after this step, valid when the panel is reset from sleep in
+    /// mode (as at power-up): RST low for at least 10 us, 5 ms after releasing RST and after
+    /// SLPOUT before the next command. DISPON needs no wait.
+    pub const fn fast_delay_ms(&self) -> u32 {
+        match self {
+            InitStep::ResetLow => 1,
+            InitStep::ResetHigh => 5,
+            InitStep::SoftwareReset => 5,
+            InitStep::SleepOut => 5,
+            InitStep::Configure | InitStep::Fill | InitStep::DisplayOn => 0,
+        }
+    }
+
+    /// The command this step sends, for steps that are a single command.
+    /// `ST7789V2DMA::init_step_packets()` has the bytes of every step, `Configure` included.
+    pub const fn command(&self) -> Option<Commands> {
+        match self {
+            InitStep::SoftwareReset => Some(Commands::SoftwareReset),
+            InitStep::SleepOut => Some(Commands::SleepOut),
+            InitStep::DisplayOn => Some(Commands::DisplayOn),
+            _ => None,
+        }
+    }
+}
+
+impl<'a, SPI, DMA, CS, DC, RST, const CHANNEL: u8, const S: u8, const W: usize, const H: usize, const OFFSET: usize, CMD>
+    ST7789V2DMA<'a, SPI, DMA, CS, DC, RST, CHANNEL, S, W, H, OFFSET, CMD>
+where
+    SPI: Instance + TxDmaRoute<StreamX<DMA, S>, CHANNEL>,
+    CS: OutputPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DMA: rcc::Enable + rcc::Reset + stm32f4xx_hal::dma::traits::Instance,
+    StreamX<DMA, S>: Stream,
+    ChannelX<CHANNEL>: Channel,
+    CMD: CommandStream,
+{
+    /// Returns `init()` as separate steps, so an async executor or a superloop can do other
+    /// boot work during the ~600 ms of waits instead of blocking in them:
+    ///
+    /// ```ignore
+    /// for step in display.init_steps() {
+    ///     let wait = display.run_init_step(step);
+    ///     Timer::after_millis(wait as u64).await;
+    /// }
+    /// ```
+    /// The steps are opaque so the driver keeps its pins and shadow state in sync; an executor
+    /// that sends the bytes itself gets them as (command bytes, delay) packets from
+    /// `init_step_packets()`.
+    pub fn init_steps(&self) -> core::iter::Copied<core::slice::Iter<'static, InitStep>> {
+        let steps: &'static [InitStep] = if self.fast_init { &FAST_INIT_STEPS } else { &INIT_STEPS };
+        steps.iter().copied()
+    }
+
+    /// Returns `init()` as a state machine to `poll()` from a superloop, see `InitSm`:
+    ///
+    /// ```ignore
+    /// let mut init = display.init_sm();
+    /// while let InitState::Busy { .. } = init.poll(millis()) {
+    ///     other_boot_work();
+    /// }
+    /// ```
+    pub fn init_sm(&mut self) -> InitSm<'_, 'a, SPI, DMA, CS, DC, RST, CHANNEL, S, W, H, OFFSET, CMD> {
+        let steps: &'static [InitStep] = if self.fast_init { &FAST_INIT_STEPS } else { &INIT_STEPS };
+        InitSm { display: self, steps, next: 0, ready_at: None }
+    }
+
+    /// Makes `init()` take the fast path: only the steps and waits the datasheet requires
+    /// after a hardware reset from sleep in mode, about 10 ms of waits instead of ~590 ms.
+    /// Meant for power-up, when the panel has just been powered and is still in sleep in mode.
+    /// Off by default; `reinit()` always takes the conservative path, since the panel was
+    /// running (sleep out), which needs 120 ms after the reset.
+    pub fn set_fast_init(&mut self, enable: bool) {
+        self.fast_init = enable;
+    }
+
+    pub fn fast_init(&self) -> bool {
+        self.fast_init
+    }
+
+    /// Calls `emit` with the packets `step` sends, each with the wait the panel needs after
+    /// it: SWRESET, SLPOUT and DISPON are one packet carrying the step's delay, `Configure`
+    /// is every configuration register as the shadow copy holds it (see `ConfigRegister`).
+    /// The reset steps drive RST and `Fill` streams pixels, so they emit nothing.
+    pub fn init_step_packets(&self, step: InitStep, mut emit: impl FnMut(Packet)) {
+        let delay = if self.fast_init { step.fast_delay_ms() } else { step.delay_ms() };
+        match step {
+            InitStep::Configure => {
+                for register in ConfigRegister::ALL {
+                    register.packets(&self.shadow, &mut emit);
+                }
+            }
+            _ => {
+                if let Some(command) = step.command() {
+                    emit(Packet::new(command, &[], delay));
+                }
+            }
+        }
+    }
+
+    /// Performs `step` without its trailing wait.
+    /// # Returns
+    /// The milliseconds to wait before the next step.
+    pub fn run_init_step(&mut self, step: InitStep) -> u32 {
+        match step {
+            InitStep::ResetLow => {
+                let reset = self.rst.set_low();
+                self.record_pin(reset.map_err(Error::RST));
+            }
+            InitStep::ResetHigh => {
+                let reset = self.rst.set_high();
+                self.record_pin(reset.map_err(Error::RST));
+            }
+            InitStep::SoftwareReset => {
+                cs_command!(self, Commands::SoftwareReset, 0);
+                self.shadow.display_on = false;
+            }
+            InitStep::SleepOut => cs_command!(self, Commands::SleepOut, 0),
+            InitStep::Configure => self.write_config(),
+            InitStep::Fill => {
+                if let Some(color) = self.init_fill {
+                    self.ram_view().clear(color).ok();
+                }
+            }
+            InitStep::DisplayOn => {
+                cs_command!(self, Commands::DisplayOn, 0);
+                self.shadow.display_on = true;
+            }
+        }
+        debug!("{} step completed in init()", step);
+        if self.fast_init { step.fast_delay_ms() } else { step.delay_ms() }
+    }
+}
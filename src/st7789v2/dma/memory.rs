@@ -0,0 +1,101 @@
+use core::ops::Range;
+
+/// Core-coupled memory on the F405/F407/F427/F429/F469 parts. Only the CPU's D-bus reaches it,
+/// so a DMA stream pointed there stops with a transfer error and nothing on the wire.
+pub const CCM_RAM: Range<usize> = 0x1000_0000..0x1001_0000;
+
+/// Flash, including the system memory and OTP area.
+const FLASH: Range<usize> = 0x0800_0000..0x1000_0000;
+/// SRAM1/SRAM2/SRAM3 and the backup SRAM on the biggest F4 parts.
+const SRAM: Range<usize> = 0x2000_0000..0x4000_0000;
+
+/// Why a buffer can't be handed to the DMA.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, defmt::Format)]
+pub enum MemoryError {
+    /// The buffer is in CCM RAM, e.g. a `static` placed in a `.ccmram` section.
+    CcmRam { address: usize },
+    /// The buffer is outside flash and SRAM.
+    NotDmaCapable { address: usize },
+    /// The buffer doesn't start on an `align`-byte boundary, which half-word and word DMA
+    /// transfers need.
+    Misaligned { address: usize, align: usize },
+}
+
+/// A byte buffer that always starts on a word boundary, so it can be sent with half-word or
+/// word DMA transfers as well as byte ones. Use it for `static` buffers instead of a bare array:
+///
+/// ```ignore
+/// let buf = singleton!(: Aligned<4096> = Aligned::new()).unwrap();
+/// ```
+#[repr(C, align(4))]
+pub struct Aligned<const N: usize>(pub [u8; N]);
+
+impl<const N: usize> Aligned<N> {
+    pub const fn new() -> Self {
+        Self([0; N])
+    }
+
+    /// The buffer as half-words in native (little-endian) order.
+    /// Fails to compile if `N` is odd.
+    pub fn half_words(&self) -> &[u16] {
+        const { assert!(N.is_multiple_of(2), "a half-word buffer needs an even number of bytes") };
+        // SAFETY: `repr(align(4))` keeps the bytes 2-aligned and N / 2 half-words fit exactly.
+        unsafe { core::slice::from_raw_parts(self.0.as_ptr().cast(), N / 2) }
+    }
+
+    /// The buffer as words in native (little-endian) order.
+    /// Fails to compile unless `N` is a multiple of 4.
+    pub fn words(&self) -> &[u32] {
+        const { assert!(N.is_multiple_of(4), "a word buffer needs a multiple of 4 bytes") };
+        // SAFETY: `repr(align(4))` keeps the bytes 4-aligned and N / 4 words fit exactly.
+        unsafe { core::slice::from_raw_parts(self.0.as_ptr().cast(), N / 4) }
+    }
+}
+
+impl<const N: usize> Default for Aligned<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> core::ops::Deref for Aligned<N> {
+    type Target = [u8; N];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<const N: usize> core::ops::DerefMut for Aligned<N> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+/// Checks that `buf` starts on an `align`-byte boundary and is a whole number of
+/// `align`-byte items, for user slices that can't be an `Aligned`.
+/// Unlike `check_dma_capable()` this also runs in release builds, it's a single mask.
+pub fn check_aligned(buf: &[u8], align: usize) -> Result<(), MemoryError> {
+    let address = buf.as_ptr() as usize;
+    if !address.is_multiple_of(align) || !buf.len().is_multiple_of(align) {
+        return Err(MemoryError::Misaligned { address, align });
+    }
+    Ok(())
+}
+
+/// Checks that the DMA's memory port can read all of `buf`.
+/// Only runs in debug builds; in release builds every buffer is accepted.
+pub fn check_dma_capable(buf: &[u8]) -> Result<(), MemoryError> {
+    if cfg!(debug_assertions) && !buf.is_empty() {
+        let start = buf.as_ptr() as usize;
+        let end = start + buf.len();
+        if start < CCM_RAM.end && end > CCM_RAM.start {
+            return Err(MemoryError::CcmRam { address: start });
+        }
+        let inside = |region: &Range<usize>| start >= region.start && end <= region.end;
+        if !inside(&FLASH) && !inside(&SRAM) {
+            return Err(MemoryError::NotDmaCapable { address: start });
+        }
+    }
+    Ok(())
+}
@@ -1,3 +1,26 @@
 pub mod st7789v2dma;
 pub mod drawtarget;
-pub mod macros;
\ No newline at end of file
+pub mod macros;
+pub mod command_stream;
+pub mod stats;
+pub mod shadow;
+pub mod audit;
+pub mod ram;
+pub mod memory;
+pub mod depth;
+pub mod doubled;
+pub mod transition;
+pub mod config;
+pub mod detect;
+pub mod init;
+pub mod frame_transfer;
+pub mod autotune;
+pub mod power;
+#[cfg(feature = "arena")]
+pub mod arena;
+#[cfg(feature = "frame-capture")]
+pub mod capture;
+#[cfg(feature = "record-commands")]
+pub mod recorder;
+#[cfg(feature = "fault-injection")]
+pub mod chaos;
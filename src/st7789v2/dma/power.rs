@@ -0,0 +1,143 @@
+use cortex_m::peripheral::DWT;
+use stm32f4xx_hal::hal::digital::OutputPin;
+
+use crate::st7789v2::params::FrameRate;
+
+/// Called when the driver takes the bus (CS low) and releases it (CS high), e.g. to toggle a
+/// GPIO a scope or logic analyzer triggers on, or to tag INA219/shunt samples so current
+/// spikes can be matched to what the display was doing. Set with `ST7789V2DMA::set_bus_hooks()`.
+/// Runs on every transaction, so keep it short.
+pub trait BusHooks {
+    fn on_bus_active(&mut self) {}
+    fn on_bus_idle(&mut self) {}
+}
+
+/// How long the display bus was active, for the energy budget of a UI design.
+/// Cycles come from the DWT cycle counter, which must be enabled (e.g. `DWT.constrain()`),
+/// otherwise only `windows` counts.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, defmt::Format)]
+pub struct BusActivity {
+    /// Core cycles spent with CS low.
+    pub active_cycles: u64,
+    /// Number of CS low periods.
+    pub windows: u32,
+}
+
+impl BusActivity {
+    /// Returns the active time in microseconds at a core clock of `sysclk_hz`.
+    pub fn active_us(&self, sysclk_hz: u32) -> u64 {
+        self.active_cycles * 1_000_000 / sysclk_hz.max(1) as u64
+    }
+}
+
+/// Tracks the current active window and feeds `BusActivity`.
+#[derive(Debug, Clone, Copy, Default)]
+pub(super) struct BusMeter {
+    pub(super) activity: BusActivity,
+    active_since: Option<u32>,
+}
+
+impl BusMeter {
+    /// Starts a window. Returns false if one is already open.
+    pub(super) fn start(&mut self) -> bool {
+        if self.active_since.is_some() {
+            return false;
+        }
+        self.active_since = Some(DWT::cycle_count());
+        true
+    }
+
+    /// Ends the open window. Returns false if there was none.
+    pub(super) fn stop(&mut self) -> bool {
+        let Some(since) = self.active_since.take() else {
+            return false;
+        };
+        self.activity.active_cycles += DWT::cycle_count().wrapping_sub(since) as u64;
+        self.activity.windows = self.activity.windows.wrapping_add(1);
+        true
+    }
+}
+
+/// Switches the backlight for the idle blank, see `ST7789V2DMA::set_backlight()`.
+/// Implemented for any active-high `OutputPin`; pin errors are ignored.
+pub trait Backlight {
+    fn set_backlight(&mut self, on: bool);
+}
+
+impl<P: OutputPin> Backlight for P {
+    fn set_backlight(&mut self, on: bool) {
+        let _ = if on { self.set_high() } else { self.set_low() };
+    }
+}
+
+/// Inactivity timer behind `ST7789V2DMA::set_idle_blank()`. Draws only set a flag, the time
+/// comes from `idle_tick()`, so the pixel path doesn't need a clock.
+#[derive(Debug, Clone, Copy, Default)]
+pub(super) struct IdleBlank {
+    pub(super) timeout_ms: Option<u32>,
+    pub(super) blanked: bool,
+    drawn: bool,
+    last_draw_ms: u32,
+}
+
+impl IdleBlank {
+    /// Notes a draw; the next `tick()` restarts the timeout from its timestamp.
+    pub(super) fn touch(&mut self) {
+        self.drawn = true;
+    }
+
+    /// Returns true when the timeout ran out at `now_ms` and the panel isn't blanked yet.
+    pub(super) fn tick(&mut self, now_ms: u32) -> bool {
+        let Some(timeout_ms) = self.timeout_ms else {
+            return false;
+        };
+        if core::mem::take(&mut self.drawn) {
+            self.last_draw_ms = now_ms;
+            return false;
+        }
+        !self.blanked && now_ms.wrapping_sub(self.last_draw_ms) >= timeout_ms
+    }
+}
+
+/// Power presets combining the panel refresh rate, idle mode, brightness and how often the
+/// application should redraw, applied with `ST7789V2DMA::set_power_profile()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, defmt::Format)]
+pub enum Profile {
+    /// 60 Hz, full colors and brightness; for interaction.
+    Full,
+    /// 39 Hz panel refresh, half brightness, 20 redraws a second; for dashboards left running.
+    Reduced,
+    /// 39 Hz, 8-color idle mode, dim, one redraw a second; for clocks and lock screens.
+    Minimal,
+}
+
+impl Profile {
+    pub const fn frame_rate(&self) -> FrameRate {
+        match self {
+            Profile::Full => FrameRate::HZ_60,
+            Profile::Reduced | Profile::Minimal => FrameRate::HZ_39,
+        }
+    }
+
+    pub const fn idle_mode(&self) -> bool {
+        matches!(self, Profile::Minimal)
+    }
+
+    /// WRDISBV brightness, see `ST7789V2DMA::set_brightness()`.
+    pub const fn brightness(&self) -> u8 {
+        match self {
+            Profile::Full => 0xFF,
+            Profile::Reduced => 0x80,
+            Profile::Minimal => 0x20,
+        }
+    }
+
+    /// Redraws per second the application should limit itself to.
+    pub const fn host_fps(&self) -> u32 {
+        match self {
+            Profile::Full => 60,
+            Profile::Reduced => 20,
+            Profile::Minimal => 1,
+        }
+    }
+}
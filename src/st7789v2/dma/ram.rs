@@ -0,0 +1,126 @@
+use crate::st7789v2::clip::Window;
+use crate::st7789v2::common::{RAM_HEIGHT, RAM_WIDTH};
+use crate::st7789v2::dma::{command_stream::CommandStream, st7789v2dma::{ST7789V2DMA, TxDmaRoute}};
+use embedded_graphics::{pixelcolor::Rgb565, prelude::*, primitives::Rectangle};
+use stm32f4xx_hal::{
+    dma::{
+        traits::{Channel, Stream}, ChannelX, StreamX
+    },
+    hal::digital::OutputPin,
+    rcc,
+    spi::Instance,
+};
+
+/// DrawTarget over the controller's whole 240x320 frame memory, including the rows
+/// the glass doesn't show (40 of them on a 240x280 panel).
+/// Coordinates are RAM addresses under the current MADCTL, so content keeps the display's
+/// orientation; `visible_window()` tells where the glass sits. Pre-render into the hidden rows,
+/// then bring them into view with `set_scroll_offset()`.
+pub struct RamView<'d, 'a, SPI, DMA, CS, DC, RST, const CHANNEL: u8, const S: u8, const W: usize, const H: usize, const OFFSET: usize, CMD>
+where
+    SPI: Instance + TxDmaRoute<StreamX<DMA, S>, CHANNEL>,
+    CS: OutputPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DMA: rcc::Enable + rcc::Reset + stm32f4xx_hal::dma::traits::Instance,
+    StreamX<DMA, S>: Stream,
+    ChannelX<CHANNEL>: Channel,
+    CMD: CommandStream,
+{
+    display: &'d mut ST7789V2DMA<'a, SPI, DMA, CS, DC, RST, CHANNEL, S, W, H, OFFSET, CMD>,
+}
+
+impl<'a, SPI, DMA, CS, DC, RST, const CHANNEL: u8, const S: u8, const W: usize, const H: usize, const OFFSET: usize, CMD>
+    ST7789V2DMA<'a, SPI, DMA, CS, DC, RST, CHANNEL, S, W, H, OFFSET, CMD>
+where
+    SPI: Instance + TxDmaRoute<StreamX<DMA, S>, CHANNEL>,
+    CS: OutputPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DMA: rcc::Enable + rcc::Reset + stm32f4xx_hal::dma::traits::Instance,
+    StreamX<DMA, S>: Stream,
+    ChannelX<CHANNEL>: Channel,
+    CMD: CommandStream,
+{
+    /// Borrows the driver as a DrawTarget over the full frame memory, see `RamView`.
+    pub fn ram_view(&mut self) -> RamView<'_, 'a, SPI, DMA, CS, DC, RST, CHANNEL, S, W, H, OFFSET, CMD> {
+        RamView { display: self }
+    }
+
+    /// Returns where the visible window sits in `ram_view()` coordinates (ignoring the scroll offset).
+    pub fn visible_window(&self) -> Rectangle {
+        let transform = self.transform();
+        let (x, y) = self.ram_offset();
+        let (width, height) = transform.size::<W, H>();
+        Rectangle::new(Point::new(x as i32, y as i32), Size::new(width as u32, height as u32))
+    }
+}
+
+impl<SPI, DMA, CS, DC, RST, const CHANNEL: u8, const S: u8, const W: usize, const H: usize, const OFFSET: usize, CMD> OriginDimensions for
+    RamView<'_, '_, SPI, DMA, CS, DC, RST, CHANNEL, S, W, H, OFFSET, CMD>
+where
+    SPI: Instance + TxDmaRoute<StreamX<DMA, S>, CHANNEL>,
+    CS: OutputPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DMA: rcc::Enable + rcc::Reset + stm32f4xx_hal::dma::traits::Instance,
+    StreamX<DMA, S>: Stream,
+    ChannelX<CHANNEL>: Channel,
+    CMD: CommandStream,
+{
+    fn size(&self) -> Size {
+        if self.display.orientation().is_landscape() {
+            Size::new(RAM_HEIGHT as u32, RAM_WIDTH as u32)
+        } else {
+            Size::new(RAM_WIDTH as u32, RAM_HEIGHT as u32)
+        }
+    }
+}
+
+impl<SPI, DMA, CS, DC, RST, const CHANNEL: u8, const S: u8, const W: usize, const H: usize, const OFFSET: usize, CMD> DrawTarget for
+    RamView<'_, '_, SPI, DMA, CS, DC, RST, CHANNEL, S, W, H, OFFSET, CMD>
+where
+    SPI: Instance + TxDmaRoute<StreamX<DMA, S>, CHANNEL>,
+    CS: OutputPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DMA: rcc::Enable + rcc::Reset + stm32f4xx_hal::dma::traits::Instance,
+    StreamX<DMA, S>: Stream,
+    ChannelX<CHANNEL>: Channel,
+    CMD: CommandStream,
+{
+    type Color = Rgb565;
+    type Error = core::convert::Infallible;
+
+    fn fill_contiguous<I>(&mut self, area: &Rectangle, colors: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Self::Color>,
+    {
+        let Some(window) = Window::clip(area, &self.bounding_box()) else {
+            return Ok(());
+        };
+
+        self.display.set_ram_window(window.xs, window.xe, window.ys, window.ye);
+        self.display.write_pixels(&window.rectangle(), window.visible_colors(area, colors));
+
+        Ok(())
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        self.fill_contiguous(area, core::iter::repeat(color))
+    }
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let bounds = self.bounding_box();
+        for Pixel(point, color) in pixels {
+            if bounds.contains(point) {
+                self.fill_solid(&Rectangle::new(point, Size::new(1, 1)), color)?;
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,87 @@
+/// Number of entries the command recorder keeps; older entries are overwritten.
+pub const RECORD_DEPTH: usize = 64;
+
+/// One step of the byte stream the driver emitted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, defmt::Format)]
+pub enum RecordEntry {
+    /// A command byte (DC low).
+    Command(u8),
+    /// A parameter byte (DC high), including CASET/RASET data.
+    Param(u8),
+    /// A run of RAMWR pixel bytes; consecutive chunks are merged into one entry.
+    Pixels(u32),
+}
+
+/// Ring buffer of the last `RECORD_DEPTH` commands, parameters and pixel runs sent to the panel,
+/// so a fault report ("why is the screen white") can include the exact sequence the driver emitted.
+#[derive(Debug, Clone)]
+pub struct CommandRecorder {
+    entries: [RecordEntry; RECORD_DEPTH],
+    next: usize,
+    len: usize,
+}
+
+impl Default for CommandRecorder {
+    fn default() -> Self {
+        Self {
+            entries: [RecordEntry::Pixels(0); RECORD_DEPTH],
+            next: 0,
+            len: 0,
+        }
+    }
+}
+
+impl CommandRecorder {
+    pub(crate) fn command(&mut self, cmd: u8) {
+        self.push(RecordEntry::Command(cmd));
+    }
+
+    pub(crate) fn param(&mut self, data: u8) {
+        self.push(RecordEntry::Param(data));
+    }
+
+    pub(crate) fn pixels(&mut self, bytes: usize) {
+        if self.len > 0 {
+            let last = (self.next + RECORD_DEPTH - 1) % RECORD_DEPTH;
+            if let RecordEntry::Pixels(run) = &mut self.entries[last] {
+                *run = run.saturating_add(bytes as u32);
+                return;
+            }
+        }
+        self.push(RecordEntry::Pixels(bytes as u32));
+    }
+
+    fn push(&mut self, entry: RecordEntry) {
+        self.entries[self.next] = entry;
+        self.next = (self.next + 1) % RECORD_DEPTH;
+        self.len = (self.len + 1).min(RECORD_DEPTH);
+    }
+
+    /// Returns the number of recorded entries.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn clear(&mut self) {
+        self.next = 0;
+        self.len = 0;
+    }
+
+    /// Iterates over the recorded entries, oldest first.
+    pub fn iter(&self) -> impl Iterator<Item = RecordEntry> + '_ {
+        let start = (self.next + RECORD_DEPTH - self.len) % RECORD_DEPTH;
+        (0..self.len).map(move |i| self.entries[(start + i) % RECORD_DEPTH])
+    }
+
+    /// Dumps the recorded sequence via defmt, oldest first.
+    pub fn log(&self) {
+        defmt::info!("command record: {} entries", self.len);
+        for entry in self.iter() {
+            defmt::info!("  {}", entry);
+        }
+    }
+}
@@ -0,0 +1,59 @@
+use crate::st7789v2::common::{ColorMode, ColorOrder, Madctl};
+use crate::st7789v2::params::{ColorEnhancement, DigitalGamma, FrameRate, GammaCurves, PartialArea, Porch, ScrollArea};
+use crate::st7789v2::transform::Transform;
+
+/// Shadow copy of every configuration register the driver writes.
+/// Getters read from here instead of the bus, and `resume()`/`reinit()` replay it
+/// to restore the exact state.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ShadowRegisters {
+    pub transform: Transform,
+    pub color_order: ColorOrder,
+    pub color_mode: ColorMode,
+    pub inverted: bool,
+    pub scroll_area: ScrollArea,
+    pub scroll_offset: u16,
+    pub partial_area: Option<PartialArea>,
+    pub brightness: u8,
+    /// `None` keeps the controller's power-on gamma.
+    pub gamma: Option<GammaCurves>,
+    /// `None` leaves digital gamma disabled.
+    pub digital_gamma: Option<DigitalGamma>,
+    pub color_enhancement: ColorEnhancement,
+    pub tearing_effect: bool,
+    pub frame_rate: FrameRate,
+    pub porch: Porch,
+    /// 8-color idle mode (IDMON).
+    pub idle_mode: bool,
+    pub display_on: bool,
+}
+
+impl Default for ShadowRegisters {
+    fn default() -> Self {
+        Self {
+            transform: Transform::default(),
+            color_order: ColorOrder::Rgb,
+            color_mode: ColorMode::RGB565,
+            inverted: true, // the panel shows inverted colors without INVON
+            scroll_area: ScrollArea::FULL,
+            scroll_offset: 0,
+            partial_area: None,
+            brightness: 0xFF,
+            gamma: None,
+            digital_gamma: None,
+            color_enhancement: ColorEnhancement::Off,
+            tearing_effect: false,
+            frame_rate: FrameRate::HZ_60,
+            porch: Porch::DEFAULT,
+            idle_mode: false,
+            display_on: false,
+        }
+    }
+}
+
+impl ShadowRegisters {
+    /// Returns the MADCTL value for the current orientation, mirroring and color order.
+    pub fn madctl(&self) -> Madctl {
+        self.transform.madctl() | self.color_order.madctl_bits()
+    }
+}
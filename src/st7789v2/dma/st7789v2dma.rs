@@ -1,15 +1,38 @@
 
-use crate::{cs_command, cs_command_data_sequence, cs_data, st7789v2::common::{ColorMode, Commands}};
+use crate::{cs_command, cs_command_data_sequence, cs_data, st7789v2::common::{ColorMode, ColorOrder, Commands, Error, Orientation, Parts, RAM_HEIGHT, RAM_WIDTH}};
+use crate::st7789v2::clip::Window;
+use crate::st7789v2::color::ColorFilter;
+use crate::st7789v2::encode::{self, ConfigRegister};
+use crate::st7789v2::params::{check_max, ColorEnhancement, DigitalGamma, FrameRate, GammaCurves, ParamError, PartialArea, Porch, ScrollArea};
+use core::convert::Infallible;
 use cortex_m::delay::Delay;
 use defmt::debug;
+use embedded_dma::ReadBuffer;
+use embedded_graphics::{pixelcolor::{raw::{RawU16, ToBytes}, Rgb565, Rgb666, RgbColor}, prelude::{Dimensions, Point}, primitives::{PointsIter, Rectangle}};
+#[cfg(feature = "arena")]
+use crate::st7789v2::dma::arena::DmaArena;
+#[cfg(feature = "frame-capture")]
+use crate::st7789v2::dma::capture::FrameSink;
+#[cfg(feature = "record-commands")]
+use crate::st7789v2::dma::recorder::CommandRecorder;
+#[cfg(feature = "fault-injection")]
+use crate::st7789v2::dma::chaos::{Chaos, Fault};
+use crate::st7789v2::dma::command_stream::{CommandStream, SharedStream};
+use crate::st7789v2::dma::init::InitStep;
+use crate::st7789v2::dma::memory::{check_dma_capable, MemoryError};
+use crate::st7789v2::schedule::ScheduleUpdate;
+use crate::st7789v2::dma::power::{Backlight, BusActivity, BusHooks, BusMeter, IdleBlank, Profile};
+use crate::st7789v2::dma::shadow::ShadowRegisters;
+use crate::st7789v2::transform::Transform;
+use crate::st7789v2::dma::stats::{ErrorStats, LinkError};
 use stm32f4xx_hal::{
     dma::{
         ChannelX, MemoryToPeripheral, StreamX, Transfer,
-        config::DmaConfig,
-        traits::{Channel, DMASet, Stream, StreamISR},
+        config::{DmaConfig, Priority},
+        traits::{Channel, DMASet, Stream},
     },
-    hal::digital::OutputPin,
-    rcc,
+    hal::digital::{ErrorType, OutputPin},
+    pac, rcc,
     spi::{Instance, Tx},
 };
 
@@ -18,7 +41,119 @@ use stm32f4xx_hal::{
 
 // Macro for handling CS timing with commands
 
+/// Default chunk buffer size, the one `DmaArena::take()` uses.
 pub const CHUNK_SIZE: usize = 1024 * 4;
+/// Smallest chunk buffer the driver takes: one RGB565 row along the RAM's long side, so a
+/// row hook always gets whole rows.
+pub const MIN_CHUNK_SIZE: usize = RAM_HEIGHT * 2;
+
+/// Checks a chunk buffer size at compile time.
+/// Chunks must split on word boundaries once pixels go out as half-words or words.
+pub(super) const fn check_chunk_size(chunk: usize) {
+    assert!(chunk.is_multiple_of(4), "the chunk buffer size must be a multiple of 4");
+    assert!(chunk >= MIN_CHUNK_SIZE, "the chunk buffer must hold at least MIN_CHUNK_SIZE bytes");
+}
+
+/// Most bytes one DMA transfer can move: NDTR is 16 bits wide.
+pub const MAX_TRANSFER: usize = u16::MAX as usize;
+
+/// Bytes for one pixel transfer: the filled prefix of the chunk buffer, or a piece of a
+/// `'static` buffer sent straight from where it lives (see `stream_static()`).
+/// Only the address and length, so the pixel transfer can stay set up between runs while
+/// the driver refills the chunk buffer; a run is only started on bytes that stay valid
+/// and untouched until it completes.
+pub(super) struct PixelRun {
+    ptr: *const u8,
+    len: usize,
+}
+
+impl PixelRun {
+    pub(super) fn new(bytes: &[u8]) -> Self {
+        Self { ptr: bytes.as_ptr(), len: bytes.len() }
+    }
+}
+
+unsafe impl ReadBuffer for PixelRun {
+    type Word = u8;
+
+    unsafe fn read_buffer(&self) -> (*const Self::Word, usize) {
+        (self.ptr, self.len)
+    }
+}
+
+/// A failed pin write, from pins that can fail such as ones behind an I/O expander; the
+/// driver keeps the first one until `take_pin_error()`, see `checked()`. Never `Error::Spi`,
+/// the DMA path has no SPI error to report.
+pub type DmaPinError<CS, DC, RST> =
+    Error<Infallible, <CS as ErrorType>::Error, <DC as ErrorType>::Error, <RST as ErrorType>::Error>;
+
+/// Per-row raster hook, called with the display row and its big-endian RGB565 bytes,
+/// see `set_row_hook()`.
+pub type RowHook<'a> = &'a mut dyn FnMut(u16, &mut [u8]);
+
+/// The pixel stream while it is set up, see `start_pixels()`.
+pub(super) type PixelTransfer<SPI, DMA, const CHANNEL: u8, const S: u8> =
+    Transfer<StreamX<DMA, S>, CHANNEL, Tx<SPI>, MemoryToPeripheral, PixelRun>;
+
+/// Length of the window script: CASET + 4 params, RASET + 4 params, RAMWR.
+const WINDOW_SCRIPT_LEN: usize = 11;
+
+/// DC level of each run of the window script, as (length, is data).
+const WINDOW_SCRIPT_DC: [(usize, bool); 5] = [(1, false), (4, true), (1, false), (4, true), (1, false)];
+
+/// One run of the window script prepared at the start of the chunk buffer.
+struct ScriptRun {
+    buf: &'static mut [u8],
+    start: usize,
+    len: usize,
+}
+
+unsafe impl ReadBuffer for ScriptRun {
+    type Word = u8;
+
+    unsafe fn read_buffer(&self) -> (*const Self::Word, usize) {
+        (unsafe { self.buf.as_ptr().add(self.start) }, self.len)
+    }
+}
+
+/// Says `STREAM` on `CHANNEL` carries the TX DMA requests of the SPI implementing it.
+/// Implemented for exactly the SPI TX routes of the STM32F401 (RM0368 tables 27/28), each
+/// also checked against the HAL's `DMASet` map. A stream or channel the SPI isn't wired to
+/// fails to compile with the valid choices spelled out, instead of bringing up a driver
+/// whose transfers never complete.
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` TX DMA requests are not routed to `{STREAM}` on channel {CHANNEL}",
+    label = "no TX DMA route for this SPI, stream and channel",
+    note = "SPI TX routes on the F401: SPI1 = DMA2 stream 3 or 5, channel 3; SPI2 = DMA1 stream 4, channel 0; SPI3 = DMA1 stream 5 or 7, channel 0; SPI4 = DMA2 stream 1, channel 4 or stream 4, channel 5",
+    note = "take the stream from `StreamsTuple::new(dp.DMAx)` and set CHANNEL in the driver's type to match"
+)]
+pub trait TxDmaRoute<STREAM, const CHANNEL: u8>: DMASet<STREAM, CHANNEL, MemoryToPeripheral> {}
+
+macro_rules! tx_dma_routes {
+    ($(($spi:ty, $dma:ty, $stream:literal, $channel:literal)),+ $(,)?) => {
+        $(impl TxDmaRoute<StreamX<$dma, $stream>, $channel> for $spi {})+
+    };
+}
+
+tx_dma_routes!(
+    (pac::SPI1, pac::DMA2, 3, 3),
+    (pac::SPI1, pac::DMA2, 5, 3),
+    (pac::SPI2, pac::DMA1, 4, 0),
+    (pac::SPI3, pac::DMA1, 5, 0),
+    (pac::SPI3, pac::DMA1, 7, 0),
+    (pac::SPI4, pac::DMA2, 1, 4),
+    (pac::SPI4, pac::DMA2, 4, 5),
+);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, defmt::Format)]
+pub enum WindowError {
+    /// The area reaches past the screen; the data would land misaligned if clipped.
+    OffScreen,
+    /// `data` doesn't hold 2 bytes for every pixel of the area.
+    Length { expected: usize, actual: usize },
+    /// `data` is somewhere the DMA can't read from, see `check_dma_capable()`.
+    Memory(MemoryError),
+}
 
 pub struct ST7789V2DMA<
     'a,
@@ -32,26 +167,56 @@ pub struct ST7789V2DMA<
     const W: usize = 240,
     const H: usize = 280,
     const OFFSET: usize = 20,
+    CMD = SharedStream,
 > where
-    SPI: Instance + DMASet<StreamX<DMA, S>, CHANNEL, MemoryToPeripheral>,
+    SPI: Instance + TxDmaRoute<StreamX<DMA, S>, CHANNEL>,
+    DMA: stm32f4xx_hal::dma::traits::Instance,
+    StreamX<DMA, S>: Stream,
 {
     pub(super) cs: CS,
     pub(super) dc: DC,
-    rst: RST,
-    pub(super) tx: Option<Tx<SPI>>,
-    pub(super) st: Option<StreamX<DMA, S>>,
+    pub(super) rst: RST,
+    tx: Option<Tx<SPI>>,
+    st: Option<StreamX<DMA, S>>,
+    /// The pixel stream's transfer, kept set up between runs while commands have their own stream.
+    pixel: Option<PixelTransfer<SPI, DMA, CHANNEL, S>>,
     pub d: &'a mut Delay,
     cmd_buf: Option<&'static mut [u8; 1]>,
     data_buf: Option<&'static mut [u8; 1]>,
     caset_buf: Option<&'static mut [u8; 4]>, // Column address set buffer (user-provided)
     raset_buf: Option<&'static mut [u8; 4]>, // Row address set buffer (user-provided)
-    pub(super) chunk_buffer: Option<&'static mut [u8; CHUNK_SIZE]>,
+    chunk_buffer: Option<&'static mut [u8]>,
+    cmd_stream: CMD,
+    pub(super) stats: ErrorStats,
+    pub(super) shadow: ShadowRegisters,
+    pub(super) init_fill: Option<Rgb565>,
+    pub(super) fast_init: bool,
+    /// Skips the short per-command waits, see `InitSm`.
+    pub(super) skip_waits: bool,
+    pin_settle_us: u32,
+    chunk_gap_us: u32,
+    column_offset: u16,
+    color_filter: ColorFilter,
+    dma_priority: Priority,
+    pub(super) pause_while: Option<&'a dyn Fn() -> bool>,
+    bus_hooks: Option<&'a mut dyn BusHooks>,
+    bus_meter: BusMeter,
+    idle_blank: IdleBlank,
+    backlight: Option<&'a mut dyn Backlight>,
+    pin_error: Option<DmaPinError<CS, DC, RST>>,
+    row_hook: Option<RowHook<'a>>,
+    #[cfg(feature = "frame-capture")]
+    pub(super) capture: Option<&'a mut dyn FrameSink>,
+    #[cfg(feature = "record-commands")]
+    pub(super) recorder: CommandRecorder,
+    #[cfg(feature = "fault-injection")]
+    chaos: Option<Chaos>,
 }
 
 impl<'a, SPI, DMA, CS, DC, RST, const CHANNEL: u8, const S: u8, const W: usize, const H: usize, const OFFSET: usize>
     ST7789V2DMA<'a, SPI, DMA, CS, DC, RST, CHANNEL, S, W, H, OFFSET>
 where
-    SPI: Instance + DMASet<StreamX<DMA, S>, CHANNEL, MemoryToPeripheral>,
+    SPI: Instance + TxDmaRoute<StreamX<DMA, S>, CHANNEL>,
     CS: OutputPin,
     DC: OutputPin,
     RST: OutputPin,
@@ -59,7 +224,9 @@ where
     StreamX<DMA, S>: Stream,
     ChannelX<CHANNEL>: Channel,
 {
-    pub fn new(
+    /// `chunk_buffer` can be any size from `MIN_CHUNK_SIZE` up that is a multiple of 4, e.g.
+    /// `CHUNK_SIZE`; bigger chunks mean fewer DMA restarts per frame.
+    pub fn new<const CHUNK: usize>(
         cs: CS,
         dc: DC,
         rst: RST,
@@ -70,81 +237,324 @@ where
         data_buf: &'static mut [u8; 1],
         caset_buf: &'static mut [u8; 4], // User-provided column address buffer
         raset_buf: &'static mut [u8; 4], // User-provided row address buffer
-        chunk_buffer: &'static mut [u8; CHUNK_SIZE],
+        chunk_buffer: &'static mut [u8; CHUNK],
     ) -> Self {
+        const { check_chunk_size(CHUNK) };
         Self {
             cs,
             dc,
             rst,
             tx: Some(tx),
             st: Some(st),
+            pixel: None,
             d,
             cmd_buf: Some(cmd_buf),
             data_buf: Some(data_buf),
             caset_buf: Some(caset_buf),
             raset_buf: Some(raset_buf),
             chunk_buffer: Some(chunk_buffer),
+            cmd_stream: SharedStream,
+            stats: ErrorStats::default(),
+            shadow: ShadowRegisters::default(),
+            init_fill: None,
+            fast_init: false,
+            skip_waits: false,
+            pin_settle_us: 0,
+            chunk_gap_us: 0,
+            column_offset: 0,
+            color_filter: ColorFilter::None,
+            dma_priority: Priority::Medium,
+            pause_while: None,
+            bus_hooks: None,
+            bus_meter: BusMeter::default(),
+            idle_blank: IdleBlank::default(),
+            backlight: None,
+            pin_error: None,
+            row_hook: None,
+            #[cfg(feature = "frame-capture")]
+            capture: None,
+            #[cfg(feature = "record-commands")]
+            recorder: CommandRecorder::default(),
+            #[cfg(feature = "fault-injection")]
+            chaos: None,
+        }
+    }
+
+    /// Creates a new instance of the driver with all DMA buffers carved from `arena`, the
+    /// chunk buffer being CHUNK bytes. Get the arena with `DmaArena::take()`.
+    #[cfg(feature = "arena")]
+    pub fn new_with_arena<const CHUNK: usize>(
+        cs: CS,
+        dc: DC,
+        rst: RST,
+        tx: Tx<SPI>,
+        st: StreamX<DMA, S>,
+        d: &'a mut Delay,
+        arena: &'static mut DmaArena<CHUNK>,
+    ) -> Self {
+        let DmaArena {
+            cmd_buf,
+            data_buf,
+            caset_buf,
+            raset_buf,
+            chunk_buffer,
+        } = arena;
+
+        Self::new(cs, dc, rst, tx, st, d, cmd_buf, data_buf, caset_buf, raset_buf, chunk_buffer)
+    }
+}
+
+impl<'a, SPI, DMA, CS, DC, RST, const CHANNEL: u8, const S: u8, const W: usize, const H: usize, const OFFSET: usize, CMD>
+    ST7789V2DMA<'a, SPI, DMA, CS, DC, RST, CHANNEL, S, W, H, OFFSET, CMD>
+where
+    SPI: Instance + TxDmaRoute<StreamX<DMA, S>, CHANNEL>,
+    CS: OutputPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DMA: rcc::Enable + rcc::Reset + stm32f4xx_hal::dma::traits::Instance,
+    StreamX<DMA, S>: Stream,
+    ChannelX<CHANNEL>: Channel,
+    CMD: CommandStream,
+{
+    /// Moves command and parameter transfers onto `cmd_stream`, e.g. a `DedicatedStream`,
+    /// leaving the pixel stream for RAMWR data only. The pixel stream is then set up by the
+    /// first pixel transfer and stays set up; `set_dma_priority()` is the only thing that resets it.
+    pub fn with_command_stream<C: CommandStream>(
+        self,
+        cmd_stream: C,
+    ) -> ST7789V2DMA<'a, SPI, DMA, CS, DC, RST, CHANNEL, S, W, H, OFFSET, C> {
+        ST7789V2DMA {
+            cs: self.cs,
+            dc: self.dc,
+            rst: self.rst,
+            tx: self.tx,
+            st: self.st,
+            pixel: self.pixel,
+            d: self.d,
+            cmd_buf: self.cmd_buf,
+            data_buf: self.data_buf,
+            caset_buf: self.caset_buf,
+            raset_buf: self.raset_buf,
+            chunk_buffer: self.chunk_buffer,
+            cmd_stream,
+            stats: self.stats,
+            shadow: self.shadow,
+            init_fill: self.init_fill,
+            fast_init: self.fast_init,
+            skip_waits: self.skip_waits,
+            pin_settle_us: self.pin_settle_us,
+            chunk_gap_us: self.chunk_gap_us,
+            column_offset: self.column_offset,
+            color_filter: self.color_filter,
+            dma_priority: self.dma_priority,
+            pause_while: self.pause_while,
+            bus_hooks: self.bus_hooks,
+            bus_meter: self.bus_meter,
+            idle_blank: self.idle_blank,
+            backlight: self.backlight,
+            pin_error: self.pin_error,
+            row_hook: self.row_hook,
+            #[cfg(feature = "frame-capture")]
+            capture: self.capture,
+            #[cfg(feature = "record-commands")]
+            recorder: self.recorder,
+            #[cfg(feature = "fault-injection")]
+            chaos: self.chaos,
         }
     }
 
     pub fn init(&mut self){
+        self.init_deferred_on();
+        self.show();
+    }
+
+    /// Runs `init()` without the final DISPON, leaving the panel dark. Draw the first frame,
+    /// then call `show()`, so the uninitialized RAM content is never visible at power-up.
+    pub fn init_deferred_on(&mut self) {
         // Initialization sequence for ST7789V2
         // This method should be called after creating the instance to initialize the display.
-        // Order of commands:
-        // 1. Software reset
-        // 2. Sleep out
-        // 3. Set color mode
-        // 4. Memory data access control
-        // 5. Display on
-
-        self.rst.set_low().ok();
-        self.d.delay_ms(120);
-        self.rst.set_high().ok();
-        self.d.delay_ms(150);
-        debug!("Hardware reset completed in init()");
-
-        // Use macros for proper CS timing - CS stays low during delay for command processing
-        cs_command!(self, Commands::SoftwareReset, 150);
-        debug!("Software reset step completed in init()");
+        // Order of commands (see InitStep):
+        // 1. Hardware reset
+        // 2. Software reset
+        // 3. Sleep out
+        // 4. Configuration, from the shadow registers, so settings made before init() are kept
+        // 5. RAM fill, if set with set_init_fill()
+        // 6. Display on, done by show() in init()
 
-        cs_command!(self, Commands::SleepOut, 120);
-        debug!("Sleep out step completed in init()");
+        for step in self.init_steps().take_while(|&step| step != InitStep::DisplayOn) {
+            let wait = self.run_init_step(step);
+            self.d.delay_ms(wait);
+        }
+    }
 
-        cs_command!(self, Commands::SetColorMode, 1);
-        cs_data!(self, ColorMode::RGB565 as u8, 10);
-        debug!("Set color mode step completed in init()");
+    /// Turns the display on (DISPON), e.g. after `init_deferred_on()` and the first draw.
+    pub fn show(&mut self) {
+        let wait = self.run_init_step(InitStep::DisplayOn);
+        self.d.delay_ms(wait);
+    }
+
+    /// Makes `init()` fill the whole frame memory with `color` before DISPON, so the panel
+    /// comes up showing that color instead of random RAM content. `None` (the default) skips
+    /// the fill, which takes about 100 ms at 12 MHz.
+    pub fn set_init_fill(&mut self, color: Option<Rgb565>) {
+        self.init_fill = color;
+    }
+
+    /// Hardware-resets the panel and runs `init()` again, restoring the exact configuration
+    /// from the shadow registers (orientation, mirroring, inversion, scroll offset, brightness).
+    /// Always takes the conservative path, see `set_fast_init()`.
+    pub fn reinit(&mut self) {
+        let display_on = self.shadow.display_on;
+        let fast_init = core::mem::replace(&mut self.fast_init, false);
+        self.init_deferred_on();
+        if display_on {
+            self.show();
+        }
+        self.fast_init = fast_init;
+    }
 
-        cs_command!(self, Commands::MemoryDataAccessControl, 1);
-        cs_data!(self, 0b0000_0000, 10); // Set to normal mode (no rotation)
-        debug!("Memory data access control step completed in init()");
+    /// Puts the panel to sleep (SLPIN). The configuration is kept in the shadow registers.
+    pub fn sleep(&mut self) {
+        cs_command!(self, Commands::SleepIn, 5);
+    }
 
-        cs_command!(self, Commands::InversionOn, 1);
-        debug!("Inversion on step completed in init()");
+    /// Wakes the panel (SLPOUT) and rewrites every configuration register from the shadow
+    /// copy, so the panel ends up in the exact state it was in before `sleep()`.
+    pub fn resume(&mut self) {
+        cs_command!(self, Commands::SleepOut, 120);
+        self.write_config();
+        if self.shadow.display_on {
+            cs_command!(self, Commands::DisplayOn, 50);
+        }
+    }
 
-        cs_command!(self, Commands::DisplayOn, 50);
-        debug!("Display on step completed in init()");
+    /// Writes every shadowed configuration register to the panel.
+    pub(super) fn write_config(&mut self) {
+        for register in ConfigRegister::ALL {
+            self.write_register(register);
+        }
+    }
 
+    /// Writes one register from the shadow copy, see `ConfigRegister::packets()`.
+    fn write_register(&mut self, register: ConfigRegister) {
+        let shadow = self.shadow;
+        register.packets(&shadow, |packet| {
+            self.command(packet.command, packet.params());
+            self.command_wait(packet.delay_ms);
+        });
     }
 
+    #[deprecated(note = "use `set_window()`, which takes a `Rectangle` and validates it")]
     pub fn set_size(&mut self, xs: u16, xe: u16, ys: u16, ye: u16) {
+        self.set_window_inclusive(xs, xe, ys, ye);
+    }
+
+    /// Sets CASET and RASET to `area` in display coordinates, clamped to the screen. Returns
+    /// the clamped window, which is how many pixels the next RAMWR expects. An area with no
+    /// pixel on the screen leaves the window untouched and fails with `OutOfRange` for the
+    /// coordinate past the right or bottom edge, or `Empty` for a zero-sized area or one
+    /// entirely left of or above the screen.
+    pub fn set_window(&mut self, area: &Rectangle) -> Result<Rectangle, ParamError> {
+        let screen = self.bounding_box();
+        let Some(window) = Window::clip(area, &screen) else {
+            let (x, y) = (area.top_left.x.clamp(0, u16::MAX as i32) as u16, area.top_left.y.clamp(0, u16::MAX as i32) as u16);
+            let (max_x, max_y) = (screen.size.width as u16 - 1, screen.size.height as u16 - 1);
+            return Err(if area.is_zero_sized() {
+                ParamError::Empty
+            } else if x > max_x {
+                ParamError::OutOfRange { value: x, max: max_x }
+            } else if y > max_y {
+                ParamError::OutOfRange { value: y, max: max_y }
+            } else {
+                ParamError::Empty
+            });
+        };
+
+        self.set_window_inclusive(window.xs, window.xe, window.ys, window.ye);
+        Ok(window.rectangle())
+    }
+
+    pub(super) fn set_window_inclusive(&mut self, xs: u16, xe: u16, ys: u16, ye: u16) {
         // sets CASET and RASET based on given width and height
-        // accounts for offset based on OFFSET
+        // accounts for offset based on OFFSET, the column offset, orientation and mirroring (see Transform::ram_offset_at)
+
+        #[cfg(feature = "frame-capture")]
+        if let Some(sink) = self.capture.as_deref_mut() {
+            sink.window(xs, xe, ys, ye);
+        }
+
+        let (x_offset, y_offset) = self.ram_offset();
+        self.set_ram_window(xs + x_offset, xe + x_offset, ys + y_offset, ye + y_offset);
+    }
+
+    /// Sets the window like `set_window()` (inclusive bounds, unchecked) and sends RAMWR, leaving CS low and DC high so pixel
+    /// data can follow right away with `with_chunk()`/`stream_chunks()`; `deselect()` when done.
+    /// CASET, RASET and RAMWR go out as one prepared byte script in a single CS cycle,
+    /// toggling DC between runs, instead of five CS cycles with millisecond delays.
+    pub fn begin_region(&mut self, xs: u16, xe: u16, ys: u16, ye: u16) {
+        self.wake();
+
+        #[cfg(feature = "frame-capture")]
+        if let Some(sink) = self.capture.as_deref_mut() {
+            sink.window(xs, xe, ys, ye);
+        }
+
+        let (x_offset, y_offset) = self.ram_offset();
+        self.send_window_script(xs + x_offset, xe + x_offset, ys + y_offset, ye + y_offset);
+    }
 
-        let actual_ys = ys + OFFSET as u16;
-        let actual_ye = ye + OFFSET as u16;
+    fn send_window_script(&mut self, xs: u16, xe: u16, ys: u16, ye: u16) {
+        let chunk = self.chunk_buffer.take().unwrap();
+        chunk[0] = Commands::CASET as u8;
+        chunk[1..5].copy_from_slice(&encode::caset(xs, xe));
+        chunk[5] = Commands::RASET as u8;
+        chunk[6..10].copy_from_slice(&encode::raset(ys, ye));
+        chunk[10] = Commands::RAMWR as u8;
 
+        #[cfg(feature = "record-commands")]
+        for (i, &byte) in chunk[..WINDOW_SCRIPT_LEN].iter().enumerate() {
+            match i {
+                0 | 5 | 10 => self.recorder.command(byte),
+                _ => self.recorder.param(byte),
+            }
+        }
+
+        self.select();
+
+        let mut run = ScriptRun { buf: chunk, start: 0, len: 0 };
+        for (len, data) in WINDOW_SCRIPT_DC {
+            self.set_dc(data);
+
+            run.len = len;
+            let (sent, _) = self.transfer_small(run);
+            run = sent;
+            run.start += len;
+        }
+        debug_assert_eq!(run.start, WINDOW_SCRIPT_LEN);
+
+        self.set_dc(true);
+        self.chunk_buffer = Some(run.buf);
+    }
+
+    /// Waits until the last byte handed to the SPI has been shifted out, so DC can change.
+    /// The DMA is done once the last byte is in the data register, possibly before BSY is
+    /// set for it; the reference manual's sequence waits for TXE = 1, then BSY = 0.
+    fn wait_tx_idle() {
+        // SAFETY: only the status register is read
+        let spi = unsafe { &*SPI::ptr() };
+        while spi.sr().read().txe().bit_is_clear() {}
+        while spi.sr().read().bsy().bit_is_set() {}
+    }
+
+    /// Sets CASET and RASET as-is, in the RAM address space of the current MADCTL,
+    /// without the visible window's offset. Lets callers reach the rows the glass doesn't show;
+    /// see `ram_view()`.
+    pub fn set_ram_window(&mut self, xs: u16, xe: u16, ys: u16, ye: u16) {
         let caset_buf = self.caset_buf.take().unwrap();
         let raset_buf = self.raset_buf.take().unwrap();
 
-        caset_buf[0] = (xs >> 8) as u8; // Start column MSB
-        caset_buf[1] = (xs & 0xFF) as u8; // Start column LSB
-        caset_buf[2] = (xe >> 8) as u8; // End column MSB
-        caset_buf[3] = (xe & 0xFF) as u8; // End column LSB
-
-        raset_buf[0] = (actual_ys >> 8) as u8; // Start row MSB
-        raset_buf[1] = (actual_ys & 0xFF) as u8; // Start row LSB
-        raset_buf[2] = (actual_ye >> 8) as u8; // End row MSB
-        raset_buf[3] = (actual_ye & 0xFF) as u8; // End row LSB
+        *caset_buf = encode::caset(xs, xe);
+        *raset_buf = encode::raset(ys, ye);
 
         self.caset_buf = Some(caset_buf);
         self.raset_buf = Some(raset_buf);
@@ -154,39 +564,467 @@ where
 
     }
 
+    /// Sets the panel's color order and updates MADCTL accordingly.
+    /// Use `ColorOrder::Bgr` for panels wired BGR, so Rgb565 colors drawn through
+    /// the DrawTarget come out with red and blue in the right place.
+    pub fn set_color_order(&mut self, order: ColorOrder) {
+        self.shadow.color_order = order;
+        self.write_madctl();
+    }
+
+    pub fn color_order(&self) -> ColorOrder {
+        self.shadow.color_order
+    }
+
+    /// Rotates the display. The logical size (`bounding_box()`) and the RAM offsets follow.
+    pub fn set_orientation(&mut self, orientation: Orientation) {
+        self.shadow.transform.orientation = orientation;
+        self.write_madctl();
+    }
+
+    pub fn orientation(&self) -> Orientation {
+        self.shadow.transform.orientation
+    }
+
+    /// Mirrors the image horizontally, e.g. for panels viewed through a mirror.
+    /// Independent of rotation; the RAM offset is adjusted automatically.
+    pub fn mirror_x(&mut self, enable: bool) {
+        self.shadow.transform.mirror_x = enable;
+        self.write_madctl();
+    }
+
+    /// Mirrors the image vertically, e.g. for panels mounted upside down behind glass.
+    /// Independent of rotation; the RAM offset is adjusted automatically.
+    pub fn mirror_y(&mut self, enable: bool) {
+        self.shadow.transform.mirror_y = enable;
+        self.write_madctl();
+    }
+
+    /// Switches COLMOD. Private: the pixel pipeline has to match, see `rgb666()`.
+    pub(super) fn set_color_mode(&mut self, mode: ColorMode) {
+        self.shadow.color_mode = mode;
+        cs_command!(self, Commands::SetColorMode, 1);
+        cs_data!(self, mode as u8, 10);
+    }
+
+    pub fn color_mode(&self) -> ColorMode {
+        self.shadow.color_mode
+    }
+
+    /// Turns color inversion on or off (INVON/INVOFF).
+    /// This panel needs inversion on to show colors correctly, which is the default.
+    pub fn set_inversion(&mut self, inverted: bool) {
+        self.shadow.inverted = inverted;
+        let cmd = if inverted { Commands::InversionOn } else { Commands::InversionOff };
+        cs_command!(self, cmd, 1);
+    }
+
+    pub fn inverted(&self) -> bool {
+        self.shadow.inverted
+    }
+
+    /// Sets the gamma curves (PVGAMCTRL/NVGAMCTRL). They are kept across `sleep()`/`reinit()`,
+    /// `None` falls back to the controller's defaults from the next `reinit()` on.
+    pub fn set_gamma(&mut self, gamma: Option<GammaCurves>) {
+        self.shadow.gamma = gamma;
+        self.write_gamma();
+    }
+
+    pub fn gamma(&self) -> Option<GammaCurves> {
+        self.shadow.gamma
+    }
+
+    /// Uploads the red and blue digital gamma tables and enables digital gamma (DGMEN),
+    /// or disables it with `None`. Kept across `sleep()`/`reinit()` like the analog curves.
+    pub fn set_digital_gamma(&mut self, gamma: Option<DigitalGamma>) {
+        self.shadow.digital_gamma = gamma;
+        self.write_digital_gamma();
+    }
+
+    pub fn digital_gamma(&self) -> Option<DigitalGamma> {
+        self.shadow.digital_gamma
+    }
+
+    /// Sets the panel's color enhancement (WRCACE), off by default.
+    pub fn set_color_enhancement(&mut self, enhancement: ColorEnhancement) {
+        self.shadow.color_enhancement = enhancement;
+        self.write_color_enhancement();
+    }
+
+    pub fn color_enhancement(&self) -> ColorEnhancement {
+        self.shadow.color_enhancement
+    }
+
+    /// Sets the panel refresh rate in normal mode (FRCTRL2), 60 Hz by default.
+    pub fn set_frame_rate(&mut self, rate: FrameRate) {
+        self.shadow.frame_rate = rate;
+        self.write_frame_rate();
+    }
+
+    pub fn frame_rate(&self) -> FrameRate {
+        self.shadow.frame_rate
+    }
+
+    /// Sets the back and front porch in normal mode (PORCTRL), 12 lines each by default.
+    pub fn set_porch(&mut self, porch: Porch) {
+        self.shadow.porch = porch;
+        self.write_porch();
+    }
+
+    pub fn porch(&self) -> Porch {
+        self.shadow.porch
+    }
+
+    /// Switches to 8-color idle mode (IDMON): each channel is reduced to its MSB, which cuts
+    /// the source driver's power. Drawing still works, the colors are just coarse.
+    pub fn set_idle_mode(&mut self, enable: bool) {
+        self.shadow.idle_mode = enable;
+        self.write_idle_mode();
+    }
+
+    pub fn idle_mode(&self) -> bool {
+        self.shadow.idle_mode
+    }
+
+    /// Applies the refresh rate, idle mode and brightness of `profile` in the order that
+    /// avoids a visible glitch: leaving idle mode first when powering up, entering it last
+    /// when powering down. The host side, how often the application redraws, is
+    /// `profile.host_fps()`, e.g. for `RefreshTimer::set_rate()`.
+    pub fn set_power_profile(&mut self, profile: Profile) {
+        if !profile.idle_mode() {
+            self.set_idle_mode(false);
+        }
+        self.set_frame_rate(profile.frame_rate());
+        self.set_brightness(profile.brightness());
+        if profile.idle_mode() {
+            self.set_idle_mode(true);
+        }
+    }
+
+    /// Applies the brightness and gamma of a `DimSchedule` update. The theme is the
+    /// application's to switch, see `ScheduleUpdate::period`.
+    pub fn apply_schedule(&mut self, update: &ScheduleUpdate) {
+        if let Some(period) = update.period
+            && period.gamma != self.shadow.gamma
+        {
+            self.set_gamma(period.gamma);
+        }
+        if let Some(brightness) = update.brightness {
+            self.set_brightness(brightness);
+        }
+    }
+
+    /// Returns the profile the current settings match, if any.
+    pub fn power_profile(&self) -> Option<Profile> {
+        [Profile::Full, Profile::Reduced, Profile::Minimal].into_iter().find(|profile| {
+            profile.frame_rate() == self.shadow.frame_rate
+                && profile.idle_mode() == self.shadow.idle_mode
+                && profile.brightness() == self.shadow.brightness
+        })
+    }
+
+    /// Enables the TE output (TEON, V-blank pulses only) or disables it (TEOFF).
+    /// Count the pulses with a `TeCounter`.
+    pub fn set_tearing_effect(&mut self, enable: bool) {
+        self.shadow.tearing_effect = enable;
+        self.write_tearing_effect();
+    }
+
+    pub fn tearing_effect(&self) -> bool {
+        self.shadow.tearing_effect
+    }
+
+    /// Sets the vertical scroll start address (VSCSAD), the RAM line shown at the top of the screen.
+    /// # Errors
+    /// `ParamError::OutOfRange` if `offset` is past the last RAM line; nothing is sent.
+    pub fn set_scroll_offset(&mut self, offset: u16) -> Result<(), ParamError> {
+        self.shadow.scroll_offset = check_max(offset, RAM_HEIGHT as u16 - 1)?;
+        self.write_scroll_offset();
+        Ok(())
+    }
+
+    /// Sets the first visible RAM column, for panels narrower than the 240 RAM columns whose
+    /// glass doesn't start at column 0 (e.g. 135 or 172 wide modules centered in RAM).
+    /// Like OFFSET for rows it is given in the native portrait orientation; rotations and
+    /// mirroring are accounted for, so `clear()` and `bounding_box()` keep covering exactly the glass.
+    /// # Errors
+    /// `ParamError::OutOfRange` if the window would run past the last RAM column; nothing changes.
+    pub fn set_column_offset(&mut self, offset: u16) -> Result<(), ParamError> {
+        self.column_offset = check_max(offset, (RAM_WIDTH - W) as u16)?;
+        Ok(())
+    }
+
+    pub fn column_offset(&self) -> u16 {
+        self.column_offset
+    }
+
+    /// RAM (column, row) of the visible window's top left corner in the current MADCTL address space.
+    pub(super) fn ram_offset(&self) -> (u16, u16) {
+        self.shadow.transform.ram_offset_at::<W, H>(self.column_offset, OFFSET as u16)
+    }
+
+    pub fn scroll_offset(&self) -> u16 {
+        self.shadow.scroll_offset
+    }
+
+    /// Sets the vertical scroll area (VSCRDEF): fixed bands at the top and bottom of RAM
+    /// and the band in between that `set_scroll_offset()` scrolls.
+    pub fn set_scroll_area(&mut self, area: ScrollArea) {
+        self.shadow.scroll_area = area;
+        self.write_scroll_area();
+    }
+
+    pub fn scroll_area(&self) -> ScrollArea {
+        self.shadow.scroll_area
+    }
+
+    /// Restricts the display to `area` (PTLAR + PTLON), or back to normal mode (NORON) with `None`.
+    /// Rows outside the partial area show the non-display color.
+    pub fn set_partial_area(&mut self, area: Option<PartialArea>) {
+        self.shadow.partial_area = area;
+        self.write_partial_area();
+    }
+
+    pub fn partial_area(&self) -> Option<PartialArea> {
+        self.shadow.partial_area
+    }
+
+    /// Sets the display brightness (WRDISBV), 0 is darkest and 255 brightest.
+    /// Only has an effect on modules whose backlight is driven by the controller's CABC output.
+    pub fn set_brightness(&mut self, brightness: u8) {
+        self.shadow.brightness = brightness;
+        self.write_brightness();
+    }
+
+    pub fn brightness(&self) -> u8 {
+        self.shadow.brightness
+    }
+
+    /// Returns the logical size in the current orientation.
+    pub fn size(&self) -> (usize, usize) {
+        self.shadow.transform.size::<W, H>()
+    }
+
+    /// Returns the current display/panel coordinate transform, e.g. to map touch reports.
+    pub fn transform(&self) -> Transform {
+        self.shadow.transform
+    }
+
+    fn write_madctl(&mut self) {
+        self.write_register(ConfigRegister::Madctl);
+    }
+
+    fn write_scroll_offset(&mut self) {
+        self.write_register(ConfigRegister::ScrollOffset);
+    }
+
+    fn write_scroll_area(&mut self) {
+        self.write_register(ConfigRegister::ScrollArea);
+    }
+
+    fn write_partial_area(&mut self) {
+        self.write_register(ConfigRegister::PartialArea);
+    }
+
+    fn write_gamma(&mut self) {
+        self.write_register(ConfigRegister::Gamma);
+    }
+
+    fn write_digital_gamma(&mut self) {
+        self.write_register(ConfigRegister::DigitalGamma);
+    }
+
+    fn write_color_enhancement(&mut self) {
+        self.write_register(ConfigRegister::ColorEnhancement);
+    }
+
+    fn write_porch(&mut self) {
+        self.write_register(ConfigRegister::Porch);
+    }
+
+    fn write_frame_rate(&mut self) {
+        self.write_register(ConfigRegister::FrameRate);
+    }
+
+    fn write_idle_mode(&mut self) {
+        self.write_register(ConfigRegister::IdleMode);
+    }
+
+    fn write_tearing_effect(&mut self) {
+        self.write_register(ConfigRegister::TearingEffect);
+    }
+
+    fn write_brightness(&mut self) {
+        self.write_register(ConfigRegister::Brightness);
+    }
+
+    /// Returns the display-link error counters and the last error seen.
+    pub fn error_stats(&self) -> ErrorStats {
+        self.stats
+    }
+
+    /// Checks that every buffer handed to `new()` is reachable by the DMA, e.g. not in CCM RAM,
+    /// where transfers fail with nothing but a transfer error. Debug builds only, see
+    /// `check_dma_capable()`.
+    pub fn check_buffers(&self) -> Result<(), MemoryError> {
+        let small = [self.cmd_buf.as_deref(), self.data_buf.as_deref()];
+        let params = [self.caset_buf.as_deref(), self.raset_buf.as_deref()];
+        small.into_iter().flatten().try_for_each(|buf| check_dma_capable(buf))?;
+        params.into_iter().flatten().try_for_each(|buf| check_dma_capable(buf))?;
+        self.chunk_buffer.as_deref().map_or(Ok(()), check_dma_capable)
+    }
+
+    pub fn clear_error_stats(&mut self) {
+        self.stats = ErrorStats::default();
+    }
+
+    /// Calls `hook(y, row)` on every display row drawn through the DrawTarget or
+    /// `draw_entire_screen()` just before it's sent, with the row's big-endian RGB565 bytes,
+    /// for raster effects: palette cycling, scanline dimming, horizontal wobble.
+    /// Rows are sent whole per chunk while a hook is set. `None` removes it.
+    pub fn set_row_hook(&mut self, hook: Option<RowHook<'a>>) {
+        self.row_hook = hook;
+    }
+
+    /// Mirrors every window update and RAMWR byte into `sink` from now on, `None` stops capturing.
+    #[cfg(feature = "frame-capture")]
+    pub fn set_capture(&mut self, sink: Option<&'a mut dyn FrameSink>) {
+        self.capture = sink;
+    }
+
+    /// Injects seeded faults into every transfer from now on, see `Chaos`; `None` stops.
+    #[cfg(feature = "fault-injection")]
+    pub fn set_chaos(&mut self, chaos: Option<Chaos>) {
+        self.chaos = chaos;
+    }
+
+    #[cfg(feature = "fault-injection")]
+    pub fn chaos(&self) -> Option<&Chaos> {
+        self.chaos.as_ref()
+    }
+
+    /// Draws the injected fault for a transfer of `len` bytes and sits out its delay.
+    /// # Returns
+    /// How many of the bytes to send, and whether to count the transfer as failed.
+    #[cfg(feature = "fault-injection")]
+    fn inject_fault(&mut self, len: usize) -> (usize, bool) {
+        let Some(chaos) = self.chaos.as_mut() else {
+            return (len, false);
+        };
+        match chaos.next_fault(len) {
+            Fault::None => (len, false),
+            Fault::Error => (len, true),
+            Fault::Truncate(sent) => (sent, false),
+            Fault::Delay(us) => {
+                self.d.delay_us(us);
+                (len, false)
+            }
+        }
+    }
+
+    #[cfg(not(feature = "fault-injection"))]
+    #[inline(always)]
+    fn inject_fault(&mut self, len: usize) -> (usize, bool) {
+        (len, false)
+    }
+
+    /// Sends `cmd` followed by `params` as-is, for registers without a wrapper method.
+    /// The shadow registers are not updated, so prefer the typed setters where one exists.
+    /// The switch to data mode and the final deselect wait for the SPI to drain (see
+    /// `set_dc()`/`deselect()`), so the command byte and the last parameter arrive whole.
+    pub fn command(&mut self, cmd: Commands, params: &[u8]) {
+        self.select();
+        self.send_command(cmd);
+        for &param in params {
+            self.send_data_u8(param);
+        }
+        self.deselect();
+    }
+
+    /// Returns the recorded command/parameter/pixel sequence, e.g. to `log()` it after a fault.
+    #[cfg(feature = "record-commands")]
+    pub fn command_record(&self) -> &CommandRecorder {
+        &self.recorder
+    }
+
+    #[cfg(feature = "record-commands")]
+    pub fn clear_command_record(&mut self) {
+        self.recorder.clear();
+    }
+
+    /// Sends RAMWR for the window already set. See `draw_window()` for a checked one-shot.
     #[inline(always)]
     pub fn begin_draw(&mut self){
+        self.wake();
         cs_command!(self, Commands::RAMWR, 1);
     }
 
     pub fn off(&mut self) {
         cs_command!(self, Commands::DisplayOff, 50);
+        self.shadow.display_on = false;
     }
 
-    fn send_command(&mut self, cmd: Commands) {
-        let cmd_buf = self.cmd_buf.take().unwrap();
-        cmd_buf[0] = cmd as u8;
+    /// Sends one of the small command/parameter buffers on the command stream,
+    /// falling back to the pixel stream when there is no dedicated one.
+    /// # Returns
+    /// The buffer and whether a transfer error was detected.
+    fn transfer_small<B>(&mut self, buf: B) -> (B, bool)
+    where
+        B: ReadBuffer<Word = u8> + 'static,
+    {
+        // SAFETY: only the length is used, the pointer is dropped right away
+        let len = unsafe { buf.read_buffer() }.1;
+        let (sent, fail) = self.inject_fault(len);
+        if sent < len {
+            return (buf, false);
+        }
+        if fail {
+            self.stats.record(LinkError::Transfer);
+        }
+
+        // a pixel run still going here was abandoned by a forgotten FrameTransfer; stop it
+        // rather than have two streams feed the SPI at once
+        if let Some(tf) = self.pixel.as_mut() {
+            tf.pause(|_| {});
+        }
+
+        let buf = match self.cmd_stream.send(buf, &mut self.stats) {
+            Ok((buf, error)) => return (buf, error || fail),
+            Err(buf) => buf,
+        };
 
+        let config = self.dma_config();
+
+        self.release_pixels();
         let st = self.st.take().unwrap();
         let tx = self.tx.take().unwrap();
 
-        // Set DC mode (CS is handled externally by macro)
-        self.dc.set_low().ok(); // Command mode
+        let mut tf = Transfer::init_memory_to_peripheral(st, tx, buf, None, config);
+        tf.start(|_| {});
+        let error = self.stats.wait(&tf);
 
-        let config = DmaConfig::default()
-            .peripheral_increment(false)
-            .memory_increment(true)
-            .fifo_enable(false)
-            .transfer_complete_interrupt(false);
+        let (st, tx, buf, _) = tf.release();
+        self.st = Some(st);
+        self.tx = Some(tx);
 
+        (buf, error || fail)
+    }
 
-        let mut tf =
-            Transfer::init_memory_to_peripheral(st, tx, cmd_buf, None, config);
-        tf.start(|_| {});
-        tf.wait();
+    pub(super) fn send_command(&mut self, cmd: Commands) {
+        let cmd_buf = self.cmd_buf.take().unwrap();
+        cmd_buf[0] = cmd as u8;
+        #[cfg(feature = "record-commands")]
+        self.recorder.command(cmd as u8);
+
+        // Set DC mode (CS is handled externally by macro)
+        self.set_dc(false); // Command mode
 
-        // Check for transfer errors
-        if tf.is_transfer_error() {
+        #[cfg_attr(not(feature = "trace-transfers"), allow(unused_variables))]
+        let (cmd_buf, error) = self.transfer_small(cmd_buf);
+
+        // Check for transfer errors (compiled out unless `trace-transfers` is enabled)
+        #[cfg(feature = "trace-transfers")]
+        if error {
             debug!(
                 "ERROR: Transfer error detected in send_command for cmd 0x{:02X}",
                 cmd as u8
@@ -198,9 +1036,6 @@ where
             );
         }
 
-        let (st, tx, cmd_buf, _) = tf.release();
-        self.st = Some(st);
-        self.tx = Some(tx);
         self.cmd_buf = Some(cmd_buf);
 
         // CS stays low for external delay handling
@@ -209,26 +1044,18 @@ where
     fn send_data_u8(&mut self, data: u8){
         let data_buf = self.data_buf.take().unwrap();
         data_buf[0] = data;
-
-        let st = self.st.take().unwrap();
-        let tx = self.tx.take().unwrap();
+        #[cfg(feature = "record-commands")]
+        self.recorder.param(data);
 
         // Set DC mode (CS is handled externally by macro)
-        self.dc.set_high().ok(); // Data mode
-
-        let config = DmaConfig::default()
-            .peripheral_increment(false)
-            .memory_increment(true)
-            .fifo_enable(false)
-            .transfer_complete_interrupt(false);
+        self.set_dc(true); // Data mode
 
-        let mut tf =
-            Transfer::init_memory_to_peripheral(st, tx, data_buf, None, config);
-        tf.start(|_| {});
-        tf.wait();
+        #[cfg_attr(not(feature = "trace-transfers"), allow(unused_variables))]
+        let (data_buf, error) = self.transfer_small(data_buf);
 
-        // Check for transfer errors
-        if tf.is_transfer_error() {
+        // Check for transfer errors (compiled out unless `trace-transfers` is enabled)
+        #[cfg(feature = "trace-transfers")]
+        if error {
             debug!(
                 "ERROR: Transfer error detected in send_data_u8 for data 0x{:02X}",
                 data
@@ -240,9 +1067,6 @@ where
             );
         }
 
-        let (st, tx, data_buf, _) = tf.release();
-        self.st = Some(st);
-        self.tx = Some(tx);
         self.data_buf = Some(data_buf);
 
         // CS stays low for external delay handling
@@ -250,84 +1074,668 @@ where
 
     fn send_caset_data_safe(&mut self, delay_ms: u32){
         // CS is already LOW from macro, just send data
-        self.dc.set_high().ok(); // Data mode
-        
-        let config = DmaConfig::default()
-            .peripheral_increment(false)
-            .memory_increment(true)
-            .fifo_enable(false)
-            .transfer_complete_interrupt(false);
+        self.set_dc(true); // Data mode
 
-        let st = self.st.take().unwrap();
-        let tx = self.tx.take().unwrap();
         let caset_buf = self.caset_buf.take().unwrap();
-
-        let mut tf = Transfer::init_memory_to_peripheral(st, tx, caset_buf, None, config);
-        tf.start(|_| {});
-        tf.wait();
-        let (st, tx, caset_buf, _) = tf.release();
-        self.st = Some(st); // restoring the updated copy.
-        self.tx = Some(tx);
+        #[cfg(feature = "record-commands")]
+        caset_buf.iter().for_each(|&b| self.recorder.param(b));
+        let (caset_buf, _) = self.transfer_small(caset_buf);
         self.caset_buf = Some(caset_buf);
 
-        self.d.delay_ms(delay_ms); // Data processing delay
+        self.command_wait(delay_ms); // Data processing delay
     }
 
     fn send_raset_data_safe(&mut self, delay_ms: u32){
         // CS is already LOW from macro, just send data
-        self.dc.set_high().ok(); // Data mode
-        
-        let config = DmaConfig::default()
-            .peripheral_increment(false)
-            .memory_increment(true)
-            .fifo_enable(false)
-            .transfer_complete_interrupt(false);
+        self.set_dc(true); // Data mode
 
-        let st = self.st.take().unwrap();
-        let tx = self.tx.take().unwrap();
         let raset_buf = self.raset_buf.take().unwrap();
-
-        let mut tf = Transfer::init_memory_to_peripheral(st, tx, raset_buf, None, config);
-        tf.start(|_| {});
-        tf.wait();
-        let (st, tx, raset_buf, _) = tf.release();
-        self.st = Some(st);
-        self.tx = Some(tx);
+        #[cfg(feature = "record-commands")]
+        raset_buf.iter().for_each(|&b| self.recorder.param(b));
+        let (raset_buf, _) = self.transfer_small(raset_buf);
         self.raset_buf = Some(raset_buf);
 
-        self.d.delay_ms(delay_ms); // Data processing delay
+        self.command_wait(delay_ms); // Data processing delay
     }
 
-    pub fn send_data_chunk(&mut self, chunk: &'static mut [u8; CHUNK_SIZE]) -> &'static mut [u8; CHUNK_SIZE] {
-        let config = DmaConfig::default()
-            .peripheral_increment(false)
-            .memory_increment(true)
-            .fifo_enable(false)
-            .transfer_complete_interrupt(false);
+    /// Size of the chunk buffer given to `new()`.
+    pub fn chunk_size(&self) -> usize {
+        self.chunk_buffer.as_deref().map_or(0, <[u8]>::len)
+    }
 
-        let st = self.st.take().unwrap();
-        let tx = self.tx.take().unwrap();
+    /// Lends the driver's whole chunk buffer to `fill` and streams the bytes it wrote.
+    /// `fill` returns how many bytes of the buffer it filled; only that many are sent.
+    /// DC and CS are left untouched, so call this between `begin_draw()`/`select()` and `deselect()`.
+    /// # Returns
+    /// The number of bytes sent, 0 means `fill` had nothing more to write.
+    pub fn with_chunk<F>(&mut self, fill: F) -> usize
+    where
+        F: FnOnce(&mut [u8]) -> usize,
+    {
+        let chunk = self.chunk_buffer.take().unwrap();
+        let len = fill(chunk).min(chunk.len());
 
-        let mut tf = Transfer::init_memory_to_peripheral(st, tx, chunk, None, config);
-        tf.start(|_| {});
-        tf.wait();
-        let (st, tx, d, _) = tf.release();
-        self.st = Some(st);
-        self.tx = Some(tx);
-        d
+        if len == 0 {
+            self.chunk_buffer = Some(chunk);
+            return 0;
+        }
+
+        #[cfg(feature = "frame-capture")]
+        if let Some(sink) = self.capture.as_deref_mut() {
+            sink.pixels(&chunk[..len]);
+        }
+        #[cfg(feature = "record-commands")]
+        self.recorder.pixels(len);
+
+        self.wait_unpaused();
+        let (sent, fail) = self.inject_fault(len);
+        self.start_pixels(PixelRun::new(&chunk[..sent.max(1)]));
+        self.wait_pixels();
+        if fail {
+            self.stats.record(LinkError::Transfer);
+        }
+        self.end_pixels();
+        self.chunk_buffer = Some(chunk);
+
+        len
+    }
+
+    /// Streams `total_len` bytes of pixel data through the chunk buffer, calling `fill` once per chunk
+    /// with the slice to fill (the chunk buffer's size, except for the last one).
+    /// Fast path: the DMA transfer is configured once for the first chunk, after which only the
+    /// memory address and NDTR are updated between chunks instead of rebuilding the stream config.
+    /// Like `with_chunk`, DC and CS are left untouched.
+    pub fn stream_chunks<F>(&mut self, total_len: usize, fill: F)
+    where
+        F: FnMut(&mut [u8]),
+    {
+        self.stream_chunks_of(total_len, self.chunk_size(), fill);
+    }
+
+    /// `stream_chunks()` with chunks of at most `chunk_len` bytes.
+    fn stream_chunks_of<F>(&mut self, total_len: usize, chunk_len: usize, mut fill: F)
+    where
+        F: FnMut(&mut [u8]),
+    {
+        let chunk_len = chunk_len.min(self.chunk_size());
+        if total_len == 0 || chunk_len == 0 {
+            return;
+        }
+
+        let chunk = self.chunk_buffer.take().unwrap();
+
+        let len = total_len.min(chunk_len);
+        fill(&mut chunk[..len]);
+        #[cfg(feature = "frame-capture")]
+        if let Some(sink) = self.capture.as_deref_mut() {
+            sink.pixels(&chunk[..len]);
+        }
+        let mut remaining = total_len - len;
+
+        #[cfg(feature = "record-commands")]
+        self.recorder.pixels(total_len);
+
+        self.wait_unpaused();
+        let (sent, fail) = self.inject_fault(len);
+        self.start_pixels(PixelRun::new(&chunk[..sent.max(1)]));
+        self.wait_pixels();
+        if fail {
+            self.stats.record(LinkError::Transfer);
+        }
+
+        while remaining > 0 {
+            if self.chunk_gap_us > 0 {
+                self.d.delay_us(self.chunk_gap_us);
+            }
+            self.wait_unpaused();
+            let len = remaining.min(chunk_len);
+            let (sent, fail) = self.inject_fault(len);
+            // the previous run has completed or was stopped (`wait_pixels()`), the buffer is free
+            fill(&mut chunk[..len]);
+            #[cfg(feature = "frame-capture")]
+            if let Some(sink) = self.capture.as_deref_mut() {
+                sink.pixels(&chunk[..len]);
+            }
+            self.start_pixels(PixelRun::new(&chunk[..sent.max(1)]));
+            self.wait_pixels();
+            if fail {
+                self.stats.record(LinkError::Transfer);
+            }
+            remaining -= len;
+        }
+
+        self.end_pixels();
+        self.chunk_buffer = Some(chunk);
+    }
+
+    /// Draws a whole frame of big-endian RGB565 bytes (width * height * 2, in the current
+    /// orientation), e.g. an image converted offline. Extra bytes are ignored, a short buffer
+    /// leaves the rest of the screen untouched.
+    pub fn draw_entire_screen(&mut self, buffer: &[u8]) {
+        let (width, height) = self.size();
+        let len = buffer.len().min(width * height * 2);
+        if len == 0 {
+            return;
+        }
+
+        self.begin_region(0, width as u16 - 1, 0, height as u16 - 1);
+        self.stream_be_bytes(0, 0, width, &buffer[..len]);
+    }
+
+    /// Old name of `draw_entire_screen()`.
+    #[deprecated(note = "use `draw_entire_screen()`")]
+    pub fn send_frame(&mut self, buffer: &[u8]) {
+        self.draw_entire_screen(buffer);
+    }
+
+    /// Draws big-endian RGB565 bytes into `area` in one go: checks that `area` is on the screen
+    /// and `data` holds exactly its pixels, then sets the window, sends RAMWR and streams `data`.
+    /// Nothing is sent on error. Use this instead of `set_window()` + `begin_draw()` + streaming,
+    /// where RAMWR has to come after the window and CS has to stay low for the data.
+    pub fn draw_window(&mut self, area: &Rectangle, data: &[u8]) -> Result<(), WindowError> {
+        let Some(bottom_right) = area.bottom_right() else {
+            return Err(WindowError::Length { expected: 0, actual: data.len() });
+        };
+        if area.intersection(&self.bounding_box()) != *area {
+            return Err(WindowError::OffScreen);
+        }
+        let expected = area.size.width as usize * area.size.height as usize * 2;
+        if data.len() != expected {
+            return Err(WindowError::Length { expected, actual: data.len() });
+        }
+
+        let (xs, ys) = (area.top_left.x as u16, area.top_left.y as u16);
+        self.begin_region(xs, bottom_right.x as u16, ys, bottom_right.y as u16);
+        self.stream_be_bytes(xs, ys, area.size.width as usize, data);
+        Ok(())
+    }
+
+    /// `draw_window()` for data that lives forever, e.g. an image in flash: the DMA reads
+    /// `data` in place in transfers of up to `MAX_TRANSFER` bytes instead of copying it through
+    /// the 4 KB chunk buffer, so a full frame takes 3 transfers instead of 33 and the CPU
+    /// is free meanwhile. Falls back to `draw_window()` while a color filter or row hook is
+    /// set, since those have to rewrite the bytes.
+    pub fn draw_window_static(&mut self, area: &Rectangle, data: &'static [u8]) -> Result<(), WindowError> {
+        if self.color_filter != ColorFilter::None || self.row_hook.is_some() {
+            return self.draw_window(area, data);
+        }
+
+        let window = self.check_dma_window(area, data)?;
+        self.begin_region(window.xs, window.xe, window.ys, window.ye);
+        self.stream_static(data);
+        self.deselect();
+        Ok(())
+    }
+
+    /// Checks that `data` holds exactly the pixels of `area`, which must be on-screen,
+    /// and that the DMA can read it in place.
+    pub(super) fn check_dma_window(&self, area: &Rectangle, data: &[u8]) -> Result<Window, WindowError> {
+        if area.is_zero_sized() {
+            return Err(WindowError::Length { expected: 0, actual: data.len() });
+        }
+        let window = Window::clip(area, &self.bounding_box()).filter(|window| window.rectangle() == *area);
+        let Some(window) = window else {
+            return Err(WindowError::OffScreen);
+        };
+        let expected = area.size.width as usize * area.size.height as usize * 2;
+        if data.len() != expected {
+            return Err(WindowError::Length { expected, actual: data.len() });
+        }
+        check_dma_capable(data).map_err(WindowError::Memory)?;
+        Ok(window)
+    }
+
+    /// Sends `data` as pixel bytes straight from memory, `MAX_TRANSFER` bytes per transfer.
+    /// Like `stream_chunks_of()`, DC and CS are left untouched.
+    fn stream_static(&mut self, data: &'static [u8]) {
+        if data.is_empty() {
+            return;
+        }
+
+        #[cfg(feature = "frame-capture")]
+        if let Some(sink) = self.capture.as_deref_mut() {
+            sink.pixels(data);
+        }
+        #[cfg(feature = "record-commands")]
+        self.recorder.pixels(data.len());
+
+        let mut start = 0;
+        while start < data.len() {
+            if start > 0 && self.chunk_gap_us > 0 {
+                self.d.delay_us(self.chunk_gap_us);
+            }
+            self.wait_unpaused();
+            let len = (data.len() - start).min(MAX_TRANSFER);
+            let (sent, fail) = self.inject_fault(len);
+            self.start_pixels(PixelRun::new(&data[start..start + sent.max(1)]));
+            self.wait_pixels();
+            if fail {
+                self.stats.record(LinkError::Transfer);
+            }
+            start += len;
+        }
+
+        self.end_pixels();
+    }
+
+    /// Streams big-endian RGB565 rows `width` pixels wide, starting at display pixel
+    /// (`first_column`, `first_row`), through the color filter and row hook, then deselects.
+    fn stream_be_bytes(&mut self, first_column: u16, first_row: u16, width: usize, buffer: &[u8]) {
+        let filter = self.color_filter;
+        let mut sent = 0;
+        self.stream_rows(buffer.len(), width, first_row, |chunk| {
+            chunk.copy_from_slice(&buffer[sent..sent + chunk.len()]);
+            if filter != ColorFilter::None {
+                for (i, color_bytes) in chunk.chunks_exact_mut(2).enumerate() {
+                    let pixel = sent / 2 + i;
+                    let color: Rgb565 = RawU16::new(u16::from_be_bytes([color_bytes[0], color_bytes[1]])).into();
+                    let (x, y) = (first_column + (pixel % width) as u16, first_row + (pixel / width) as u16);
+                    color_bytes.copy_from_slice(&filter.apply(color, x, y).to_be_bytes());
+                }
+            }
+            sent += chunk.len();
+        });
+
+        self.deselect();
+    }
+
+    /// Fills `area` with `shader(x, y)` evaluated per pixel, in display coordinates, straight
+    /// into the DMA chunks: plasma, noise or computed gradients without a pixel buffer.
+    /// The parts of `area` off the screen are skipped.
+    pub fn fill_with<F>(&mut self, area: &Rectangle, mut shader: F)
+    where
+        F: FnMut(i32, i32) -> Rgb565,
+    {
+        let area = area.intersection(&self.bounding_box());
+        let Some(bottom_right) = area.bottom_right() else {
+            return;
+        };
+
+        let (xs, ys) = (area.top_left.x as u16, area.top_left.y as u16);
+        let (xe, ye) = (bottom_right.x as u16, bottom_right.y as u16);
+        self.write_region(xs, xe, ys, ye, area.points().map(|p| shader(p.x, p.y)));
+    }
+
+    /// Sends RAMWR and streams the pixels of `area` from `colors` into the current window.
+    pub(super) fn write_pixels<I>(&mut self, area: &Rectangle, colors: I)
+    where
+        I: Iterator<Item = Rgb565>,
+    {
+        self.begin_draw();
+        self.set_dc(true);
+        self.select();
+        self.stream_pixels(area, false, colors);
+    }
+
+    /// Sets the window and streams its pixels from `colors` into it, see `begin_region()`.
+    pub(super) fn write_region<I>(&mut self, xs: u16, xe: u16, ys: u16, ye: u16, colors: I)
+    where
+        I: Iterator<Item = Rgb565>,
+    {
+        let area = Rectangle::with_corners(Point::new(xs as i32, ys as i32), Point::new(xe as i32, ye as i32));
+        self.begin_region(xs, xe, ys, ye);
+        self.stream_pixels(&area, true, colors);
+    }
+
+    /// Like `write_region()` with 18-bit pixels, 3 bytes each with the channel in the top
+    /// 6 bits, for COLMOD `RGB666`. The row hook and color filter are skipped.
+    pub(super) fn write_region_rgb666<I>(&mut self, xs: u16, xe: u16, ys: u16, ye: u16, mut colors: I)
+    where
+        I: Iterator<Item = Rgb666>,
+    {
+        let count = (xe - xs + 1) as usize * (ye - ys + 1) as usize;
+        self.begin_region(xs, xe, ys, ye);
+        self.stream_chunks_of(count * 3, self.chunk_size() / 3 * 3, |chunk| {
+            for color_bytes in chunk.chunks_exact_mut(3) {
+                let color = colors.next().unwrap();
+                color_bytes.copy_from_slice(&[color.r() << 2, color.g() << 2, color.b() << 2]);
+            }
+        });
+        self.deselect();
+    }
+
+    /// Streams the pixels of `area` to the selected panel in data mode, then deselects it.
+    /// `display_rows` says the area is in display coordinates, so the row hook applies.
+    fn stream_pixels<I>(&mut self, area: &Rectangle, display_rows: bool, mut colors: I)
+    where
+        I: Iterator<Item = Rgb565>,
+    {
+        let width = area.size.width as usize;
+        let count = width * area.size.height as usize;
+        let (x0, y0) = (area.top_left.x as u16, area.top_left.y as u16);
+        let (mut x, mut y) = (x0, y0);
+
+        let filter = self.color_filter;
+        let fill = |chunk_buffer: &mut [u8]| {
+            for color_bytes in chunk_buffer.chunks_exact_mut(2) {
+                color_bytes.copy_from_slice(&filter.apply(colors.next().unwrap(), x, y).to_be_bytes());
+                x += 1;
+                if (x - x0) as usize == width {
+                    x = x0;
+                    y += 1;
+                }
+            }
+        };
+        if display_rows {
+            self.stream_rows(count * 2, width, y0, fill);
+        } else {
+            self.stream_chunks(count * 2, fill);
+        }
+
+        self.deselect();
+    }
+
+    /// Streams like `stream_chunks()`, passing each row through the row hook if one is set.
+    /// Chunks then hold whole rows of `width` pixels, the first being display row `first_row`.
+    fn stream_rows<F>(&mut self, total_len: usize, width: usize, first_row: u16, mut fill: F)
+    where
+        F: FnMut(&mut [u8]),
+    {
+        let Some(hook) = self.row_hook.take() else {
+            self.stream_chunks(total_len, fill);
+            return;
+        };
+
+        let row_len = width * 2;
+        let mut y = first_row;
+        self.stream_chunks_of(total_len, self.chunk_size() / row_len * row_len, |chunk| {
+            fill(chunk);
+            for row in chunk.chunks_mut(row_len) {
+                hook(y, row);
+                y = y.wrapping_add(1);
+            }
+        });
+        self.row_hook = Some(hook);
+    }
+
+    /// The conservative wait after a command or parameter byte, used by the `cs_*` macros.
+    /// `InitSm` skips them and waits only between init steps; `set_dc()` and `deselect()`
+    /// drain the SPI on their own, so the bytes are safe either way.
+    #[inline(always)]
+    pub(crate) fn command_wait(&mut self, ms: u32) {
+        if !self.skip_waits {
+            self.d.delay_ms(ms);
+        }
     }
 
     #[inline(always)]
     pub fn select(&mut self) -> &mut Self {
-        self.cs.set_low().ok(); // Select the device
+        if self.bus_meter.start()
+            && let Some(hooks) = self.bus_hooks.as_deref_mut()
+        {
+            hooks.on_bus_active();
+        }
+        let selected = self.cs.set_low(); // Select the device
+        self.record_pin(selected.map_err(Error::CS));
+        self.settle_pin();
         self
     }
 
+    /// Raises CS once the last byte has left the SPI: the DMA reports the transfer complete
+    /// while that byte is still in the data or shift register, and a CS edge within a byte
+    /// makes the panel drop it.
     #[inline(always)]
     pub fn deselect(&mut self) -> &mut Self {
-        self.cs.set_high().ok(); // Deselect the device
+        Self::wait_tx_idle();
+        let deselected = self.cs.set_high(); // Deselect the device
+        self.record_pin(deselected.map_err(Error::CS));
+        self.settle_pin();
+        if self.bus_meter.stop()
+            && let Some(hooks) = self.bus_hooks.as_deref_mut()
+        {
+            hooks.on_bus_idle();
+        }
         self
     }
 
+    /// Calls `hooks` whenever the bus goes active or idle from now on, `None` stops.
+    pub fn set_bus_hooks(&mut self, hooks: Option<&'a mut dyn BusHooks>) {
+        self.bus_hooks = hooks;
+    }
+
+    /// Returns the bus active time accumulated since creation or `reset_bus_activity()`.
+    pub fn bus_activity(&self) -> BusActivity {
+        self.bus_meter.activity
+    }
+
+    pub fn reset_bus_activity(&mut self) {
+        self.bus_meter.activity = BusActivity::default();
+    }
+
+    /// Blanks the panel (DISPOFF and backlight off) once no draw happened for `timeout_ms`,
+    /// as seen by `idle_tick()`; the next draw turns it back on before its pixels go out.
+    /// `None` (the default) disables it and wakes a blanked panel.
+    pub fn set_idle_blank(&mut self, timeout_ms: Option<u32>) {
+        self.idle_blank.timeout_ms = timeout_ms;
+        self.idle_blank.touch();
+        if timeout_ms.is_none() {
+            self.wake();
+        }
+    }
+
+    /// Sets the backlight switched by the idle blank, e.g. the BL pin as an `OutputPin`.
+    /// Without one only the panel is turned off.
+    pub fn set_backlight(&mut self, backlight: Option<&'a mut dyn Backlight>) {
+        self.backlight = backlight;
+    }
+
+    /// Advances the idle blank timer to `now_ms`, any wrapping millisecond clock, and blanks
+    /// the panel when it ran out. Call it regularly, e.g. once per main loop iteration.
+    pub fn idle_tick(&mut self, now_ms: u32) {
+        if !self.idle_blank.tick(now_ms) {
+            return;
+        }
+        if let Some(backlight) = self.backlight.as_deref_mut() {
+            backlight.set_backlight(false);
+        }
+        self.off();
+        self.idle_blank.blanked = true;
+    }
+
+    pub fn is_blanked(&self) -> bool {
+        self.idle_blank.blanked
+    }
+
+    /// Notes a draw for the idle blank and restores a blanked panel.
+    fn wake(&mut self) {
+        self.idle_blank.touch();
+        if !core::mem::take(&mut self.idle_blank.blanked) {
+            return;
+        }
+        self.show();
+        if let Some(backlight) = self.backlight.as_deref_mut() {
+            backlight.set_backlight(true);
+        }
+    }
+
+    /// Returns the delay, e.g. for application timing between draws.
+    pub fn delay(&mut self) -> &mut Delay {
+        self.d
+    }
+
+    /// Borrows the pins and delay, see `Parts`. No transfer is in flight between driver calls,
+    /// so the bus is idle with CS high.
+    pub fn parts(&mut self) -> Parts<'_, CS, DC, RST> {
+        Parts {
+            cs: &mut self.cs,
+            dc: &mut self.dc,
+            rst: &mut self.rst,
+            delay: self.d,
+        }
+    }
+
+    /// Drives DC: high for data, low for commands. Waits for the last byte to leave the SPI
+    /// first, so it is still sampled with the DC level it was sent under.
+    #[inline(always)]
+    pub(super) fn set_dc(&mut self, data: bool) {
+        Self::wait_tx_idle();
+        let set = if data { self.dc.set_high() } else { self.dc.set_low() };
+        self.record_pin(set.map_err(Error::DC));
+        self.settle_pin();
+    }
+
+    /// Runs `f` on the driver and fails if any pin write failed during it, the fallible
+    /// form of every method: `display.checked(|d| d.set_orientation(Orientation::Landscape))?`.
+    /// Outside of `checked()` a failed write is logged and kept for `take_pin_error()`, and the
+    /// driver carries on; MCU pins (`Error = Infallible`) never fail.
+    pub fn checked<R, F>(&mut self, f: F) -> Result<R, DmaPinError<CS, DC, RST>>
+    where
+        F: FnOnce(&mut Self) -> R,
+    {
+        self.pin_error = None;
+        let result = f(self);
+        match self.pin_error.take() {
+            Some(e) => Err(e),
+            None => Ok(result),
+        }
+    }
+
+    /// Returns and clears the first pin write failure since the last call.
+    pub fn take_pin_error(&mut self) -> Option<DmaPinError<CS, DC, RST>> {
+        self.pin_error.take()
+    }
+
+    #[inline(always)]
+    pub(super) fn record_pin(&mut self, result: Result<(), DmaPinError<CS, DC, RST>>) {
+        if let Err(e) = result
+            && self.pin_error.is_none()
+        {
+            debug!("pin write failed, first error kept for take_pin_error()");
+            self.pin_error = Some(e);
+        }
+    }
+
+    /// Converts every pixel drawn from now on, e.g. `ColorFilter::Grayscale` for a locked screen.
+    /// Only affects later drawing; redraw to convert what is already on the screen.
+    pub fn set_color_filter(&mut self, filter: ColorFilter) {
+        self.color_filter = filter;
+    }
+
+    pub fn color_filter(&self) -> ColorFilter {
+        self.color_filter
+    }
+
+    /// Sets the software priority of the pixel stream's DMA transfers, `Medium` by default.
+    /// The DMA controller arbitrates between its streams by this priority, then by stream
+    /// number. A full-screen blit keeps its stream busy for tens of milliseconds, so give
+    /// streams that can't wait (ADC sampling, UART RX) a higher priority than the display,
+    /// or they overrun; the display only gets slower when it loses arbitration.
+    /// Takes effect from the next transfer. A dedicated command stream keeps the HAL default.
+    pub fn set_dma_priority(&mut self, priority: Priority) {
+        self.dma_priority = priority;
+        // a pixel stream kept set up still has the old priority
+        self.release_pixels();
+    }
+
+    pub fn dma_priority(&self) -> Priority {
+        self.dma_priority
+    }
+
+    /// Holds pixel streaming between chunks, with CS low, for as long as `critical` returns
+    /// true, e.g. while another stream on the same DMA controller runs a burst that must not
+    /// share the bus. Checked before every chunk; `None` streams without checking.
+    pub fn set_pause_while(&mut self, critical: Option<&'a dyn Fn() -> bool>) {
+        self.pause_while = critical;
+    }
+
+    fn wait_unpaused(&self) {
+        if let Some(critical) = self.pause_while {
+            while critical() {}
+        }
+    }
+
+    /// Starts `run` on the pixel stream. The first run sets the stream up; while commands have
+    /// their own stream (`CommandStream::DEDICATED`) it then stays set up, and later runs only
+    /// load the memory address and length. The previous run must have completed or been stopped.
+    pub(super) fn start_pixels(&mut self, run: PixelRun) {
+        if let Some(tf) = self.pixel.as_mut() {
+            // no double buffering, so this can't fail: the stream is disabled and restarted
+            tf.next_transfer(run).ok();
+            return;
+        }
+
+        let config = self.dma_config();
+        let st = self.st.take().unwrap();
+        let tx = self.tx.take().unwrap();
+        let mut tf = Transfer::init_memory_to_peripheral(st, tx, run, None, config);
+        tf.start(|_| {});
+        self.pixel = Some(tf);
+    }
+
+    /// Waits for the pixel run started last. A run that failed or timed out is stopped, so its
+    /// bytes can be reused right away.
+    /// # Returns
+    /// `true` if the transfer failed.
+    pub(super) fn wait_pixels(&mut self) -> bool {
+        let Some(tf) = self.pixel.as_mut() else {
+            return false;
+        };
+        let failed = self.stats.wait(tf);
+        if failed {
+            tf.pause(|_| {});
+        }
+        failed
+    }
+
+    /// `wait_pixels()` without the waiting, for runs polled from the application.
+    /// # Returns
+    /// `None` while the run is in flight, then `Some(true)` if it failed.
+    pub(super) fn check_pixels(&mut self) -> Option<bool> {
+        let tf = self.pixel.as_mut()?;
+        let failed = self.stats.check(tf)?;
+        if failed {
+            tf.pause(|_| {});
+        }
+        Some(failed)
+    }
+
+    /// Ends a pixel stream: with a dedicated command stream the transfer stays set up for the
+    /// next one, otherwise the stream goes back for the command transfers.
+    pub(super) fn end_pixels(&mut self) {
+        if !CMD::DEDICATED {
+            self.release_pixels();
+        }
+    }
+
+    /// Tears the pixel transfer down, e.g. before its configuration changes.
+    fn release_pixels(&mut self) {
+        if let Some(tf) = self.pixel.take() {
+            let (st, tx, _, _) = tf.release();
+            self.st = Some(st);
+            self.tx = Some(tx);
+        }
+    }
+
+    pub(super) fn dma_config(&self) -> DmaConfig {
+        DmaConfig::default()
+            .priority(self.dma_priority)
+            .peripheral_increment(false)
+            .memory_increment(true)
+            .fifo_enable(false)
+            .transfer_complete_interrupt(false)
+    }
+
+    /// Pauses `us` microseconds between the chunks of a pixel stream, with CS held low.
+    /// A workaround for marginal wiring that drops data on long back-to-back bursts;
+    /// 0, the default, streams without gaps.
+    pub fn set_chunk_gap_us(&mut self, us: u32) {
+        self.chunk_gap_us = us;
+    }
+
+    /// Waits after every CS and DC change, for pins that don't switch the moment the write
+    /// returns, e.g. PCF8574-style I2C expanders whose quasi-bidirectional outputs rise through
+    /// a weak pull-up (tens of microseconds with a few tens of pF on the line).
+    /// 0, the default, adds no delay for MCU pins. RST needs nothing: its pulses are 100+ ms.
+    pub fn set_pin_settle_us(&mut self, us: u32) {
+        self.pin_settle_us = us;
+    }
+
+    #[inline(always)]
+    fn settle_pin(&mut self) {
+        if self.pin_settle_us > 0 {
+            self.d.delay_us(self.pin_settle_us);
+        }
+    }
+
     // Additional methods for DMA operations can be added here
 }
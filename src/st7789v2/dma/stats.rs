@@ -0,0 +1,81 @@
+use stm32f4xx_hal::dma::traits::StreamISR;
+
+/// Number of polls of the transfer-complete flag before a transfer is counted as timed out.
+/// A full 4 KiB chunk at 12 MHz SPI takes ~2.7 ms, well below this at any F4 core clock.
+pub const WAIT_SPIN_LIMIT: u32 = 1_000_000;
+
+/// The kind of DMA link error last seen by the driver.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, defmt::Format)]
+pub enum LinkError {
+    Transfer,
+    Fifo,
+    Timeout,
+}
+
+/// Display-link health counters kept by the DMA driver, for field telemetry.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, defmt::Format)]
+pub struct ErrorStats {
+    pub transfer_errors: u32,
+    pub fifo_errors: u32,
+    pub timeouts: u32,
+    pub last_error: Option<LinkError>,
+}
+
+impl ErrorStats {
+    /// Waits for `tf` to complete, recording any error or timeout.
+    /// # Returns
+    /// `true` if the transfer failed.
+    pub(crate) fn wait<T: StreamISR>(&mut self, tf: &T) -> bool {
+        let mut spins = 0;
+        let mut failed = false;
+
+        while !tf.is_transfer_complete() {
+            if tf.is_transfer_error() {
+                self.record(LinkError::Transfer);
+                failed = true;
+                break;
+            }
+            spins += 1;
+            if spins == WAIT_SPIN_LIMIT {
+                self.record(LinkError::Timeout);
+                failed = true;
+                break;
+            }
+        }
+
+        if tf.is_fifo_error() {
+            self.record(LinkError::Fifo);
+            failed = true;
+        }
+
+        failed
+    }
+
+    /// `wait()` without the waiting, for transfers polled from the application.
+    /// # Returns
+    /// `None` while `tf` is still running, then `Some(true)` if it failed.
+    pub(crate) fn check<T: StreamISR>(&mut self, tf: &T) -> Option<bool> {
+        if !tf.is_transfer_complete() {
+            if tf.is_transfer_error() {
+                self.record(LinkError::Transfer);
+                return Some(true);
+            }
+            return None;
+        }
+
+        let fifo = tf.is_fifo_error();
+        if fifo {
+            self.record(LinkError::Fifo);
+        }
+        Some(fifo)
+    }
+
+    pub(crate) fn record(&mut self, error: LinkError) {
+        match error {
+            LinkError::Transfer => self.transfer_errors = self.transfer_errors.wrapping_add(1),
+            LinkError::Fifo => self.fifo_errors = self.fifo_errors.wrapping_add(1),
+            LinkError::Timeout => self.timeouts = self.timeouts.wrapping_add(1),
+        }
+        self.last_error = Some(error);
+    }
+}
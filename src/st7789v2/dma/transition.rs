@@ -0,0 +1,259 @@
+use crate::st7789v2::common::{Madctl, Orientation, RAM_HEIGHT};
+use crate::st7789v2::dma::{command_stream::CommandStream, st7789v2dma::{ST7789V2DMA, TxDmaRoute}};
+use crate::st7789v2::params::ScrollArea;
+#[cfg(feature = "framebuffer")]
+use crate::st7789v2::{color::blend, framebuffer::Framebuffer};
+use embedded_graphics::{pixelcolor::Rgb565, prelude::*, primitives::Rectangle};
+use stm32f4xx_hal::{
+    dma::{
+        traits::{Channel, Stream}, ChannelX, StreamX
+    },
+    hal::digital::OutputPin,
+    rcc,
+    spi::Instance,
+};
+
+/// Rows drawn per scroll step. Must not exceed the number of hidden RAM rows (40 on a 240x280 panel).
+pub const TRANSITION_STEP: usize = 8;
+
+/// Hardware-scrolled screen transitions, see `ST7789V2DMA::transition_to()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Transition {
+    /// The new screen pushes the old one out through the top.
+    SlideUp,
+    /// The new screen pushes the old one out through the bottom.
+    SlideDown,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, defmt::Format)]
+pub enum TransitionError {
+    /// Hardware scroll runs along the panel's native rows, so only unrotated,
+    /// vertically unmirrored orientations can slide.
+    UnsupportedOrientation,
+    /// A custom scroll area or a non-zero scroll offset is set.
+    ScrollInUse,
+    /// The panel shows every RAM row, so there is nowhere to pre-render.
+    NoHiddenRows,
+}
+
+/// DrawTarget that passes through only the pixels inside `area`.
+/// Unlike embedded-graphics' `Clipped`, partially covered `fill_contiguous` calls stay one
+/// window write instead of falling back to per-pixel `draw_iter`.
+pub struct Strip<'t, T> {
+    target: &'t mut T,
+    area: Rectangle,
+}
+
+impl<'t, T: DrawTarget> Strip<'t, T> {
+    pub fn new(target: &'t mut T, area: Rectangle) -> Self {
+        Self { target, area }
+    }
+}
+
+impl<T: DrawTarget> Dimensions for Strip<'_, T> {
+    fn bounding_box(&self) -> Rectangle {
+        self.target.bounding_box()
+    }
+}
+
+impl<T: DrawTarget> DrawTarget for Strip<'_, T> {
+    type Color = T::Color;
+    type Error = T::Error;
+
+    fn fill_contiguous<I>(&mut self, area: &Rectangle, colors: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Self::Color>,
+    {
+        let visible = area.intersection(&self.area).intersection(&self.target.bounding_box());
+        if visible.is_zero_sized() {
+            return Ok(());
+        }
+
+        let colors = area
+            .points()
+            .zip(colors)
+            .filter(|(point, _)| visible.contains(*point))
+            .map(|(_, color)| color);
+        self.target.fill_contiguous(&visible, colors)
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        let visible = area.intersection(&self.area);
+        if visible.is_zero_sized() {
+            return Ok(());
+        }
+        self.target.fill_solid(&visible, color)
+    }
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let area = self.area;
+        self.target
+            .draw_iter(pixels.into_iter().filter(|Pixel(point, _)| area.contains(*point)))
+    }
+}
+
+impl<'a, SPI, DMA, CS, DC, RST, const CHANNEL: u8, const S: u8, const W: usize, const H: usize, const OFFSET: usize, CMD>
+    ST7789V2DMA<'a, SPI, DMA, CS, DC, RST, CHANNEL, S, W, H, OFFSET, CMD>
+where
+    SPI: Instance + TxDmaRoute<StreamX<DMA, S>, CHANNEL>,
+    CS: OutputPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DMA: rcc::Enable + rcc::Reset + stm32f4xx_hal::dma::traits::Instance,
+    StreamX<DMA, S>: Stream,
+    ChannelX<CHANNEL>: Channel,
+    CMD: CommandStream,
+{
+    /// Slides from the current screen to the one `render` draws, over about `duration_ms`.
+    /// The screen scrolls through the whole 320-line RAM with VSCSAD; each new row is drawn while
+    /// it is in the hidden rows, so `render` is called once per step with a `Strip` that only
+    /// lets those rows through. The hidden rows show as a black band between the two screens.
+    /// Ends with the new screen in place and the scroll offset back at 0.
+    pub fn transition_to<F>(&mut self, mut render: F, transition: Transition, duration_ms: u32) -> Result<(), TransitionError>
+    where
+        F: FnMut(&mut Strip<'_, Self>),
+    {
+        let madctl = self.transform().madctl();
+        if madctl.contains(Madctl::MV) || madctl.contains(Madctl::MY) {
+            return Err(TransitionError::UnsupportedOrientation);
+        }
+        if self.scroll_area() != ScrollArea::FULL || self.scroll_offset() != 0 {
+            return Err(TransitionError::ScrollInUse);
+        }
+        let hidden = RAM_HEIGHT - H;
+        if hidden == 0 {
+            return Err(TransitionError::NoHiddenRows);
+        }
+
+        let step = TRANSITION_STEP.min(hidden);
+        let step_delay = duration_ms * step as u32 / RAM_HEIGHT as u32;
+
+        // the band between the screens
+        let visible = self.visible_window();
+        let mut ram = self.ram_view();
+        let ram_width = ram.size().width;
+        ram.fill_solid(&Rectangle::new(Point::zero(), Size::new(ram_width, visible.top_left.y as u32)), Rgb565::BLACK).ok();
+        let below = visible.top_left.y + H as i32;
+        ram.fill_solid(&Rectangle::new(Point::new(0, below), Size::new(ram_width, (RAM_HEIGHT as i32 - below) as u32)), Rgb565::BLACK).ok();
+
+        let mut scrolled = 0;
+        while scrolled < RAM_HEIGHT {
+            let s = step.min(RAM_HEIGHT - scrolled);
+
+            // rows that come into view with the next step, currently in the hidden rows
+            let (first, last, offset) = match transition {
+                Transition::SlideUp => (
+                    scrolled as i32 - hidden as i32,
+                    (scrolled + s) as i32 - hidden as i32,
+                    (scrolled + s) % RAM_HEIGHT,
+                ),
+                Transition::SlideDown => (
+                    RAM_HEIGHT as i32 - (scrolled + s) as i32,
+                    RAM_HEIGHT as i32 - scrolled as i32,
+                    (RAM_HEIGHT - (scrolled + s)) % RAM_HEIGHT,
+                ),
+            };
+            let (first, last) = (first.max(0), last.min(H as i32));
+            if first < last {
+                let rows = Rectangle::new(Point::new(0, first), Size::new(self.size().0 as u32, (last - first) as u32));
+                render(&mut Strip::new(self, rows));
+            }
+
+            self.set_scroll_offset(offset as u16).ok();
+            self.d.delay_ms(step_delay);
+            scrolled += s;
+        }
+
+        Ok(())
+    }
+
+    /// Dissolves from the screen `render_old` draws to the one `render_new` draws in `steps` steps,
+    /// `step_delay_ms` apart. Both are rendered into the `old`/`new` strips band by band and
+    /// blended as they are packed. With strips as tall as the display each screen is rendered
+    /// once; shorter strips (two 240x280 frames don't fit in an F401's RAM) re-render every
+    /// band on every step, trading CPU time for memory.
+    #[cfg(feature = "framebuffer")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn crossfade<const FW: usize, const FH: usize, A, B>(
+        &mut self,
+        old: &mut Framebuffer<FW, FH>,
+        new: &mut Framebuffer<FW, FH>,
+        mut render_old: A,
+        mut render_new: B,
+        steps: u8,
+        step_delay_ms: u32,
+    ) where
+        A: FnMut(&mut Framebuffer<FW, FH>),
+        B: FnMut(&mut Framebuffer<FW, FH>),
+    {
+        let (width, height) = self.size();
+        let width = width.min(FW);
+        let whole_screen = FH >= height;
+        let steps = steps.max(1);
+
+        for step in 1..=steps {
+            let alpha = (step as u32 * 255 / steps as u32) as u8;
+
+            for top in (0..height).step_by(FH) {
+                let rows = FH.min(height - top);
+                if !whole_screen || step == 1 {
+                    for strip in [&mut *old, &mut *new] {
+                        strip.set_origin(Point::new(0, top as i32));
+                        strip.clear(Rgb565::BLACK).ok();
+                    }
+                    render_old(old);
+                    render_new(new);
+                }
+
+                let band = Rectangle::new(Point::new(0, top as i32), Size::new(width as u32, rows as u32));
+                let colors = old.rows()[..rows]
+                    .iter()
+                    .zip(&new.rows()[..rows])
+                    .flat_map(|(o, n)| o[..width].iter().zip(&n[..width]))
+                    .map(|(&o, &n)| blend(n, o, alpha));
+                self.fill_contiguous(&band, colors).ok();
+            }
+
+            self.d.delay_ms(step_delay_ms);
+        }
+    }
+
+    /// Changes the orientation and has `render` redraw the screen for it. MADCTL only changes
+    /// how new pixels are written, so without a redraw the old content stays on the glass in
+    /// the old orientation and partial updates land on top of it. The panel is off (DISPOFF)
+    /// while `render` runs, so the half-redrawn screen never shows.
+    pub fn rotate_to<F>(&mut self, orientation: Orientation, render: F)
+    where
+        F: FnOnce(&mut Self),
+    {
+        let display_on = self.shadow.display_on;
+        if display_on {
+            self.off();
+        }
+        self.set_orientation(orientation);
+        render(self);
+        if display_on {
+            self.show();
+        }
+    }
+
+    /// Like `rotate_to()`, re-blitting `frame`, the whole screen as drawn in the old
+    /// orientation, so the picture stays upright in the new one. When the new logical size is
+    /// the old one transposed (a quarter turn on a non-square panel) the frame is centered,
+    /// cropped along one side and padded with black along the other.
+    #[cfg(feature = "framebuffer")]
+    pub fn rotate_blit<const FW: usize, const FH: usize>(&mut self, orientation: Orientation, frame: &Framebuffer<FW, FH>) {
+        self.rotate_to(orientation, |display| {
+            let screen = display.bounding_box();
+            let centered = Point::new(
+                (screen.size.width as i32 - FW as i32) / 2,
+                (screen.size.height as i32 - FH as i32) / 2,
+            );
+            let source = Rectangle::new(frame.origin() - centered, screen.size);
+            display.fill_contiguous(&screen, frame.region(&source)).ok();
+        });
+    }
+}
@@ -0,0 +1,211 @@
+use crate::st7789v2::{
+    common::{ColorOrder, Commands},
+    dma::{detect::PanelPreset, shadow::ShadowRegisters},
+    params::DigitalGamma,
+    transform::Transform,
+};
+
+/// Largest parameter list of a single command, the 64-entry digital gamma tables.
+pub const MAX_PARAMS: usize = 64;
+
+/// WRCTRLD parameter the drivers write: BCTRL | BL, brightness control and backlight on.
+pub const CTRL_DISPLAY_BRIGHTNESS_ON: u8 = 0b0010_0100;
+
+/// Packets in the longest `init_sequence()`: SWRESET, SLPOUT, the configuration, DISPON.
+const INIT_PACKETS: usize = ConfigRegister::MAX_PACKETS + 3;
+
+/// One command with its parameters and the time to wait after it, as sent on the wire:
+/// the command byte with DC low, then the parameters with DC high.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Packet {
+    pub command: Commands,
+    params: [u8; MAX_PARAMS],
+    len: usize,
+    pub delay_ms: u32,
+}
+
+impl Packet {
+    /// # Panics
+    /// If `params` is longer than `MAX_PARAMS`.
+    pub fn new(command: Commands, params: &[u8], delay_ms: u32) -> Self {
+        let mut packet = Self {
+            command,
+            params: [0; MAX_PARAMS],
+            len: params.len(),
+            delay_ms,
+        };
+        packet.params[..params.len()].copy_from_slice(params);
+        packet
+    }
+
+    pub fn params(&self) -> &[u8] {
+        &self.params[..self.len]
+    }
+}
+
+/// CASET parameters for columns `x0..=x1`, in RAM addresses.
+pub const fn caset(x0: u16, x1: u16) -> [u8; 4] {
+    address_range(x0, x1)
+}
+
+/// RASET parameters for rows `y0..=y1`, in RAM addresses.
+pub const fn raset(y0: u16, y1: u16) -> [u8; 4] {
+    address_range(y0, y1)
+}
+
+const fn address_range(start: u16, end: u16) -> [u8; 4] {
+    [(start >> 8) as u8, start as u8, (end >> 8) as u8, end as u8]
+}
+
+/// MADCTL parameter for an orientation/mirroring and color order.
+pub const fn madctl(transform: Transform, order: ColorOrder) -> u8 {
+    transform.madctl().union(order.madctl_bits()).bits()
+}
+
+/// The configuration registers the DMA driver shadows, in the order `write_config()` writes
+/// them. Each one's packets are built here from the shadow registers, so the driver and
+/// `init_sequence()` send the same bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, defmt::Format)]
+pub enum ConfigRegister {
+    ColorMode,
+    Madctl,
+    Inversion,
+    ScrollArea,
+    ScrollOffset,
+    PartialArea,
+    Brightness,
+    Gamma,
+    DigitalGamma,
+    ColorEnhancement,
+    TearingEffect,
+    Porch,
+    FrameRate,
+    IdleMode,
+}
+
+impl ConfigRegister {
+    /// Every register, in write order.
+    pub const ALL: [ConfigRegister; 14] = [
+        ConfigRegister::ColorMode,
+        ConfigRegister::Madctl,
+        ConfigRegister::Inversion,
+        ConfigRegister::ScrollArea,
+        ConfigRegister::ScrollOffset,
+        ConfigRegister::PartialArea,
+        ConfigRegister::Brightness,
+        ConfigRegister::Gamma,
+        ConfigRegister::DigitalGamma,
+        ConfigRegister::ColorEnhancement,
+        ConfigRegister::TearingEffect,
+        ConfigRegister::Porch,
+        ConfigRegister::FrameRate,
+        ConfigRegister::IdleMode,
+    ];
+
+    /// Packets of a full configuration write at most.
+    pub const MAX_PACKETS: usize = {
+        let mut total = 0;
+        let mut i = 0;
+        while i < Self::ALL.len() {
+            total += Self::ALL[i].max_packets();
+            i += 1;
+        }
+        total
+    };
+
+    /// Most packets `packets()` emits for this register.
+    pub const fn max_packets(self) -> usize {
+        match self {
+            ConfigRegister::DigitalGamma => 3,
+            ConfigRegister::PartialArea | ConfigRegister::Brightness | ConfigRegister::Gamma => 2,
+            _ => 1,
+        }
+    }
+
+    /// Calls `emit` with each packet that writes this register's value from `shadow`.
+    pub fn packets(self, shadow: &ShadowRegisters, mut emit: impl FnMut(Packet)) {
+        match self {
+            ConfigRegister::ColorMode => emit(Packet::new(Commands::SetColorMode, &[shadow.color_mode as u8], 10)),
+            ConfigRegister::Madctl => emit(Packet::new(Commands::MemoryDataAccessControl, &[shadow.madctl().bits()], 10)),
+            ConfigRegister::Inversion => {
+                let inversion = if shadow.inverted { Commands::InversionOn } else { Commands::InversionOff };
+                emit(Packet::new(inversion, &[], 1));
+            }
+            ConfigRegister::ScrollArea => emit(Packet::new(Commands::VerticalScrollDefinition, &shadow.scroll_area.params(), 0)),
+            ConfigRegister::ScrollOffset => {
+                emit(Packet::new(Commands::VerticalScrollStartAddress, &shadow.scroll_offset.to_be_bytes(), 1));
+            }
+            ConfigRegister::PartialArea => match shadow.partial_area {
+                Some(area) => {
+                    emit(Packet::new(Commands::PartialArea, &area.params(), 0));
+                    emit(Packet::new(Commands::PartialModeOn, &[], 1));
+                }
+                None => emit(Packet::new(Commands::NormalModeOn, &[], 1)),
+            },
+            ConfigRegister::Brightness => {
+                emit(Packet::new(Commands::WriteDisplayBrightness, &[shadow.brightness], 1));
+                emit(Packet::new(Commands::WriteCtrlDisplay, &[CTRL_DISPLAY_BRIGHTNESS_ON], 1));
+            }
+            ConfigRegister::Gamma => {
+                if let Some(gamma) = shadow.gamma {
+                    emit(Packet::new(Commands::PositiveGammaControl, &gamma.positive, 0));
+                    emit(Packet::new(Commands::NegativeGammaControl, &gamma.negative, 0));
+                }
+            }
+            ConfigRegister::DigitalGamma => match shadow.digital_gamma {
+                Some(gamma) => {
+                    emit(Packet::new(Commands::DigitalGammaLutRed, &gamma.red, 0));
+                    emit(Packet::new(Commands::DigitalGammaLutBlue, &gamma.blue, 0));
+                    emit(Packet::new(Commands::DigitalGammaEnable, &[DigitalGamma::ENABLE], 0));
+                }
+                None => emit(Packet::new(Commands::DigitalGammaEnable, &[0x00], 0)),
+            },
+            ConfigRegister::ColorEnhancement => {
+                emit(Packet::new(Commands::WriteContentAdaptiveBrightness, &[shadow.color_enhancement.param()], 0));
+            }
+            ConfigRegister::TearingEffect => {
+                if shadow.tearing_effect {
+                    emit(Packet::new(Commands::TearingEffectOn, &[0x00], 1)); // TEM = 0: V-blank only
+                } else {
+                    emit(Packet::new(Commands::TearingEffectOff, &[], 1));
+                }
+            }
+            ConfigRegister::Porch => emit(Packet::new(Commands::PorchControl, &shadow.porch.params(), 0)),
+            ConfigRegister::FrameRate => emit(Packet::new(Commands::FrameRateControl2, &[shadow.frame_rate.param()], 0)),
+            ConfigRegister::IdleMode => {
+                let idle = if shadow.idle_mode { Commands::IdleModeOn } else { Commands::IdleModeOff };
+                emit(Packet::new(idle, &[], 1));
+            }
+        }
+    }
+}
+
+/// The commands `init()` sends for a panel with `preset`'s settings and everything else at
+/// the driver's defaults (portrait, full brightness, no scrolling), hardware reset excluded.
+/// The configuration comes from `ConfigRegister`, like the driver's.
+/// Runs anywhere, for simulators, host-side tests or tools that emit init tables.
+pub fn init_sequence(preset: &PanelPreset) -> impl Iterator<Item = Packet> {
+    let shadow = ShadowRegisters {
+        color_order: preset.color_order,
+        inverted: preset.inverted,
+        color_enhancement: preset.color_enhancement,
+        digital_gamma: preset.digital_gamma,
+        ..ShadowRegisters::default()
+    };
+
+    let mut packets = [Packet::new(Commands::Nop, &[], 0); INIT_PACKETS];
+    let mut len = 0;
+    let mut push = |packet: Packet| {
+        packets[len] = packet;
+        len += 1;
+    };
+
+    push(Packet::new(Commands::SoftwareReset, &[], 150));
+    push(Packet::new(Commands::SleepOut, &[], 120));
+    for register in ConfigRegister::ALL {
+        register.packets(&shadow, &mut push);
+    }
+    push(Packet::new(Commands::DisplayOn, &[], 50));
+
+    packets.into_iter().take(len)
+}
@@ -0,0 +1,402 @@
+use embedded_graphics::{pixelcolor::Rgb565, prelude::*, primitives::Rectangle};
+
+#[cfg(feature = "framebuffer")]
+use crate::st7789v2::framebuffer::Framebuffer;
+use crate::st7789v2::te::TeCounter;
+
+/// How drawing reaches the display: straight through, or via a framebuffer that is
+/// pushed out on `flush()`. Render into a `Canvas` and the strategy can be swapped without
+/// touching the rendering code.
+pub trait FlushStrategy<D: DrawTarget<Color = Rgb565>> {
+    fn fill_contiguous<I>(&mut self, display: &mut D, area: &Rectangle, colors: I) -> Result<(), D::Error>
+    where
+        I: IntoIterator<Item = Rgb565>;
+
+    fn draw_iter<I>(&mut self, display: &mut D, pixels: I) -> Result<(), D::Error>
+    where
+        I: IntoIterator<Item = Pixel<Rgb565>>;
+
+    fn fill_solid(&mut self, display: &mut D, area: &Rectangle, color: Rgb565) -> Result<(), D::Error> {
+        self.fill_contiguous(display, area, core::iter::repeat(color))
+    }
+
+    /// Sends whatever was drawn since the last flush.
+    fn flush(&mut self, display: &mut D) -> Result<(), D::Error>;
+
+    /// Makes the next `flush()` send `area` even if nothing was drawn there, e.g. after raw
+    /// drawing on the display itself overwrote it. Strategies that always send everything,
+    /// or hold nothing back, ignore it.
+    fn invalidate(&mut self, _area: &Rectangle) {}
+
+    /// Makes the next `flush()` send the whole frame.
+    fn invalidate_all(&mut self) {}
+}
+
+/// DrawTarget that routes drawing through a `FlushStrategy`.
+pub struct Canvas<'c, D, S> {
+    display: &'c mut D,
+    strategy: &'c mut S,
+}
+
+impl<'c, D, S> Canvas<'c, D, S>
+where
+    D: DrawTarget<Color = Rgb565>,
+    S: FlushStrategy<D>,
+{
+    pub fn new(display: &'c mut D, strategy: &'c mut S) -> Self {
+        Self { display, strategy }
+    }
+
+    pub fn flush(&mut self) -> Result<(), D::Error> {
+        self.strategy.flush(self.display)
+    }
+
+    /// See `FlushStrategy::invalidate()`. Drawing through the canvas, by hand or by the widgets,
+    /// marks what it touches already; this is for changes the strategy can't see.
+    pub fn invalidate(&mut self, area: &Rectangle) {
+        self.strategy.invalidate(area);
+    }
+
+    pub fn invalidate_all(&mut self) {
+        self.strategy.invalidate_all();
+    }
+}
+
+impl<D, S> Dimensions for Canvas<'_, D, S>
+where
+    D: DrawTarget<Color = Rgb565>,
+{
+    fn bounding_box(&self) -> Rectangle {
+        self.display.bounding_box()
+    }
+}
+
+impl<D, S> DrawTarget for Canvas<'_, D, S>
+where
+    D: DrawTarget<Color = Rgb565>,
+    S: FlushStrategy<D>,
+{
+    type Color = Rgb565;
+    type Error = D::Error;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        self.strategy.draw_iter(self.display, pixels)
+    }
+
+    fn fill_contiguous<I>(&mut self, area: &Rectangle, colors: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Self::Color>,
+    {
+        self.strategy.fill_contiguous(self.display, area, colors)
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        self.strategy.fill_solid(self.display, area, color)
+    }
+}
+
+/// Draws straight to the display, `flush()` has nothing to do. The default for widgets
+/// that already redraw only what changed.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Immediate;
+
+impl<D: DrawTarget<Color = Rgb565>> FlushStrategy<D> for Immediate {
+    fn fill_contiguous<I>(&mut self, display: &mut D, area: &Rectangle, colors: I) -> Result<(), D::Error>
+    where
+        I: IntoIterator<Item = Rgb565>,
+    {
+        display.fill_contiguous(area, colors)
+    }
+
+    fn draw_iter<I>(&mut self, display: &mut D, pixels: I) -> Result<(), D::Error>
+    where
+        I: IntoIterator<Item = Pixel<Rgb565>>,
+    {
+        display.draw_iter(pixels)
+    }
+
+    fn fill_solid(&mut self, display: &mut D, area: &Rectangle, color: Rgb565) -> Result<(), D::Error> {
+        display.fill_solid(area, color)
+    }
+
+    fn flush(&mut self, _display: &mut D) -> Result<(), D::Error> {
+        Ok(())
+    }
+}
+
+/// Write-combining cache for scattered single pixels, e.g. scatter plots and particles.
+/// Pixels from `draw_iter()` are held (the last color per point wins) until N have gathered
+/// or `flush()` is called, then sent sorted as row runs, one window per run instead of one
+/// per pixel. Area fills flush the cache first and go straight through, keeping draw order.
+pub struct WriteCache<const N: usize = 64> {
+    pixels: [(Point, Rgb565); N],
+    len: usize,
+}
+
+impl<const N: usize> WriteCache<N> {
+    pub const fn new() -> Self {
+        Self {
+            pixels: [(Point::zero(), Rgb565::new(0, 0, 0)); N],
+            len: 0,
+        }
+    }
+
+    /// Number of pixels waiting for the next flush.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+impl<const N: usize> Default for WriteCache<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<D: DrawTarget<Color = Rgb565>, const N: usize> FlushStrategy<D> for WriteCache<N> {
+    fn fill_contiguous<I>(&mut self, display: &mut D, area: &Rectangle, colors: I) -> Result<(), D::Error>
+    where
+        I: IntoIterator<Item = Rgb565>,
+    {
+        self.flush(display)?;
+        display.fill_contiguous(area, colors)
+    }
+
+    fn draw_iter<I>(&mut self, display: &mut D, pixels: I) -> Result<(), D::Error>
+    where
+        I: IntoIterator<Item = Pixel<Rgb565>>,
+    {
+        let bounds = display.bounding_box();
+        for Pixel(point, color) in pixels {
+            if !bounds.contains(point) {
+                continue;
+            }
+            if let Some(cached) = self.pixels[..self.len].iter_mut().find(|(p, _)| *p == point) {
+                cached.1 = color;
+                continue;
+            }
+            if self.len == N {
+                self.flush(display)?;
+            }
+            self.pixels[self.len] = (point, color);
+            self.len += 1;
+        }
+        Ok(())
+    }
+
+    fn fill_solid(&mut self, display: &mut D, area: &Rectangle, color: Rgb565) -> Result<(), D::Error> {
+        self.flush(display)?;
+        display.fill_solid(area, color)
+    }
+
+    fn flush(&mut self, display: &mut D) -> Result<(), D::Error> {
+        let pixels = &mut self.pixels[..self.len];
+        pixels.sort_unstable_by_key(|(p, _)| (p.y, p.x));
+
+        let mut rest = &pixels[..];
+        while let Some(&(start, _)) = rest.first() {
+            let run = rest
+                .iter()
+                .zip(0..)
+                .take_while(|((p, _), i)| *p == start + Point::new(*i, 0))
+                .count();
+            let area = Rectangle::new(start, Size::new(run as u32, 1));
+            display.fill_contiguous(&area, rest[..run].iter().map(|(_, color)| *color))?;
+            rest = &rest[run..];
+        }
+
+        self.len = 0;
+        Ok(())
+    }
+}
+
+/// Draws into a full-screen framebuffer and sends all of it on `flush()`, so the panel never
+/// shows a half-drawn frame. Needs W x H x 2 bytes of RAM.
+#[cfg(feature = "framebuffer")]
+pub struct DoubleBuffered<const W: usize, const H: usize> {
+    frame: Framebuffer<W, H>,
+}
+
+#[cfg(feature = "framebuffer")]
+impl<const W: usize, const H: usize> DoubleBuffered<W, H> {
+    pub const fn new() -> Self {
+        Self { frame: Framebuffer::new() }
+    }
+}
+
+#[cfg(feature = "framebuffer")]
+impl<const W: usize, const H: usize> Default for DoubleBuffered<W, H> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "framebuffer")]
+impl<D: DrawTarget<Color = Rgb565>, const W: usize, const H: usize> FlushStrategy<D> for DoubleBuffered<W, H> {
+    fn fill_contiguous<I>(&mut self, _display: &mut D, area: &Rectangle, colors: I) -> Result<(), D::Error>
+    where
+        I: IntoIterator<Item = Rgb565>,
+    {
+        self.frame.fill_contiguous(area, colors).ok();
+        Ok(())
+    }
+
+    fn draw_iter<I>(&mut self, _display: &mut D, pixels: I) -> Result<(), D::Error>
+    where
+        I: IntoIterator<Item = Pixel<Rgb565>>,
+    {
+        self.frame.draw_iter(pixels).ok();
+        Ok(())
+    }
+
+    fn flush(&mut self, display: &mut D) -> Result<(), D::Error> {
+        display.fill_contiguous(&self.frame.bounding_box(), self.frame.pixels())
+    }
+}
+
+/// Like `DoubleBuffered`, but `flush()` only sends the bounding box of what was drawn
+/// since the last flush.
+#[cfg(feature = "framebuffer")]
+pub struct DirtyRect<const W: usize, const H: usize> {
+    frame: Framebuffer<W, H>,
+    dirty: Option<Rectangle>,
+}
+
+#[cfg(feature = "framebuffer")]
+impl<const W: usize, const H: usize> DirtyRect<W, H> {
+    pub const fn new() -> Self {
+        Self {
+            frame: Framebuffer::new(),
+            dirty: None,
+        }
+    }
+
+    fn mark(&mut self, area: Rectangle) {
+        let area = area.intersection(&self.frame.bounding_box());
+        if area.is_zero_sized() {
+            return;
+        }
+        self.dirty = Some(match self.dirty {
+            Some(dirty) => envelope(dirty, area),
+            None => area,
+        });
+    }
+}
+
+#[cfg(feature = "framebuffer")]
+impl<const W: usize, const H: usize> Default for DirtyRect<W, H> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "framebuffer")]
+impl<D: DrawTarget<Color = Rgb565>, const W: usize, const H: usize> FlushStrategy<D> for DirtyRect<W, H> {
+    fn fill_contiguous<I>(&mut self, _display: &mut D, area: &Rectangle, colors: I) -> Result<(), D::Error>
+    where
+        I: IntoIterator<Item = Rgb565>,
+    {
+        self.mark(*area);
+        self.frame.fill_contiguous(area, colors).ok();
+        Ok(())
+    }
+
+    fn draw_iter<I>(&mut self, _display: &mut D, pixels: I) -> Result<(), D::Error>
+    where
+        I: IntoIterator<Item = Pixel<Rgb565>>,
+    {
+        for pixel in pixels {
+            self.mark(Rectangle::new(pixel.0, Size::new(1, 1)));
+            self.frame.draw_iter(core::iter::once(pixel)).ok();
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self, display: &mut D) -> Result<(), D::Error> {
+        let Some(dirty) = self.dirty.take() else {
+            return Ok(());
+        };
+
+        let rows = self.frame.rows();
+        let colors = dirty
+            .points()
+            .map(|p| rows[p.y as usize][p.x as usize]);
+        display.fill_contiguous(&dirty, colors)
+    }
+
+    fn invalidate(&mut self, area: &Rectangle) {
+        self.mark(*area);
+    }
+
+    fn invalidate_all(&mut self) {
+        self.mark(self.frame.bounding_box());
+    }
+}
+
+/// Returns the smallest rectangle containing both `a` and `b`.
+pub(crate) fn envelope(a: Rectangle, b: Rectangle) -> Rectangle {
+    let top_left = a.top_left.component_min(b.top_left);
+    let bottom_right = a.bottom_right().unwrap_or(a.top_left).component_max(b.bottom_right().unwrap_or(b.top_left));
+    Rectangle::with_corners(top_left, bottom_right)
+}
+
+/// Polls of the TE counter before `VSync` gives up waiting and flushes anyway,
+/// so a panel without TE wired still updates.
+pub const VSYNC_SPIN_LIMIT: u32 = 1_000_000;
+
+/// Wraps another strategy and delays its `flush()` until just after the next TE pulse,
+/// so the transfer starts in the blanking period. Needs a `TeCounter` fed from the TE pin.
+pub struct VSync<S> {
+    inner: S,
+    te: &'static TeCounter,
+}
+
+impl<S> VSync<S> {
+    pub const fn new(inner: S, te: &'static TeCounter) -> Self {
+        Self { inner, te }
+    }
+}
+
+impl<D: DrawTarget<Color = Rgb565>, S: FlushStrategy<D>> FlushStrategy<D> for VSync<S> {
+    fn fill_contiguous<I>(&mut self, display: &mut D, area: &Rectangle, colors: I) -> Result<(), D::Error>
+    where
+        I: IntoIterator<Item = Rgb565>,
+    {
+        self.inner.fill_contiguous(display, area, colors)
+    }
+
+    fn draw_iter<I>(&mut self, display: &mut D, pixels: I) -> Result<(), D::Error>
+    where
+        I: IntoIterator<Item = Pixel<Rgb565>>,
+    {
+        self.inner.draw_iter(display, pixels)
+    }
+
+    fn fill_solid(&mut self, display: &mut D, area: &Rectangle, color: Rgb565) -> Result<(), D::Error> {
+        self.inner.fill_solid(display, area, color)
+    }
+
+    fn flush(&mut self, display: &mut D) -> Result<(), D::Error> {
+        let count = self.te.count();
+        let mut spins = 0;
+        while self.te.count() == count && spins < VSYNC_SPIN_LIMIT {
+            spins += 1;
+        }
+        self.inner.flush(display)
+    }
+
+    fn invalidate(&mut self, area: &Rectangle) {
+        self.inner.invalidate(area);
+    }
+
+    fn invalidate_all(&mut self) {
+        self.inner.invalidate_all();
+    }
+}
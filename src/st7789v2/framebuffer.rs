@@ -0,0 +1,87 @@
+use embedded_graphics::{pixelcolor::Rgb565, prelude::*, primitives::Rectangle};
+
+/// In-memory W x H block of pixels standing in for a horizontal strip of the screen.
+/// Drawing uses display coordinates: pixels between rows `origin().y` and `origin().y + H`
+/// are kept, everything else is dropped, so a full-screen renderer can be run once per strip.
+/// With H equal to the display height it is a whole framebuffer (134 KB for 240x280).
+pub struct Framebuffer<const W: usize, const H: usize> {
+    pixels: [[Rgb565; W]; H],
+    origin: Point,
+}
+
+impl<const W: usize, const H: usize> Framebuffer<W, H> {
+    pub const fn new() -> Self {
+        Self {
+            pixels: [[Rgb565::new(0, 0, 0); W]; H],
+            origin: Point::zero(),
+        }
+    }
+
+    /// Moves the strip so it covers display rows starting at `origin.y`.
+    pub fn set_origin(&mut self, origin: Point) {
+        self.origin = origin;
+    }
+
+    pub fn origin(&self) -> Point {
+        self.origin
+    }
+
+    /// Returns the strip's rows, top to bottom.
+    pub fn rows(&self) -> &[[Rgb565; W]; H] {
+        &self.pixels
+    }
+
+    /// Iterates over the pixels of `area` (display coordinates) in row-major order, black
+    /// where it leaves the strip; e.g. what an overlay about to cover `area` has to save.
+    pub fn region(&self, area: &Rectangle) -> impl Iterator<Item = Rgb565> + '_ {
+        let origin = self.origin;
+        area.points().map(move |point| {
+            let p = point - origin;
+            if (0..W as i32).contains(&p.x) && (0..H as i32).contains(&p.y) {
+                self.pixels[p.y as usize][p.x as usize]
+            } else {
+                Rgb565::new(0, 0, 0)
+            }
+        })
+    }
+
+    /// Iterates over the strip's pixels in row-major order, as `fill_contiguous` expects them.
+    pub fn pixels(&self) -> impl Iterator<Item = Rgb565> + '_ {
+        self.pixels.iter().flatten().copied()
+    }
+}
+
+impl<const W: usize, const H: usize> Default for Framebuffer<W, H> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const W: usize, const H: usize> Dimensions for Framebuffer<W, H> {
+    fn bounding_box(&self) -> Rectangle {
+        Rectangle::new(self.origin, Size::new(W as u32, H as u32))
+    }
+}
+
+impl<const W: usize, const H: usize> DrawTarget for Framebuffer<W, H> {
+    type Color = Rgb565;
+    type Error = core::convert::Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        for Pixel(point, color) in pixels {
+            let p = point - self.origin;
+            if (0..W as i32).contains(&p.x) && (0..H as i32).contains(&p.y) {
+                self.pixels[p.y as usize][p.x as usize] = color;
+            }
+        }
+        Ok(())
+    }
+
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        self.pixels = [[color; W]; H];
+        Ok(())
+    }
+}
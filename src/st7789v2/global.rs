@@ -0,0 +1,58 @@
+use core::cell::RefCell;
+
+use cortex_m::interrupt::{self, Mutex};
+
+/// A driver stashed in a `static`, reachable from `main` and interrupt handlers alike.
+/// Declare one with `static_display!`. Every access runs in a critical section, which is
+/// simple rather than fast: fine for sketch-style applications, not for streaming frames from an ISR.
+pub struct GlobalDisplay<T> {
+    inner: Mutex<RefCell<Option<T>>>,
+}
+
+impl<T> GlobalDisplay<T> {
+    pub const fn new() -> Self {
+        Self {
+            inner: Mutex::new(RefCell::new(None)),
+        }
+    }
+
+    /// Stores the initialized driver, returning the previous one if there was one.
+    pub fn install(&self, display: T) -> Option<T> {
+        interrupt::free(|cs| self.inner.borrow(cs).replace(Some(display)))
+    }
+
+    /// Takes the driver back out.
+    pub fn take(&self) -> Option<T> {
+        interrupt::free(|cs| self.inner.borrow(cs).take())
+    }
+
+    /// Runs `f` on the driver inside a critical section.
+    /// # Returns
+    /// `None` if no driver is installed yet.
+    pub fn with_display<R, F: FnOnce(&mut T) -> R>(&self, f: F) -> Option<R> {
+        interrupt::free(|cs| self.inner.borrow(cs).borrow_mut().as_mut().map(f))
+    }
+}
+
+impl<T> Default for GlobalDisplay<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Declares a `static` `GlobalDisplay` for the given driver type.
+/// The driver must be `'static`, e.g. built with a `singleton!` delay.
+///
+/// ```ignore
+/// type Display = ST7789V2DMA<'static, SPI1, DMA2, CsPin, DcPin, RstPin, 3, 3>;
+/// static_display!(DISPLAY: Display);
+///
+/// DISPLAY.install(display);
+/// DISPLAY.with_display(|d| d.clear(Rgb565::BLACK).ok());
+/// ```
+#[macro_export]
+macro_rules! static_display {
+    ($name:ident : $ty:ty) => {
+        static $name: $crate::st7789v2::global::GlobalDisplay<$ty> = $crate::st7789v2::global::GlobalDisplay::new();
+    };
+}
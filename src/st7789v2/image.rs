@@ -0,0 +1,117 @@
+use embedded_graphics::{
+    pixelcolor::{raw::RawU16, Rgb565},
+    prelude::*,
+    primitives::Rectangle,
+};
+
+/// Pixel encoding of an `Image`'s data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageFormat<'a> {
+    /// Two big-endian RGB565 bytes per pixel, the layout `draw_entire_screen()` takes.
+    Raw565,
+    /// Runs of `count, color MSB, color LSB`, count 1..=255, for images with flat areas.
+    Rle,
+    /// One byte per pixel, indexing `palette`. Indices past its end draw black.
+    Indexed(&'a [Rgb565]),
+}
+
+/// A flash-resident image that carries its size and encoding with the data, e.g.
+/// `static LOGO: Image = Image::new(64, 64, ImageFormat::Rle, include_bytes!("logo.rle"));`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Image<'a> {
+    width: u16,
+    height: u16,
+    format: ImageFormat<'a>,
+    data: &'a [u8],
+}
+
+impl<'a> Image<'a> {
+    pub const fn new(width: u16, height: u16, format: ImageFormat<'a>, data: &'a [u8]) -> Self {
+        Self { width, height, format, data }
+    }
+
+    pub const fn width(&self) -> u16 {
+        self.width
+    }
+
+    pub const fn height(&self) -> u16 {
+        self.height
+    }
+
+    pub const fn format(&self) -> ImageFormat<'a> {
+        self.format
+    }
+
+    pub const fn data(&self) -> &'a [u8] {
+        self.data
+    }
+
+    pub const fn size(&self) -> Size {
+        Size::new(self.width as u32, self.height as u32)
+    }
+
+    /// Decodes the pixels in row-major order. Always yields width * height pixels:
+    /// short data is padded with black, extra data is ignored.
+    pub fn pixels(&self) -> impl Iterator<Item = Rgb565> + 'a {
+        let decoded = match self.format {
+            ImageFormat::Raw565 => Pixels::Raw(self.data.chunks_exact(2)),
+            ImageFormat::Rle => Pixels::Rle { runs: self.data.chunks_exact(3), color: Rgb565::BLACK, left: 0 },
+            ImageFormat::Indexed(palette) => Pixels::Indexed { indices: self.data.iter(), palette },
+        };
+        let count = self.width as usize * self.height as usize;
+        decoded.chain(core::iter::repeat(Rgb565::BLACK)).take(count)
+    }
+
+    /// Draws the image with its top left corner at (`x`, `y`), in one windowed transfer.
+    pub fn draw_at<D>(&self, target: &mut D, x: i32, y: i32) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Rgb565>,
+    {
+        target.fill_contiguous(&Rectangle::new(Point::new(x, y), self.size()), self.pixels())
+    }
+}
+
+enum Pixels<'a> {
+    Raw(core::slice::ChunksExact<'a, u8>),
+    Rle {
+        runs: core::slice::ChunksExact<'a, u8>,
+        color: Rgb565,
+        left: u8,
+    },
+    Indexed {
+        indices: core::slice::Iter<'a, u8>,
+        palette: &'a [Rgb565],
+    },
+}
+
+impl Iterator for Pixels<'_> {
+    type Item = Rgb565;
+
+    fn next(&mut self) -> Option<Rgb565> {
+        match self {
+            Pixels::Raw(bytes) => bytes.next().map(|b| Rgb565::from(RawU16::new(u16::from_be_bytes([b[0], b[1]])))),
+            Pixels::Rle { runs, color, left } => {
+                while *left == 0 {
+                    let run = runs.next()?;
+                    *left = run[0];
+                    *color = Rgb565::from(RawU16::new(u16::from_be_bytes([run[1], run[2]])));
+                }
+                *left -= 1;
+                Some(*color)
+            }
+            Pixels::Indexed { indices, palette } => {
+                indices.next().map(|&i| palette.get(i as usize).copied().unwrap_or(Rgb565::BLACK))
+            }
+        }
+    }
+}
+
+/// Expands to the `Image` build.rs generated from `assets/<file>`, converted to RGB565 or RLE
+/// at compile time: `static LOGO: Image = static_image!("logo.bmp");`.
+#[macro_export]
+macro_rules! static_image {
+    ($file:literal) => {{
+        use $crate::st7789v2::image::{Image, ImageFormat};
+        include!(concat!(env!("OUT_DIR"), "/assets/", $file, ".rs"))
+    }};
+}
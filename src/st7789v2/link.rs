@@ -0,0 +1,121 @@
+use stm32f4xx_hal::gpio::Speed;
+
+/// Fastest SCK the ST7789V2 accepts for writes (serial write cycle tSCYCW = 16 ns).
+pub const PANEL_MAX_WRITE_HZ: u32 = 62_500_000;
+
+/// Fastest SCK an STM32F4 SPI can generate, the peripheral clock divided by 2.
+const MIN_PRESCALER: u32 = 2;
+const MAX_PRESCALER: u32 = 256;
+
+/// Why a requested SPI link can't be set up cleanly.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, defmt::Format)]
+pub enum LinkConfigError {
+    /// The panel can't be written this fast.
+    PanelLimit { requested_hz: u32, max_hz: u32 },
+    /// Even the largest prescaler (256) leaves SCK above the requested clock.
+    ClockTooSlow { requested_hz: u32, min_hz: u32 },
+    /// The GPIO output speed is too low for clean edges at the resulting SCK.
+    PinSpeedTooLow { sck_hz: u32, required: Speed },
+}
+
+/// Checked SPI clock and GPIO speed for the display link.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LinkPlan {
+    /// SCK the SPI will actually run at, pass this to `Spi::new`.
+    pub sck_hz: u32,
+    /// The SPI baud-rate prescaler producing `sck_hz` (2, 4, ... 256).
+    pub prescaler: u32,
+    /// GPIO speed to set on SCK, MOSI, DC and CS.
+    pub pin_speed: Speed,
+}
+
+/// Validates an SPI clock request against the peripheral clock and the panel's limits,
+/// before the pins and SPI are set up.
+/// `check()` is a `const fn`, so with fixed clocks a bad setup can fail the build:
+/// `const LINK: LinkPlan = match LinkConfig::new(84_000_000).spi_clock(20_000_000).check() { ... }`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LinkConfig {
+    pclk_hz: u32,
+    requested_hz: u32,
+    pin_speed: Option<Speed>,
+}
+
+impl LinkConfig {
+    /// Starts from the clock of the APB bus the SPI sits on (pclk2 for SPI1, pclk1 for SPI2/3).
+    pub const fn new(pclk_hz: u32) -> Self {
+        Self {
+            pclk_hz,
+            requested_hz: 12_000_000,
+            pin_speed: None,
+        }
+    }
+
+    /// Sets the wanted SCK. The closest prescaler at or below it is picked.
+    pub const fn spi_clock(mut self, hz: u32) -> Self {
+        self.requested_hz = hz;
+        self
+    }
+
+    /// Sets the GPIO speed the pins will be configured with, to be checked against the SCK.
+    /// Leave unset to have `check()` pick the lowest sufficient speed.
+    pub const fn pin_speed(mut self, speed: Speed) -> Self {
+        self.pin_speed = Some(speed);
+        self
+    }
+
+    pub const fn check(self) -> Result<LinkPlan, LinkConfigError> {
+        if self.requested_hz > PANEL_MAX_WRITE_HZ {
+            return Err(LinkConfigError::PanelLimit { requested_hz: self.requested_hz, max_hz: PANEL_MAX_WRITE_HZ });
+        }
+
+        let mut prescaler = MIN_PRESCALER;
+        while self.pclk_hz / prescaler > self.requested_hz {
+            if prescaler == MAX_PRESCALER {
+                return Err(LinkConfigError::ClockTooSlow {
+                    requested_hz: self.requested_hz,
+                    min_hz: self.pclk_hz / MAX_PRESCALER,
+                });
+            }
+            prescaler *= 2;
+        }
+        let sck_hz = self.pclk_hz / prescaler;
+
+        let required = required_pin_speed(sck_hz);
+        let pin_speed = match self.pin_speed {
+            Some(speed) => {
+                if max_toggle_hz(speed) < max_toggle_hz(required) {
+                    return Err(LinkConfigError::PinSpeedTooLow { sck_hz, required });
+                }
+                speed
+            }
+            None => required,
+        };
+
+        Ok(LinkPlan { sck_hz, prescaler, pin_speed })
+    }
+}
+
+/// Maximum output frequency per GPIO speed setting (STM32F401 datasheet, CL = 50 pF, VDD > 2.7 V).
+const fn max_toggle_hz(speed: Speed) -> u32 {
+    match speed {
+        Speed::Low => 4_000_000,
+        Speed::Medium => 25_000_000,
+        Speed::High => 50_000_000,
+        Speed::VeryHigh => 100_000_000,
+    }
+}
+
+/// Returns the lowest GPIO speed with 2x headroom over `sck_hz`, so edges stay square
+/// instead of the marginal "20 MHz on medium-speed pins" setup.
+pub const fn required_pin_speed(sck_hz: u32) -> Speed {
+    let needed = sck_hz.saturating_mul(2);
+    if needed <= max_toggle_hz(Speed::Low) {
+        Speed::Low
+    } else if needed <= max_toggle_hz(Speed::Medium) {
+        Speed::Medium
+    } else if needed <= max_toggle_hz(Speed::High) {
+        Speed::High
+    } else {
+        Speed::VeryHigh
+    }
+}
@@ -0,0 +1,70 @@
+use serde::{
+    de::{self, SeqAccess, Visitor},
+    ser::SerializeTuple,
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+
+use crate::st7789v2::common::{ColorOrder, Orientation};
+use crate::st7789v2::params::{ColorEnhancement, DigitalGamma, GammaCurves, DIGITAL_GAMMA_LEN};
+
+/// Display tuning loaded from flash instead of compiled in, see `ST7789V2DMA::apply_display_config()`.
+/// The size and row offset describe the panel the settings were made for and must match
+/// the driver's W, H and OFFSET.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DisplayConfig {
+    pub width: u16,
+    pub height: u16,
+    pub row_offset: u16,
+    pub orientation: Orientation,
+    pub mirror_x: bool,
+    pub mirror_y: bool,
+    pub color_order: ColorOrder,
+    pub inverted: bool,
+    pub brightness: u8,
+    pub gamma: Option<GammaCurves>,
+    pub digital_gamma: Option<DigitalGamma>,
+    pub color_enhancement: ColorEnhancement,
+}
+
+impl DisplayConfig {
+    /// Deserializes a config from postcard bytes, e.g. a flash page. No allocation involved.
+    pub fn from_postcard(bytes: &[u8]) -> Result<Self, postcard::Error> {
+        postcard::from_bytes(bytes)
+    }
+}
+
+// serde only derives arrays up to 32 elements, so the 64-entry tables go through
+// a tuple of red entries followed by blue entries.
+impl Serialize for DigitalGamma {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut tuple = serializer.serialize_tuple(DIGITAL_GAMMA_LEN * 2)?;
+        for entry in self.red.iter().chain(self.blue.iter()) {
+            tuple.serialize_element(entry)?;
+        }
+        tuple.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for DigitalGamma {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct TablesVisitor;
+
+        impl<'de> Visitor<'de> for TablesVisitor {
+            type Value = DigitalGamma;
+
+            fn expecting(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+                write!(f, "{} digital gamma entries", DIGITAL_GAMMA_LEN * 2)
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<DigitalGamma, A::Error> {
+                let mut gamma = DigitalGamma { red: [0; DIGITAL_GAMMA_LEN], blue: [0; DIGITAL_GAMMA_LEN] };
+                for (i, entry) in gamma.red.iter_mut().chain(gamma.blue.iter_mut()).enumerate() {
+                    *entry = seq.next_element()?.ok_or_else(|| de::Error::invalid_length(i, &self))?;
+                }
+                Ok(gamma)
+            }
+        }
+
+        deserializer.deserialize_tuple(DIGITAL_GAMMA_LEN * 2, TablesVisitor)
+    }
+}
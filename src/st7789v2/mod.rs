@@ -1,3 +1,28 @@
 pub mod common;
 pub mod dma;
 pub mod spi;
+pub mod spi_irq;
+pub mod bench;
+pub mod color;
+pub mod ui;
+pub mod transform;
+pub mod params;
+pub mod link;
+pub mod te;
+pub mod scheduler;
+pub mod schedule;
+pub mod flush;
+pub mod split;
+pub mod clip;
+pub mod global;
+pub mod panic;
+pub mod encode;
+pub mod image;
+#[cfg(feature = "bitbang")]
+pub mod bitbang;
+#[cfg(feature = "touch")]
+pub mod touch;
+#[cfg(feature = "framebuffer")]
+pub mod framebuffer;
+#[cfg(feature = "config-loader")]
+pub mod loader;
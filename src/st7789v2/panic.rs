@@ -0,0 +1,184 @@
+use core::{
+    cell::UnsafeCell,
+    fmt::{self, Write},
+    panic::PanicInfo,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use cortex_m::interrupt;
+use embedded_graphics::{
+    mono_font::{ascii::FONT_6X10, MonoTextStyle},
+    pixelcolor::Rgb565,
+    prelude::*,
+    primitives::Rectangle,
+    text::{Baseline, Text},
+};
+
+/// Space left around the text, in pixels.
+const MARGIN: u32 = 8;
+
+/// A driver set aside for the panic handler, declared with `panic_display!`.
+/// Install an initialized driver during setup; on a panic the handler takes it back
+/// regardless of what the application was doing with it, and draws the panic message
+/// on a red screen. Works with any `DrawTarget`, but the blocking `ST7789V2` is the
+/// safest choice: it has no DMA transfer that could be left half done.
+pub struct PanicDisplay<T> {
+    display: UnsafeCell<Option<T>>,
+    stolen: AtomicBool,
+}
+
+// SAFETY: the driver is only reached through `install()` (in a critical section),
+// `with_display()` (same) and `steal()` (once, from the panic handler).
+unsafe impl<T: Send> Sync for PanicDisplay<T> {}
+
+impl<T> PanicDisplay<T> {
+    pub const fn new() -> Self {
+        Self {
+            display: UnsafeCell::new(None),
+            stolen: AtomicBool::new(false),
+        }
+    }
+
+    /// Stores the initialized driver, returning the previous one if there was one.
+    pub fn install(&self, display: T) -> Option<T> {
+        // SAFETY: no other reference to the cell exists outside a critical section
+        interrupt::free(|_| unsafe { (*self.display.get()).replace(display) })
+    }
+
+    /// Runs `f` on the driver inside a critical section, for applications that draw
+    /// through the same driver the panic screen uses.
+    /// # Returns
+    /// `None` if no driver is installed, or the panic handler already took it.
+    pub fn with_display<R, F: FnOnce(&mut T) -> R>(&self, f: F) -> Option<R> {
+        if self.stolen.load(Ordering::Acquire) {
+            return None;
+        }
+        // SAFETY: see `install()`
+        interrupt::free(|_| unsafe { (*self.display.get()).as_mut().map(f) })
+    }
+
+    /// Takes the driver for the panic handler, even if it was in use when the panic hit.
+    /// # Returns
+    /// `None` if no driver is installed, or on a second call (a panic while drawing the panic).
+    /// # Safety
+    /// Only call from the panic handler, with interrupts disabled: the driver may be
+    /// mid-operation and nothing else may touch it afterwards.
+    #[allow(clippy::mut_from_ref)] // handed out once, guarded by `stolen`
+    pub unsafe fn steal(&self) -> Option<&mut T> {
+        if self.stolen.swap(true, Ordering::AcqRel) {
+            return None;
+        }
+        unsafe { (*self.display.get()).as_mut() }
+    }
+}
+
+impl<T> Default for PanicDisplay<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Draws the panic screen: a red background, then the message and its location in white,
+/// wrapped at the screen edge. Text that doesn't fit is dropped and drawing errors are
+/// ignored, there's nothing left to report them to.
+pub fn draw_panic<D>(target: &mut D, info: &PanicInfo)
+where
+    D: DrawTarget<Color = Rgb565>,
+{
+    let screen = target.bounding_box();
+    target.fill_solid(&screen, Rgb565::RED).ok();
+
+    let mut writer = PanicWriter {
+        area: screen.offset(-(MARGIN as i32)),
+        cursor: screen.top_left + Point::new(MARGIN as i32, MARGIN as i32),
+        style: MonoTextStyle::new(&FONT_6X10, Rgb565::WHITE),
+        target,
+    };
+    write!(writer, "PANIC\n\n{}", info.message()).ok();
+    if let Some(location) = info.location() {
+        write!(writer, "\n\nat {}:{}:{}", location.file(), location.line(), location.column()).ok();
+    }
+}
+
+/// Body of the handler declared by `panic_display!`: logs the panic over defmt, draws it with
+/// the stolen driver if there is one, then halts.
+pub fn show_panic<D>(display: &PanicDisplay<D>, info: &PanicInfo) -> !
+where
+    D: DrawTarget<Color = Rgb565>,
+{
+    interrupt::disable();
+    defmt::error!("{}", defmt::Display2Format(info));
+
+    // SAFETY: we are the panic handler and interrupts are off
+    if let Some(target) = unsafe { display.steal() } {
+        draw_panic(target, info);
+    }
+
+    loop {
+        cortex_m::asm::wfi();
+    }
+}
+
+/// Writes text into `area` a character at a time, wrapping at its right edge.
+struct PanicWriter<'d, D> {
+    target: &'d mut D,
+    area: Rectangle,
+    cursor: Point,
+    style: MonoTextStyle<'static, Rgb565>,
+}
+
+impl<D: DrawTarget<Color = Rgb565>> PanicWriter<'_, D> {
+    fn new_line(&mut self) {
+        self.cursor.x = self.area.top_left.x;
+        self.cursor.y += FONT_6X10.character_size.height as i32;
+    }
+}
+
+impl<D: DrawTarget<Color = Rgb565>> Write for PanicWriter<'_, D> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let cell = FONT_6X10.character_size;
+        let right = self.area.top_left.x + self.area.size.width as i32;
+        let bottom = self.area.top_left.y + self.area.size.height as i32;
+
+        for c in s.chars() {
+            if c == '\n' {
+                self.new_line();
+                continue;
+            }
+            if self.cursor.x + cell.width as i32 > right {
+                self.new_line();
+            }
+            if self.cursor.y + cell.height as i32 > bottom {
+                return Ok(());
+            }
+            let mut utf8 = [0u8; 4];
+            Text::with_baseline(c.encode_utf8(&mut utf8), self.cursor, self.style, Baseline::Top)
+                .draw(self.target)
+                .ok();
+            self.cursor.x += cell.width as i32;
+        }
+        Ok(())
+    }
+}
+
+/// Declares a `static` `PanicDisplay` for the given driver type and the `#[panic_handler]`
+/// that draws panics on it. Use it instead of `panic-probe` or `panic-halt`.
+///
+/// ```ignore
+/// type PanicScreen = ST7789V2<ExclusiveDevice<Spi<SPI1>, CsPin, NoDelay>, DcPin, RstPin, SysDelay, 240, 280>;
+/// panic_display!(PANIC_SCREEN: PanicScreen);
+///
+/// display.init().unwrap();
+/// PANIC_SCREEN.install(display);
+/// ```
+#[macro_export]
+macro_rules! panic_display {
+    ($name:ident : $ty:ty) => {
+        static $name: $crate::st7789v2::panic::PanicDisplay<$ty> = $crate::st7789v2::panic::PanicDisplay::new();
+
+        #[panic_handler]
+        fn panic(info: &core::panic::PanicInfo) -> ! {
+            $crate::st7789v2::panic::show_panic(&$name, info)
+        }
+    };
+}
@@ -0,0 +1,267 @@
+use crate::st7789v2::common::RAM_HEIGHT;
+
+/// A command parameter the panel would misinterpret, rejected before anything is sent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, defmt::Format)]
+pub enum ParamError {
+    /// VSCRDEF areas must cover the 320 RAM lines exactly; `total` is what they added up to.
+    ScrollAreaSum { total: u32 },
+    /// `value` is past the last valid value `max`.
+    OutOfRange { value: u16, max: u16 },
+    /// A start/end pair with the start after the end.
+    InvertedRange { start: u16, end: u16 },
+    /// An area with no pixel on the screen: zero-sized, or entirely left of or above it.
+    Empty,
+}
+
+/// Checks that `value` is at most `max`.
+pub const fn check_max(value: u16, max: u16) -> Result<u16, ParamError> {
+    if value > max {
+        Err(ParamError::OutOfRange { value, max })
+    } else {
+        Ok(value)
+    }
+}
+
+/// Vertical scroll area definition (VSCRDEF), validated to cover all RAM lines.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, defmt::Format)]
+pub struct ScrollArea {
+    top_fixed: u16,
+    scroll: u16,
+    bottom_fixed: u16,
+}
+
+impl ScrollArea {
+    /// The power-on default: the whole RAM scrolls.
+    pub const FULL: ScrollArea = ScrollArea { top_fixed: 0, scroll: RAM_HEIGHT as u16, bottom_fixed: 0 };
+
+    /// Creates a scroll area with fixed bands at the top and bottom.
+    /// The three heights must add up to the 320 RAM lines.
+    pub const fn new(top_fixed: u16, scroll: u16, bottom_fixed: u16) -> Result<Self, ParamError> {
+        let total = top_fixed as u32 + scroll as u32 + bottom_fixed as u32;
+        if total != RAM_HEIGHT as u32 {
+            return Err(ParamError::ScrollAreaSum { total });
+        }
+        Ok(Self { top_fixed, scroll, bottom_fixed })
+    }
+
+    pub const fn top_fixed(&self) -> u16 {
+        self.top_fixed
+    }
+
+    pub const fn scroll(&self) -> u16 {
+        self.scroll
+    }
+
+    pub const fn bottom_fixed(&self) -> u16 {
+        self.bottom_fixed
+    }
+
+    /// Returns the six VSCRDEF parameter bytes.
+    pub const fn params(&self) -> [u8; 6] {
+        [
+            (self.top_fixed >> 8) as u8,
+            self.top_fixed as u8,
+            (self.scroll >> 8) as u8,
+            self.scroll as u8,
+            (self.bottom_fixed >> 8) as u8,
+            self.bottom_fixed as u8,
+        ]
+    }
+}
+
+impl Default for ScrollArea {
+    fn default() -> Self {
+        Self::FULL
+    }
+}
+
+/// Partial display area (PTLAR), as an inclusive range of RAM rows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, defmt::Format)]
+pub struct PartialArea {
+    start_row: u16,
+    end_row: u16,
+}
+
+impl PartialArea {
+    /// Creates a partial area from `start_row` to `end_row` inclusive, both within the 320 RAM lines.
+    pub const fn new(start_row: u16, end_row: u16) -> Result<Self, ParamError> {
+        let max = RAM_HEIGHT as u16 - 1;
+        if let Err(e) = check_max(end_row, max) {
+            return Err(e);
+        }
+        if start_row > end_row {
+            return Err(ParamError::InvertedRange { start: start_row, end: end_row });
+        }
+        Ok(Self { start_row, end_row })
+    }
+
+    pub const fn start_row(&self) -> u16 {
+        self.start_row
+    }
+
+    pub const fn end_row(&self) -> u16 {
+        self.end_row
+    }
+
+    /// Returns the four PTLAR parameter bytes.
+    pub const fn params(&self) -> [u8; 4] {
+        [
+            (self.start_row >> 8) as u8,
+            self.start_row as u8,
+            (self.end_row >> 8) as u8,
+            self.end_row as u8,
+        ]
+    }
+}
+
+/// Positive and negative voltage gamma curves (PVGAMCTRL/NVGAMCTRL), 14 parameter bytes each,
+/// as listed in the module vendor's init code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, defmt::Format)]
+#[cfg_attr(feature = "config-loader", derive(serde::Serialize, serde::Deserialize))]
+pub struct GammaCurves {
+    pub positive: [u8; 14],
+    pub negative: [u8; 14],
+}
+
+/// Color enhancement level of WRCACE, the panel's built-in saturation boost.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, defmt::Format)]
+#[cfg_attr(feature = "config-loader", derive(serde::Serialize, serde::Deserialize))]
+pub enum ColorEnhancement {
+    #[default]
+    Off,
+    Low,
+    Medium,
+    High,
+}
+
+impl ColorEnhancement {
+    /// Returns the WRCACE parameter: CECTRL and CE[1:0], with content adaptive brightness off.
+    pub const fn param(&self) -> u8 {
+        match self {
+            ColorEnhancement::Off => 0x00,
+            ColorEnhancement::Low => 0b1000_0000,
+            ColorEnhancement::Medium => 0b1001_0000,
+            ColorEnhancement::High => 0b1011_0000,
+        }
+    }
+}
+
+/// Refresh rate in normal mode per FRCTRL2 RTNA value (with the default porches), in Hz.
+const FRAME_RATES_HZ: [u8; 32] = [
+    119, 111, 105, 99, 94, 90, 86, 82, 78, 75, 72, 69, 67, 64, 62, 60,
+    58, 57, 55, 53, 52, 50, 49, 48, 46, 45, 44, 43, 42, 41, 40, 39,
+];
+
+/// Panel refresh rate in normal mode (FRCTRL2), 39 to 119 Hz.
+/// Lower rates save driver IC power; below ~40 Hz some panels visibly flicker.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, defmt::Format)]
+pub struct FrameRate(u8);
+
+impl FrameRate {
+    /// Power-on default.
+    pub const HZ_60: FrameRate = FrameRate(0x0F);
+    /// Slowest rate the controller offers.
+    pub const HZ_39: FrameRate = FrameRate(0x1F);
+
+    /// Returns the supported rate closest to `hz`.
+    pub const fn from_hz(hz: u8) -> Self {
+        let mut best = 0;
+        let mut i = 1;
+        while i < FRAME_RATES_HZ.len() {
+            if FRAME_RATES_HZ[i].abs_diff(hz) < FRAME_RATES_HZ[best].abs_diff(hz) {
+                best = i;
+            }
+            i += 1;
+        }
+        FrameRate(best as u8)
+    }
+
+    pub const fn hz(&self) -> u8 {
+        FRAME_RATES_HZ[self.0 as usize]
+    }
+
+    /// Returns the FRCTRL2 parameter: NLA = 0 (dot inversion), RTNA.
+    pub const fn param(&self) -> u8 {
+        self.0
+    }
+}
+
+impl FrameRate {
+    /// Takes a FRCTRL2 parameter back, e.g. from a saved configuration.
+    /// # Errors
+    /// `ParamError::OutOfRange` past the slowest rate, RTNA 0x1F.
+    pub const fn from_param(param: u8) -> Result<Self, ParamError> {
+        match check_max(param as u16, FRAME_RATES_HZ.len() as u16 - 1) {
+            Ok(_) => Ok(FrameRate(param)),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+impl Default for FrameRate {
+    fn default() -> Self {
+        Self::HZ_60
+    }
+}
+
+/// Back and front porch in normal mode (PORCTRL), in lines. Longer porches give the
+/// controller more idle time between frames and lower the refresh rate below what
+/// `FrameRate` lists, which assumes the default.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, defmt::Format)]
+pub struct Porch {
+    back: u8,
+    front: u8,
+}
+
+impl Porch {
+    /// Power-on default, 12 lines each.
+    pub const DEFAULT: Porch = Porch { back: 0x0C, front: 0x0C };
+
+    /// Creates a porch setting of 1 to 127 lines each.
+    /// # Errors
+    /// `ParamError::OutOfRange` for a porch of 0 or past 127 lines.
+    pub const fn new(back: u8, front: u8) -> Result<Self, ParamError> {
+        if back == 0 || back > 0x7F {
+            return Err(ParamError::OutOfRange { value: back as u16, max: 0x7F });
+        }
+        if front == 0 || front > 0x7F {
+            return Err(ParamError::OutOfRange { value: front as u16, max: 0x7F });
+        }
+        Ok(Self { back, front })
+    }
+
+    pub const fn back(&self) -> u8 {
+        self.back
+    }
+
+    pub const fn front(&self) -> u8 {
+        self.front
+    }
+
+    /// Returns the five PORCTRL parameter bytes; idle and partial mode keep their defaults.
+    pub const fn params(&self) -> [u8; 5] {
+        [self.back, self.front, 0x00, 0x33, 0x33]
+    }
+}
+
+impl Default for Porch {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// Entries per digital gamma lookup table.
+pub const DIGITAL_GAMMA_LEN: usize = 64;
+
+/// Digital gamma lookup tables for red and blue (DGMLUTR/DGMLUTB), as shipped in some module
+/// vendors' calibration data. Green has no table on the ST7789.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, defmt::Format)]
+pub struct DigitalGamma {
+    pub red: [u8; DIGITAL_GAMMA_LEN],
+    pub blue: [u8; DIGITAL_GAMMA_LEN],
+}
+
+impl DigitalGamma {
+    /// DGMEN parameter with DGM_EN set.
+    pub const ENABLE: u8 = 0b0000_0100;
+}
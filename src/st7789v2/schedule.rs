@@ -0,0 +1,145 @@
+use crate::st7789v2::{params::GammaCurves, ui::theme::ThemeMode};
+
+/// Seconds in a day; times of day wrap around at this.
+pub const DAY_SECONDS: u32 = 24 * 60 * 60;
+
+/// Where a `DimSchedule` gets the time of day from, e.g. the RTC. Implemented for closures
+/// returning seconds since midnight.
+pub trait TimeOfDay {
+    fn seconds_of_day(&mut self) -> u32;
+}
+
+impl<F: FnMut() -> u32> TimeOfDay for F {
+    fn seconds_of_day(&mut self) -> u32 {
+        self()
+    }
+}
+
+/// One part of the day in a `DimSchedule`, lasting until the next period starts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Period {
+    /// Start, in seconds since midnight.
+    pub start: u32,
+    /// Brightness faded to from the previous period's over the schedule's fade time.
+    pub brightness: u8,
+    /// Theme switched to when the period starts.
+    pub theme: ThemeMode,
+    /// Gamma curves switched to when the period starts, `None` for the panel's defaults.
+    pub gamma: Option<GammaCurves>,
+}
+
+impl Period {
+    pub const fn new(start: u32, brightness: u8, theme: ThemeMode) -> Self {
+        Self { start, brightness, theme, gamma: None }
+    }
+
+    pub const fn with_gamma(mut self, gamma: GammaCurves) -> Self {
+        self.gamma = Some(gamma);
+        self
+    }
+}
+
+/// What changed since the previous `DimSchedule::poll()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScheduleUpdate {
+    /// New brightness, set while fading or when a period starts.
+    pub brightness: Option<u8>,
+    /// The period that just started: switch to its theme and gamma. Widgets need a full
+    /// redraw after `apply_theme()`.
+    pub period: Option<Period>,
+}
+
+/// Day/night schedule for brightness, theme and gamma, e.g. dim and dark at night, bright
+/// and light during the day. Brightness fades linearly into each period instead of jumping,
+/// the theme and gamma switch at its start. Apply updates with
+/// `ST7789V2DMA::apply_schedule()` and the theme to the UI's `ThemePair`:
+///
+/// ```ignore
+/// static DAY: [Period; 2] = [
+///     Period::new(7 * 3600, 255, ThemeMode::Light),
+///     Period::new(21 * 3600, 40, ThemeMode::Dark),
+/// ];
+/// let mut schedule = DimSchedule::new(&DAY, 600, || rtc.seconds_of_day());
+///
+/// loop {
+///     if let Some(update) = schedule.poll() {
+///         display.apply_schedule(&update);
+///         if let Some(period) = update.period {
+///             themes.mode = period.theme;
+///             menu.apply_theme(themes.current());
+///         }
+///     }
+/// }
+/// ```
+pub struct DimSchedule<'a, T> {
+    periods: &'a [Period],
+    fade_seconds: u32,
+    time: T,
+    /// Period index and brightness as last reported.
+    last: Option<(usize, u8)>,
+}
+
+impl<'a, T: TimeOfDay> DimSchedule<'a, T> {
+    /// `periods` must be sorted by start; the last one lasts over midnight into the first.
+    /// `fade_seconds` is how long the brightness takes to reach a period's level.
+    pub fn new(periods: &'a [Period], fade_seconds: u32, time: T) -> Self {
+        Self {
+            periods,
+            fade_seconds,
+            time,
+            last: None,
+        }
+    }
+
+    /// Returns the current period, `None` for an empty schedule.
+    pub fn current(&mut self) -> Option<Period> {
+        let now = self.time.seconds_of_day() % DAY_SECONDS;
+        self.index_at(now).map(|index| self.periods[index])
+    }
+
+    /// Reads the time and returns what changed since the last call, if anything.
+    /// The first call reports the current period. Call it every few hundred milliseconds
+    /// for a smooth fade.
+    pub fn poll(&mut self) -> Option<ScheduleUpdate> {
+        let now = self.time.seconds_of_day() % DAY_SECONDS;
+        let index = self.index_at(now)?;
+        let brightness = self.brightness_at(index, now);
+
+        let (last_index, last_brightness) = match self.last.replace((index, brightness)) {
+            Some(last) => (Some(last.0), Some(last.1)),
+            None => (None, None),
+        };
+        let update = ScheduleUpdate {
+            brightness: (last_brightness != Some(brightness)).then_some(brightness),
+            period: (last_index != Some(index)).then_some(self.periods[index]),
+        };
+        (update.brightness.is_some() || update.period.is_some()).then_some(update)
+    }
+
+    /// Makes the next `poll()` report the current period and brightness again, e.g. after
+    /// the display was reinitialized.
+    pub fn reset(&mut self) {
+        self.last = None;
+    }
+
+    fn index_at(&self, now: u32) -> Option<usize> {
+        if self.periods.is_empty() {
+            return None;
+        }
+        // before the first start it is still the last period of the previous day
+        let index = self.periods.iter().rposition(|period| period.start <= now);
+        Some(index.unwrap_or(self.periods.len() - 1))
+    }
+
+    fn brightness_at(&self, index: usize, now: u32) -> u8 {
+        let period = self.periods[index];
+        let previous = self.periods[index.checked_sub(1).unwrap_or(self.periods.len() - 1)];
+        let elapsed = (now + DAY_SECONDS - period.start) % DAY_SECONDS;
+        if elapsed >= self.fade_seconds {
+            return period.brightness;
+        }
+
+        let (from, to) = (previous.brightness as i32, period.brightness as i32);
+        (from + (to - from) * elapsed as i32 / self.fade_seconds as i32) as u8
+    }
+}
@@ -0,0 +1,115 @@
+use core::sync::atomic::{AtomicU32, Ordering};
+
+use stm32f4xx_hal::{
+    rcc::Clocks,
+    timer::{self, CounterHz, Event, Flag, Instance, TimerExt},
+    time::Hertz,
+    ClearFlags, Listen,
+};
+
+/// Fixed-rate frame clock shared between a timer interrupt and the code that draws.
+/// The interrupt only counts ticks, so animation timing follows the timer instead of
+/// main-loop jitter; `poll()` tells the drawing side how many frames are due.
+///
+/// ```ignore
+/// static FRAMES: RefreshScheduler = RefreshScheduler::new();
+///
+/// #[interrupt]
+/// fn TIM2() {
+///     TIMER.on_interrupt(&FRAMES); // TIMER: RefreshTimer<TIM2> in a Mutex/static
+/// }
+///
+/// loop {
+///     if let Some(elapsed) = FRAMES.poll() {
+///         menu.step();          // advance animations by `elapsed` frames
+///         menu.render(&mut display).ok();
+///     }
+/// }
+/// ```
+pub struct RefreshScheduler {
+    ticks: AtomicU32,
+    served: AtomicU32,
+    missed: AtomicU32,
+}
+
+impl RefreshScheduler {
+    pub const fn new() -> Self {
+        Self {
+            ticks: AtomicU32::new(0),
+            served: AtomicU32::new(0),
+            missed: AtomicU32::new(0),
+        }
+    }
+
+    /// Marks one more frame as due. Call from the timer interrupt.
+    pub fn tick(&self) {
+        self.ticks.fetch_add(1, Ordering::Release);
+    }
+
+    /// Returns the number of frames due since the last call, or `None` if none is.
+    /// More than one means the drawing side fell behind; the extra ones count as missed.
+    pub fn poll(&self) -> Option<u32> {
+        let ticks = self.ticks.load(Ordering::Acquire);
+        let elapsed = ticks.wrapping_sub(self.served.swap(ticks, Ordering::Relaxed));
+        if elapsed == 0 {
+            return None;
+        }
+        if elapsed > 1 {
+            self.missed.fetch_add(elapsed - 1, Ordering::Relaxed);
+        }
+        Some(elapsed)
+    }
+
+    /// Runs `frame` with the number of elapsed frames if one is due.
+    pub fn run<F: FnOnce(u32)>(&self, frame: F) -> bool {
+        match self.poll() {
+            Some(elapsed) => {
+                frame(elapsed);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Returns the number of ticks that passed while the previous frame was still being drawn.
+    pub fn missed(&self) -> u32 {
+        self.missed.load(Ordering::Relaxed)
+    }
+}
+
+impl Default for RefreshScheduler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A general-purpose timer set up to interrupt at the refresh rate and tick a `RefreshScheduler`.
+pub struct RefreshTimer<TIM: Instance> {
+    counter: CounterHz<TIM>,
+}
+
+impl<TIM: Instance> RefreshTimer<TIM> {
+    /// Starts `tim` at `rate` with the update interrupt enabled. Unmask the timer's
+    /// interrupt in the NVIC and call `on_interrupt()` from its handler.
+    pub fn new(tim: TIM, rate: Hertz, clocks: &Clocks) -> Result<Self, timer::Error> {
+        let mut counter = tim.counter_hz(clocks);
+        counter.start(rate)?;
+        counter.listen(Event::Update);
+        Ok(Self { counter })
+    }
+
+    /// Changes the tick rate, e.g. to `Profile::host_fps()` when switching power profiles.
+    pub fn set_rate(&mut self, rate: Hertz) -> Result<(), timer::Error> {
+        self.counter.start(rate)
+    }
+
+    /// Acknowledges the update interrupt and ticks `scheduler`.
+    pub fn on_interrupt(&mut self, scheduler: &RefreshScheduler) {
+        self.counter.clear_flags(Flag::Update);
+        scheduler.tick();
+    }
+
+    pub fn release(self) -> TIM {
+        self.counter.release().release()
+    }
+}
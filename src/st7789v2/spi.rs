@@ -1,52 +1,68 @@
-use crate::st7789v2::common::{Commands, Error};
-use cortex_m::delay::Delay;
+use core::convert::Infallible;
+
+use crate::st7789v2::clip::Window;
+use crate::st7789v2::common::{Commands, DeviceParts, Error, Madctl};
 use defmt::debug;
-use stm32f4xx_hal::{
-    hal::digital::OutputPin, spi::{Instance, Spi}
-};
+use embedded_graphics::{pixelcolor::{raw::ToBytes, Rgb565}, prelude::*, primitives::Rectangle};
+use embedded_hal::{delay::DelayNs, digital::{self, OutputPin}, spi::{self, SpiDevice}};
+
+/// Size of the blocking driver's staging buffer for small data writes.
+pub const STAGING_SIZE: usize = 64;
+
+/// Non-visible rows above the panel's first visible row.
+const ROW_OFFSET: u16 = 20;
+
+/// Errors of the blocking driver. Chip select belongs to the `SpiDevice`, so its errors come
+/// as `Error::Spi` and `Error::CS` holds `Infallible`: matches can leave that arm out.
+pub type DriverError<SPI, DC, RST> =
+    Error<<SPI as spi::ErrorType>::Error, Infallible, <DC as digital::ErrorType>::Error, <RST as digital::ErrorType>::Error>;
 
 /// ST7789V2 driver for the ST7789V2 display.
-/// This driver uses SPI for communication and requires a data/command pin, a reset pin,
-/// and a chip select pin.
-/// TODO: Implement DMA support for faster data transfer.
-pub struct ST7789V2<'a, SPI, DC, RST, CS, const W: usize, const H: usize>
+/// This driver is plain embedded-hal 1.0, so it runs on any MCU HAL: it needs an `SpiDevice`
+/// (which owns chip select, e.g. `embedded_hal_bus::spi::ExclusiveDevice`), a data/command pin,
+/// a reset pin and a delay. The DMA driver is the STM32F4 specific one, as is the
+/// interrupt-driven write path in `spi_irq`.
+pub struct ST7789V2<SPI, DC, RST, DELAY, const W: usize, const H: usize>
 where
-    SPI: Instance,
+    SPI: SpiDevice,
     DC: OutputPin,
     RST: OutputPin,
-    CS: OutputPin,
+    DELAY: DelayNs,
 {
-    spi: Spi<SPI>,
-    dc: DC,
+    pub(super) spi: SPI,
+    pub(super) dc: DC,
     rst: RST,
-    cs: CS,
-    delay: &'a mut Delay,
+    delay: DELAY,
+    staging: [u8; STAGING_SIZE], // small data writes collected until flush()
+    staged: usize,
+    pub(super) async_pending: bool, // a write started by `spi_irq` may still be running in the device
 }
 
-impl<'a, SPI, DC, RST, CS, const W: usize, const H: usize> ST7789V2<'a, SPI, DC, RST, CS, W, H>
+impl<SPI, DC, RST, DELAY, const W: usize, const H: usize> ST7789V2<SPI, DC, RST, DELAY, W, H>
 where
-    SPI: Instance,
+    SPI: SpiDevice,
     DC: OutputPin,
     RST: OutputPin,
-    CS: OutputPin,
+    DELAY: DelayNs,
 {
     /// Creates a new instance of the ST7789V2 driver.
     /// # Arguments
-    /// * `spi` - The SPI interface to use for communication. must be initialized.
+    /// * `spi` - The SPI device to use for communication, with chip select (active low) handled by the device.
     /// * `dc` - The data/command pin, used to switch between data and command mode. when high, it is in data mode and when low, it is in command mode.
     /// * `rst` - The reset pin, used to reset the display.
-    /// * `cs` - The chip select pin, used to select the display. it is active low.
-    /// * `delay` - A mutable reference to a delay object, used for timing operations.
+    /// * `delay` - A delay, used for timing operations. `&mut` delays work too.
     /// # Returns
     /// A new instance of the ST7789V2 driver.
-    pub const fn new(spi: Spi<SPI>, dc: DC, rst: RST, cs: CS, delay: &'a mut Delay) -> Self {
+    pub const fn new(spi: SPI, dc: DC, rst: RST, delay: DELAY) -> Self {
         // initialzing the controller
         Self {
             spi,
             dc,
             rst,
-            cs,
             delay,
+            staging: [0; STAGING_SIZE],
+            staged: 0,
+            async_pending: false,
         }
     }
 
@@ -63,7 +79,7 @@ where
     /// note: there is a delay after each command to allow the display to process the command.
     pub fn init(
         &mut self,
-    ) -> Result<(), Error<stm32f4xx_hal::spi::Error, CS::Error, DC::Error, RST::Error>> {
+    ) -> Result<(), DriverError<SPI, DC, RST>> {
         // Reset the display
         self.rst.set_low().map_err(Error::RST)?;
         self.delay.delay_ms(120);
@@ -78,10 +94,12 @@ where
 
         self.send_command(Commands::SetColorMode)?; // Set color mode
         self.send_data(&[0x55])?; // Set to RGB565 color mode
+        self.flush()?;
         self.delay.delay_ms(10);
 
         self.send_command(Commands::MemoryDataAccessControl)?; // Memory data access control
-        self.send_data(&[0b0000_0000])?; // Set to normal mode (no rotation)
+        self.send_data(&[Madctl::empty().bits()])?; // Set to normal mode (no rotation)
+        self.flush()?;
         self.delay.delay_ms(10);
 
         self.send_command(Commands::DisplayOn)?; // Display on
@@ -96,8 +114,8 @@ where
     pub fn draw_screen(
         &mut self,
         buffer: &[u8],
-    ) -> Result<(), Error<stm32f4xx_hal::spi::Error, CS::Error, DC::Error, RST::Error>> {
-        let y_offset = 20; // Y offset for the display
+    ) -> Result<(), DriverError<SPI, DC, RST>> {
+        let y_offset = ROW_OFFSET; // Y offset for the display
         let y_end = y_offset + H as u16 - 1; // Y end address for the display
 
         let x_offset = 0; // X offset for the display
@@ -134,38 +152,199 @@ where
         // Write memory
         self.send_command(Commands::RAMWR)?;
         self.send_data(buffer)?;
+        self.flush()?;
 
         debug!("draw screen with buffer of size: {}", buffer.len());
 
         Ok(())
     }
 
+    /// Sends a command, flushing any staged data first.
     pub fn send_command(
         &mut self,
         cmd: Commands,
-    ) -> Result<(), Error<stm32f4xx_hal::spi::Error, CS::Error, DC::Error, RST::Error>> {
+    ) -> Result<(), DriverError<SPI, DC, RST>> {
+        self.flush()?;
+        self.settle()?;
         self.dc.set_low().map_err(Error::DC)?;
-        self.cs.set_low().map_err(Error::CS)?;
         self.spi.write(&[cmd as u8]).map_err(Error::Spi)?;
-        self.cs.set_high().map_err(Error::CS)?;
 
         Ok(())
     }
 
+    /// Queues parameter or pixel data.
+    /// Small writes are collected in the staging buffer and go out in one SPI transaction on
+    /// the next `send_command()`, `flush()`, or when the buffer fills up; writes larger than the
+    /// buffer are sent directly. Call `flush()` after the last write of a sequence.
     pub fn send_data(
         &mut self,
         data: &[u8],
-    ) -> Result<(), Error<stm32f4xx_hal::spi::Error, CS::Error, DC::Error, RST::Error>> {
+    ) -> Result<(), DriverError<SPI, DC, RST>> {
+        if self.staged + data.len() > STAGING_SIZE {
+            self.flush()?;
+        }
+
+        if data.len() >= STAGING_SIZE {
+            return self.write_data(data);
+        }
+
+        self.staging[self.staged..self.staged + data.len()].copy_from_slice(data);
+        self.staged += data.len();
+
+        Ok(())
+    }
+
+    /// Sends the staged data, if any.
+    pub fn flush(
+        &mut self,
+    ) -> Result<(), DriverError<SPI, DC, RST>> {
+        if self.staged == 0 {
+            return Ok(());
+        }
+
+        let staged = self.staged;
+        self.staged = 0;
+        let staging = self.staging;
+        self.write_data(&staging[..staged])
+    }
+
+    fn write_data(
+        &mut self,
+        data: &[u8],
+    ) -> Result<(), DriverError<SPI, DC, RST>> {
+        self.settle()?;
         self.dc.set_high().map_err(Error::DC)?;
-        self.cs.set_low().map_err(Error::CS)?;
         self.spi.write(data).map_err(Error::Spi)?;
-        self.cs.set_high().map_err(Error::CS)?;
 
         Ok(())
     }
 
-    pub fn release(self) -> (Spi<SPI>, DC, RST, CS) {
+    /// Waits for a write the device may still be running (see `spi_irq::TxeDevice`) before DC
+    /// changes: an empty transaction returns once the bus is idle.
+    fn settle(&mut self) -> Result<(), DriverError<SPI, DC, RST>> {
+        if self.async_pending {
+            self.spi.transaction(&mut []).map_err(Error::Spi)?;
+            self.async_pending = false;
+        }
+
+        Ok(())
+    }
+
+    /// Sets the RAMWR window to the on-screen, non-empty `area` and starts the write.
+    fn begin_area(
+        &mut self,
+        area: &Rectangle,
+    ) -> Result<(), DriverError<SPI, DC, RST>> {
+        let xs = area.top_left.x as u16;
+        let xe = xs + area.size.width as u16 - 1;
+        let ys = area.top_left.y as u16 + ROW_OFFSET;
+        let ye = ys + area.size.height as u16 - 1;
+
+        self.send_command(Commands::CASET)?;
+        self.send_data(&[(xs >> 8) as u8, xs as u8, (xe >> 8) as u8, xe as u8])?;
+        self.send_command(Commands::RASET)?;
+        self.send_data(&[(ys >> 8) as u8, ys as u8, (ye >> 8) as u8, ye as u8])?;
+        self.send_command(Commands::RAMWR)
+    }
+
+    /// Returns the delay, e.g. for application timing between draws.
+    pub fn delay(&mut self) -> &mut DELAY {
+        &mut self.delay
+    }
+
+    /// Borrows the SPI device, pins and delay, see `DeviceParts`. Staged data is sent first
+    /// and a pending interrupt-driven write finished, on a best-effort basis like `release()`.
+    pub fn parts(&mut self) -> DeviceParts<'_, SPI, DC, RST, DELAY> {
+        self.flush().ok();
+        self.settle().ok();
+        DeviceParts {
+            spi: &mut self.spi,
+            dc: &mut self.dc,
+            rst: &mut self.rst,
+            delay: &mut self.delay,
+        }
+    }
+
+    /// Releases the peripherals. Staged data is flushed first, on a best-effort basis.
+    pub fn release(mut self) -> (SPI, DC, RST, DELAY) {
+        self.flush().ok();
+        self.settle().ok();
         // Release the resources held by the driver
-        (self.spi, self.dc, self.rst, self.cs)
+        (self.spi, self.dc, self.rst, self.delay)
+    }
+}
+
+impl<SPI, DC, RST, DELAY, const W: usize, const H: usize> OriginDimensions for ST7789V2<SPI, DC, RST, DELAY, W, H>
+where
+    SPI: SpiDevice,
+    DC: OutputPin,
+    RST: OutputPin,
+    DELAY: DelayNs,
+{
+    fn size(&self) -> Size {
+        Size::new(W as u32, H as u32)
+    }
+}
+
+/// Plain blocking drawing, one window per pixel or filled area: slow, but it needs nothing
+/// besides the SPI, e.g. for the panic screen (see `panic_display!`).
+impl<SPI, DC, RST, DELAY, const W: usize, const H: usize> DrawTarget for ST7789V2<SPI, DC, RST, DELAY, W, H>
+where
+    SPI: SpiDevice,
+    DC: OutputPin,
+    RST: OutputPin,
+    DELAY: DelayNs,
+{
+    type Color = Rgb565;
+    type Error = DriverError<SPI, DC, RST>;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let screen = self.bounding_box();
+        for Pixel(point, color) in pixels {
+            if screen.contains(point) {
+                self.begin_area(&Rectangle::new(point, Size::new(1, 1)))?;
+                self.send_data(&color.to_be_bytes())?;
+            }
+        }
+        self.flush()
+    }
+
+    fn fill_contiguous<I>(&mut self, area: &Rectangle, colors: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Self::Color>,
+    {
+        let Some(window) = Window::clip(area, &self.bounding_box()) else {
+            return Ok(());
+        };
+
+        self.begin_area(&window.rectangle())?;
+        for color in window.visible_colors(area, colors) {
+            self.send_data(&color.to_be_bytes())?;
+        }
+        self.flush()
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        let Some(window) = Window::clip(area, &self.bounding_box()) else {
+            return Ok(());
+        };
+        let area = window.rectangle();
+
+        self.begin_area(&area)?;
+        let bytes = color.to_be_bytes();
+        let mut run = [0u8; STAGING_SIZE];
+        for pair in run.chunks_exact_mut(2) {
+            pair.copy_from_slice(&bytes);
+        }
+        let mut remaining = area.size.width as usize * area.size.height as usize * 2;
+        while remaining > 0 {
+            let len = remaining.min(STAGING_SIZE);
+            self.send_data(&run[..len])?;
+            remaining -= len;
+        }
+        self.flush()
     }
 }
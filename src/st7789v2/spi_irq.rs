@@ -0,0 +1,241 @@
+use core::cell::UnsafeCell;
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use embedded_hal::delay::DelayNs;
+use embedded_hal::digital::OutputPin;
+use embedded_hal::spi::{self, ErrorKind, ErrorType, Operation, SpiBus, SpiDevice};
+use stm32f4xx_hal::spi::{Instance, Spi};
+
+use crate::st7789v2::common::Error;
+use crate::st7789v2::spi::{DriverError, ST7789V2};
+
+/// Single-producer/single-consumer byte ring between the blocking driver (producer)
+/// and the SPI TXE interrupt (consumer). Holds up to N - 1 bytes.
+pub struct TxQueue<const N: usize> {
+    buf: UnsafeCell<[u8; N]>,
+    head: AtomicUsize, // next slot the producer writes
+    tail: AtomicUsize, // next slot the consumer reads
+}
+
+// SAFETY: the producer only writes slots between head and tail - 1, the consumer only reads
+// slots between tail and head, and each index is published with release/acquire ordering.
+unsafe impl<const N: usize> Sync for TxQueue<N> {}
+
+impl<const N: usize> TxQueue<N> {
+    pub const fn new() -> Self {
+        Self {
+            buf: UnsafeCell::new([0; N]),
+            head: AtomicUsize::new(0),
+            tail: AtomicUsize::new(0),
+        }
+    }
+
+    /// Queues as much of `data` as fits. Producer side only.
+    /// # Returns
+    /// The number of bytes queued.
+    pub fn push_slice(&self, data: &[u8]) -> usize {
+        let mut head = self.head.load(Ordering::Relaxed);
+        let tail = self.tail.load(Ordering::Acquire);
+        let mut queued = 0;
+
+        for &byte in data {
+            let next = (head + 1) % N;
+            if next == tail {
+                break;
+            }
+            // SAFETY: slot `head` is not visible to the consumer until `head` is published below
+            unsafe { (*self.buf.get())[head] = byte };
+            head = next;
+            queued += 1;
+        }
+
+        self.head.store(head, Ordering::Release);
+        queued
+    }
+
+    /// Takes the oldest byte. Consumer (interrupt) side only.
+    pub fn pop(&self) -> Option<u8> {
+        let tail = self.tail.load(Ordering::Relaxed);
+        if tail == self.head.load(Ordering::Acquire) {
+            return None;
+        }
+        // SAFETY: the producer doesn't touch slot `tail` until `tail` is advanced below
+        let byte = unsafe { (*self.buf.get())[tail] };
+        self.tail.store((tail + 1) % N, Ordering::Release);
+        Some(byte)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tail.load(Ordering::Acquire) == self.head.load(Ordering::Acquire)
+    }
+}
+
+impl<const N: usize> Default for TxQueue<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Feeds the SPI from `queue`. Call from the SPI's interrupt handler; once the queue is
+/// drained the TXE interrupt is switched off until the driver queues more data.
+pub fn on_txe_interrupt<SPI: Instance, const N: usize>(queue: &TxQueue<N>) {
+    // SAFETY: only the data register and TXEIE are touched, the driver doesn't use the SPI
+    // while an asynchronous write is pending.
+    let spi = unsafe { &*SPI::ptr() };
+
+    if spi.sr().read().txe().bit_is_set() {
+        match queue.pop() {
+            Some(byte) => spi.dr8().write(|w| unsafe { w.dr().bits(byte) }),
+            None => spi.cr2().modify(|_, w| w.txeie().clear_bit()),
+        }
+    }
+}
+
+/// A failed transaction of a `TxeDevice`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TxeError<CSE> {
+    Spi(stm32f4xx_hal::spi::Error),
+    CS(CSE),
+}
+
+impl<CSE: core::fmt::Debug> spi::Error for TxeError<CSE> {
+    fn kind(&self) -> ErrorKind {
+        match self {
+            TxeError::Spi(error) => error.kind(),
+            TxeError::CS(_) => ErrorKind::ChipSelectFault,
+        }
+    }
+}
+
+/// `SpiDevice` on the stm32f4xx `Spi` and a CS pin that also runs the interrupt-driven write
+/// path: with it, `ST7789V2` gets `send_data_async()`/`finish_async()`. Every transaction
+/// waits for a pending interrupt-driven write first, so the blocking calls can be mixed in.
+pub struct TxeDevice<SPI: Instance, CS: OutputPin> {
+    spi: Spi<SPI>,
+    cs: CS,
+    async_pending: bool, // CS held low while the TXE interrupt drains a TxQueue
+}
+
+impl<SPI: Instance, CS: OutputPin> TxeDevice<SPI, CS> {
+    /// `spi` must be initialized, `cs` is active low.
+    pub const fn new(spi: Spi<SPI>, cs: CS) -> Self {
+        Self {
+            spi,
+            cs,
+            async_pending: false,
+        }
+    }
+
+    /// Selects the panel for an interrupt-driven write, until `finish_async()`.
+    fn begin_async(&mut self) -> Result<(), TxeError<CS::Error>> {
+        if !self.async_pending {
+            self.cs.set_low().map_err(TxeError::CS)?;
+            self.async_pending = true;
+        }
+
+        Ok(())
+    }
+
+    /// Waits for the interrupt-driven path to drain and releases CS.
+    fn finish_async(&mut self) -> Result<(), TxeError<CS::Error>> {
+        if !self.async_pending {
+            return Ok(());
+        }
+
+        // SAFETY: the interrupt handler clears TXEIE once the queue is empty, after that
+        // nothing else touches the registers
+        let spi = unsafe { &*SPI::ptr() };
+        while spi.cr2().read().txeie().bit_is_set() {}
+        while spi.sr().read().bsy().bit_is_set() {}
+        // drop what the transmit clocked in, and the overrun it caused, so blocking writes work again
+        let _ = spi.dr8().read();
+        let _ = spi.sr().read();
+
+        self.cs.set_high().map_err(TxeError::CS)?;
+        self.async_pending = false;
+
+        Ok(())
+    }
+
+    /// Releases the SPI and CS pin, once a pending interrupt-driven write is done.
+    pub fn release(mut self) -> (Spi<SPI>, CS) {
+        self.finish_async().ok();
+        (self.spi, self.cs)
+    }
+}
+
+impl<SPI: Instance, CS: OutputPin> ErrorType for TxeDevice<SPI, CS> {
+    type Error = TxeError<CS::Error>;
+}
+
+impl<SPI: Instance, CS: OutputPin> SpiDevice for TxeDevice<SPI, CS> {
+    /// An empty transaction only waits for a pending interrupt-driven write.
+    fn transaction(&mut self, operations: &mut [Operation<'_, u8>]) -> Result<(), Self::Error> {
+        self.finish_async()?;
+        if operations.is_empty() {
+            return Ok(());
+        }
+
+        self.cs.set_low().map_err(TxeError::CS)?;
+        let result = operations
+            .iter_mut()
+            .try_for_each(|operation| match operation {
+                Operation::Read(words) => SpiBus::read(&mut self.spi, words),
+                Operation::Write(words) => SpiBus::write(&mut self.spi, words),
+                Operation::Transfer(read, write) => SpiBus::transfer(&mut self.spi, read, write),
+                Operation::TransferInPlace(words) => SpiBus::transfer_in_place(&mut self.spi, words),
+                Operation::DelayNs(ns) => {
+                    // a cycle takes at least a nanosecond at any core clock this part runs at
+                    cortex_m::asm::delay(*ns);
+                    Ok(())
+                }
+            })
+            .and_then(|()| SpiBus::flush(&mut self.spi));
+        self.cs.set_high().map_err(TxeError::CS)?;
+
+        result.map_err(TxeError::Spi)
+    }
+}
+
+impl<SPI, CS, DC, RST, DELAY, const W: usize, const H: usize> ST7789V2<TxeDevice<SPI, CS>, DC, RST, DELAY, W, H>
+where
+    SPI: Instance,
+    CS: OutputPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DELAY: DelayNs,
+{
+    /// Queues data for the interrupt-driven path and returns without waiting for the bus.
+    /// The SPI's interrupt handler must call `spi_irq::on_txe_interrupt::<SPI, N>(queue)` with the
+    /// same queue. CS stays low until `finish_async()`, which `send_command()` calls itself.
+    /// # Returns
+    /// How many bytes of `data` fit in the queue; call again with the rest.
+    pub fn send_data_async<const N: usize>(
+        &mut self,
+        queue: &TxQueue<N>,
+        data: &[u8],
+    ) -> Result<usize, DriverError<TxeDevice<SPI, CS>, DC, RST>> {
+        self.flush()?;
+        if !self.async_pending {
+            self.dc.set_high().map_err(Error::DC)?;
+            self.spi.begin_async().map_err(Error::Spi)?;
+            self.async_pending = true;
+        }
+
+        let queued = queue.push_slice(data);
+
+        // SAFETY: only TXEIE is touched here, the interrupt handler owns the data register
+        let spi = unsafe { &*SPI::ptr() };
+        spi.cr2().modify(|_, w| w.txeie().set_bit());
+
+        Ok(queued)
+    }
+
+    /// Waits for the interrupt-driven path to drain and releases CS.
+    pub fn finish_async(&mut self) -> Result<(), DriverError<TxeDevice<SPI, CS>, DC, RST>> {
+        self.spi.finish_async().map_err(Error::Spi)?;
+        self.async_pending = false;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,154 @@
+use embedded_graphics::{prelude::*, primitives::Rectangle};
+
+use crate::st7789v2::flush::envelope;
+
+/// Where `split()` divides the screen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, defmt::Format)]
+pub enum Split {
+    /// The first pane is the top `n` rows, e.g. a status bar, the second the rest.
+    Top(u32),
+    /// The first pane is the left `n` columns, the second the rest.
+    Left(u32),
+}
+
+/// Divides `screen` (usually the display's `bounding_box()`) into two panes. `n` past the
+/// screen edge leaves the second pane empty.
+pub fn split(screen: Rectangle, at: Split) -> (Pane, Pane) {
+    let Size { width, height } = screen.size;
+    let (first, second) = match at {
+        Split::Top(n) => {
+            let n = n.min(height);
+            (
+                Rectangle::new(screen.top_left, Size::new(width, n)),
+                Rectangle::new(screen.top_left + Point::new(0, n as i32), Size::new(width, height - n)),
+            )
+        }
+        Split::Left(n) => {
+            let n = n.min(width);
+            (
+                Rectangle::new(screen.top_left, Size::new(n, height)),
+                Rectangle::new(screen.top_left + Point::new(n as i32, 0), Size::new(width - n, height)),
+            )
+        }
+    };
+    (Pane::new(first), Pane::new(second))
+}
+
+/// One part of the screen owned by one firmware component. Drawing goes through
+/// `target()`, which has its origin at the pane's top left corner and drops everything
+/// outside the pane, so components never need to know where the others are or coordinate
+/// window state: each fill sets its own window. The pane remembers the bounding box of what
+/// was drawn since `take_dirty()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Pane {
+    area: Rectangle,
+    dirty: Option<Rectangle>,
+}
+
+impl Pane {
+    pub const fn new(area: Rectangle) -> Self {
+        Self { area, dirty: None }
+    }
+
+    /// Returns the pane's area in display coordinates.
+    pub fn area(&self) -> Rectangle {
+        self.area
+    }
+
+    /// Lends `display` as a DrawTarget covering only this pane, in pane coordinates.
+    pub fn target<'t, D: DrawTarget>(&'t mut self, display: &'t mut D) -> PaneTarget<'t, D> {
+        PaneTarget { display, pane: self }
+    }
+
+    /// Returns what was drawn or invalidated since the last call, in pane coordinates.
+    pub fn take_dirty(&mut self) -> Option<Rectangle> {
+        self.dirty.take()
+    }
+
+    pub fn is_dirty(&self) -> bool {
+        self.dirty.is_some()
+    }
+
+    /// Marks `area` (pane coordinates) dirty without drawing, e.g. to force a redraw.
+    pub fn invalidate(&mut self, area: &Rectangle) {
+        let area = area.intersection(&Rectangle::new(Point::zero(), self.area.size));
+        if area.is_zero_sized() {
+            return;
+        }
+        self.dirty = Some(match self.dirty {
+            Some(dirty) => envelope(dirty, area),
+            None => area,
+        });
+    }
+
+    pub fn invalidate_all(&mut self) {
+        self.dirty = Some(Rectangle::new(Point::zero(), self.area.size));
+    }
+}
+
+/// DrawTarget for one `Pane`, see `Pane::target()`.
+pub struct PaneTarget<'t, D> {
+    display: &'t mut D,
+    pane: &'t mut Pane,
+}
+
+impl<D> PaneTarget<'_, D> {
+    /// Maps `area` in pane coordinates to the visible part in display coordinates.
+    fn visible(&self, area: &Rectangle) -> Rectangle {
+        area.translate(self.pane.area.top_left).intersection(&self.pane.area)
+    }
+}
+
+impl<D> OriginDimensions for PaneTarget<'_, D> {
+    fn size(&self) -> Size {
+        self.pane.area.size
+    }
+}
+
+impl<D: DrawTarget> DrawTarget for PaneTarget<'_, D> {
+    type Color = D::Color;
+    type Error = D::Error;
+
+    fn fill_contiguous<I>(&mut self, area: &Rectangle, colors: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Self::Color>,
+    {
+        let visible = self.visible(area);
+        if visible.is_zero_sized() {
+            return Ok(());
+        }
+        self.pane.invalidate(&visible.translate(-self.pane.area.top_left));
+
+        let offset = self.pane.area.top_left;
+        let colors = area
+            .points()
+            .zip(colors)
+            .filter(|(point, _)| visible.contains(*point + offset))
+            .map(|(_, color)| color);
+        self.display.fill_contiguous(&visible, colors)
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        let visible = self.visible(area);
+        if visible.is_zero_sized() {
+            return Ok(());
+        }
+        self.pane.invalidate(&visible.translate(-self.pane.area.top_left));
+        self.display.fill_solid(&visible, color)
+    }
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let Self { display, pane } = self;
+        let (offset, bounds) = (pane.area.top_left, Rectangle::new(Point::zero(), pane.area.size));
+        display.draw_iter(pixels.into_iter().filter_map(|Pixel(point, color)| {
+            if !bounds.contains(point) {
+                return None;
+            }
+            pane.invalidate(&Rectangle::new(point, Size::new(1, 1)));
+            Some(Pixel(point + offset, color))
+        }))
+    }
+}
@@ -0,0 +1,94 @@
+use core::sync::atomic::{AtomicU32, Ordering};
+
+use cortex_m::peripheral::DWT;
+
+/// Counts tearing-effect (TE) pulses to measure the panel's actual refresh rate and phase.
+/// Lives in a `static` and is fed from the EXTI interrupt of the pin wired to TE,
+/// with the output enabled by `ST7789V2DMA::set_tearing_effect(true)`:
+///
+/// ```ignore
+/// static TE: TeCounter = TeCounter::new();
+///
+/// #[interrupt]
+/// fn EXTI1() {
+///     // clear the EXTI pending bit first
+///     TE.on_pulse();
+/// }
+/// ```
+///
+/// Timestamps come from the DWT cycle counter, which must be enabled (e.g. `DWT.constrain()`).
+pub struct TeCounter {
+    count: AtomicU32,
+    last: AtomicU32,
+    /// Smoothed pulse period in core cycles, 0 until two pulses were seen.
+    period: AtomicU32,
+}
+
+impl TeCounter {
+    pub const fn new() -> Self {
+        Self {
+            count: AtomicU32::new(0),
+            last: AtomicU32::new(0),
+            period: AtomicU32::new(0),
+        }
+    }
+
+    /// Records a TE pulse now. Call from the TE pin's EXTI interrupt handler.
+    pub fn on_pulse(&self) {
+        self.on_pulse_at(DWT::cycle_count());
+    }
+
+    /// Records a TE pulse seen at `cycles` (DWT cycle count).
+    pub fn on_pulse_at(&self, cycles: u32) {
+        let previous = self.last.swap(cycles, Ordering::Relaxed);
+        if self.count.fetch_add(1, Ordering::Relaxed) > 0 {
+            let sample = cycles.wrapping_sub(previous);
+            let period = self.period.load(Ordering::Relaxed);
+            // 1/8 exponential average, seeded with the first sample
+            let smoothed = if period == 0 { sample } else { period - period / 8 + sample / 8 };
+            self.period.store(smoothed, Ordering::Relaxed);
+        }
+    }
+
+    /// Returns the number of pulses seen.
+    pub fn count(&self) -> u32 {
+        self.count.load(Ordering::Relaxed)
+    }
+
+    /// Returns the smoothed refresh period in core cycles, `None` before two pulses were seen.
+    pub fn period_cycles(&self) -> Option<u32> {
+        match self.period.load(Ordering::Relaxed) {
+            0 => None,
+            period => Some(period),
+        }
+    }
+
+    /// Returns the measured refresh rate in millihertz for a core running at `core_hz`,
+    /// e.g. 60_000 for 60 Hz. Compare against the rate FRCTRL2 should produce.
+    pub fn refresh_millihertz(&self, core_hz: u32) -> Option<u32> {
+        self.period_cycles()
+            .map(|period| (core_hz as u64 * 1000 / period as u64) as u32)
+    }
+
+    /// Returns how far into the current refresh the panel is, in cycles since the last pulse.
+    pub fn phase_cycles(&self) -> u32 {
+        DWT::cycle_count().wrapping_sub(self.last.load(Ordering::Relaxed))
+    }
+
+    /// Returns the cycles until the next expected pulse, for scheduling a flush right after vsync.
+    pub fn cycles_until_next(&self) -> Option<u32> {
+        self.period_cycles()
+            .map(|period| period.saturating_sub(self.phase_cycles() % period))
+    }
+
+    pub fn reset(&self) {
+        self.count.store(0, Ordering::Relaxed);
+        self.period.store(0, Ordering::Relaxed);
+    }
+}
+
+impl Default for TeCounter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
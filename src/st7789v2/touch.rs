@@ -0,0 +1,69 @@
+use embedded_graphics::{prelude::*, primitives::Rectangle};
+use embedded_hal::i2c::I2c;
+
+use crate::st7789v2::transform::Transform;
+
+/// Touch controllers found on 1.69" ST7789V2 modules.
+/// Both expose the touch count and first point at registers 0x02..=0x06.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TouchController {
+    Cst816s,
+    Ft6236,
+}
+
+impl TouchController {
+    /// Returns the controller's 7-bit I2C address.
+    pub const fn address(self) -> u8 {
+        match self {
+            TouchController::Cst816s => 0x15,
+            TouchController::Ft6236 => 0x38,
+        }
+    }
+}
+
+/// Register holding the number of touch points, followed by P1 XH, XL, YH, YL.
+const REG_TOUCH_COUNT: u8 = 0x02;
+
+/// Touch input in panel coordinates, mapped to display space through a `Transform`.
+pub struct Touch<I2C> {
+    i2c: I2C,
+    controller: TouchController,
+}
+
+impl<I2C: I2c> Touch<I2C> {
+    pub fn new(i2c: I2C, controller: TouchController) -> Self {
+        Self { i2c, controller }
+    }
+
+    /// Reads the first touch point in the panel's native portrait coordinates.
+    /// # Returns
+    /// `None` when nothing touches the panel.
+    pub fn read_raw(&mut self) -> Result<Option<Point>, I2C::Error> {
+        let mut regs = [0u8; 5];
+        self.i2c
+            .write_read(self.controller.address(), &[REG_TOUCH_COUNT], &mut regs)?;
+
+        if regs[0] & 0x0F == 0 {
+            return Ok(None);
+        }
+
+        let x = (((regs[1] & 0x0F) as i32) << 8) | regs[2] as i32;
+        let y = (((regs[3] & 0x0F) as i32) << 8) | regs[4] as i32;
+        Ok(Some(Point::new(x, y)))
+    }
+
+    /// Reads the first touch point in display space for a W x H panel drawn through `transform`
+    /// (see `ST7789V2DMA::transform()`).
+    pub fn read<const W: usize, const H: usize>(&mut self, transform: &Transform) -> Result<Option<Point>, I2C::Error> {
+        Ok(self.read_raw()?.map(|p| transform.panel_to_display::<W, H>(p)))
+    }
+
+    pub fn release(self) -> I2C {
+        self.i2c
+    }
+}
+
+/// Returns the index of the topmost (last) region containing `point`.
+pub fn hit_test(point: Point, regions: &[Rectangle]) -> Option<usize> {
+    regions.iter().rposition(|region| region.contains(point))
+}
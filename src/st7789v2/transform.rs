@@ -0,0 +1,96 @@
+use embedded_graphics::prelude::*;
+
+use crate::st7789v2::common::{Madctl, Orientation, RAM_HEIGHT, RAM_WIDTH};
+
+/// Rotation and mirroring between display space (what the DrawTarget and widgets see)
+/// and panel space (the W x H glass in its native portrait orientation).
+/// Methods take the panel size and the OFFSET of its first visible RAM row as const generics,
+/// like the driver itself.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Transform {
+    pub orientation: Orientation,
+    pub mirror_x: bool,
+    pub mirror_y: bool,
+}
+
+impl Transform {
+    pub const fn new(orientation: Orientation, mirror_x: bool, mirror_y: bool) -> Self {
+        Self { orientation, mirror_x, mirror_y }
+    }
+
+    /// Returns the MADCTL geometry bits (MY, MX, MV), without the color order.
+    /// Mirroring always flips the logical axis, whichever of MX/MY drives it in this orientation.
+    pub const fn madctl(&self) -> Madctl {
+        let mut madctl = self.orientation.madctl_bits();
+        let (x_bit, y_bit) = if self.orientation.is_landscape() {
+            (Madctl::MY, Madctl::MX)
+        } else {
+            (Madctl::MX, Madctl::MY)
+        };
+        if self.mirror_x {
+            madctl = madctl.toggle(x_bit);
+        }
+        if self.mirror_y {
+            madctl = madctl.toggle(y_bit);
+        }
+        madctl
+    }
+
+    /// Returns the display (width, height) for a W x H panel.
+    pub const fn size<const W: usize, const H: usize>(&self) -> (usize, usize) {
+        if self.orientation.is_landscape() { (H, W) } else { (W, H) }
+    }
+
+    /// Returns the RAM (column, row) offset of the visible window for a W x H panel whose
+    /// visible rows start at OFFSET, in the address space CASET/RASET see under this MADCTL.
+    /// When an axis is mirrored, the window sits at the other end of the 240x320 RAM.
+    pub const fn ram_offset<const W: usize, const H: usize, const OFFSET: usize>(&self) -> (u16, u16) {
+        self.ram_offset_at::<W, H>(0, OFFSET as u16)
+    }
+
+    /// Like `ram_offset()`, for a visible window that starts at `column`, `row` of the RAM in
+    /// the panel's native portrait orientation, e.g. narrow panels centered in the 240 columns.
+    pub const fn ram_offset_at<const W: usize, const H: usize>(&self, column: u16, row: u16) -> (u16, u16) {
+        let (column, row) = (column as usize, row as usize);
+        let madctl = self.madctl();
+        let row_offset = if madctl.contains(Madctl::MY) { RAM_HEIGHT - H - row } else { row };
+        let column_offset = if madctl.contains(Madctl::MX) { RAM_WIDTH - W - column } else { column };
+
+        if madctl.contains(Madctl::MV) {
+            (row_offset as u16, column_offset as u16)
+        } else {
+            (column_offset as u16, row_offset as u16)
+        }
+    }
+
+    /// Maps a point in display space to panel space.
+    pub fn display_to_panel<const W: usize, const H: usize>(&self, p: Point) -> Point {
+        let (dw, dh) = self.size::<W, H>();
+        let x = if self.mirror_x { dw as i32 - 1 - p.x } else { p.x };
+        let y = if self.mirror_y { dh as i32 - 1 - p.y } else { p.y };
+
+        let (w, h) = (W as i32, H as i32);
+        match self.orientation {
+            Orientation::Portrait => Point::new(x, y),
+            Orientation::Landscape => Point::new(w - 1 - y, x),
+            Orientation::PortraitFlipped => Point::new(w - 1 - x, h - 1 - y),
+            Orientation::LandscapeFlipped => Point::new(y, h - 1 - x),
+        }
+    }
+
+    /// Maps a point in panel space (e.g. a touch report) to display space.
+    pub fn panel_to_display<const W: usize, const H: usize>(&self, p: Point) -> Point {
+        let (w, h) = (W as i32, H as i32);
+        let p = match self.orientation {
+            Orientation::Portrait => p,
+            Orientation::Landscape => Point::new(p.y, w - 1 - p.x),
+            Orientation::PortraitFlipped => Point::new(w - 1 - p.x, h - 1 - p.y),
+            Orientation::LandscapeFlipped => Point::new(h - 1 - p.y, p.x),
+        };
+
+        let (dw, dh) = self.size::<W, H>();
+        let x = if self.mirror_x { dw as i32 - 1 - p.x } else { p.x };
+        let y = if self.mirror_y { dh as i32 - 1 - p.y } else { p.y };
+        Point::new(x, y)
+    }
+}
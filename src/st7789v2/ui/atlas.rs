@@ -0,0 +1,234 @@
+use crate::st7789v2::color::blend;
+use crate::st7789v2::ui::glyph::GlyphSource;
+use embedded_graphics::{pixelcolor::Rgb565, prelude::*, primitives::Rectangle};
+
+const ATLAS_MAGIC: [u8; 2] = *b"FA";
+const ATLAS_VERSION: u8 = 1;
+const HEADER_LEN: usize = 8;
+const ENTRY_LEN: usize = 12;
+
+/// Bits per pixel of an atlas' bitmaps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, defmt::Format)]
+pub enum AtlasBpp {
+    /// On/off pixels, eight per byte, MSB first.
+    One,
+    /// Alpha coverage, two pixels per byte, high nibble first.
+    Four,
+}
+
+impl AtlasBpp {
+    const fn bits(self) -> u32 {
+        match self {
+            AtlasBpp::One => 1,
+            AtlasBpp::Four => 4,
+        }
+    }
+}
+
+/// Why atlas data was rejected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, defmt::Format)]
+pub enum AtlasError {
+    /// Wrong magic, unknown version or bit depth.
+    BadHeader,
+    /// The glyph table is shorter than the header's glyph count says.
+    Truncated,
+    /// Glyph `index` pokes out of its cell or past the end of the bitmaps.
+    GlyphOutOfBounds { index: u16 },
+    /// The glyph table isn't sorted by character, lookups need it to be.
+    Unsorted { index: u16 },
+}
+
+/// A font loaded from data instead of code, usable wherever a `GlyphSource` is.
+/// Binary layout (version 1, integers big-endian):
+/// header `"FA"`, version, bpp (1 or 4), cell width, cell height, glyph count (2);
+/// then one 12-byte entry per glyph sorted by character: char (4), x, y, width, height
+/// (the glyph's box inside the cell), bitmap offset (4); then the bitmaps, one per glyph,
+/// rows padded to whole bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FontAtlas<'a> {
+    bpp: AtlasBpp,
+    cell: Size,
+    table: &'a [u8],
+    bitmaps: &'a [u8],
+}
+
+/// A glyph table entry.
+#[derive(Debug, Clone, Copy)]
+struct Entry {
+    c: u32,
+    bounds: Rectangle,
+    offset: usize,
+}
+
+impl<'a> FontAtlas<'a> {
+    /// Loads an atlas from one blob in the layout above, e.g. `include_bytes!("font.fa")`.
+    pub fn from_bytes(bytes: &'a [u8]) -> Result<Self, AtlasError> {
+        if bytes.len() < HEADER_LEN || bytes[..2] != ATLAS_MAGIC || bytes[2] != ATLAS_VERSION {
+            return Err(AtlasError::BadHeader);
+        }
+        let bpp = match bytes[3] {
+            1 => AtlasBpp::One,
+            4 => AtlasBpp::Four,
+            _ => return Err(AtlasError::BadHeader),
+        };
+        let count = u16::from_be_bytes([bytes[6], bytes[7]]) as usize;
+        let table_end = HEADER_LEN + count * ENTRY_LEN;
+        if bytes.len() < table_end {
+            return Err(AtlasError::Truncated);
+        }
+
+        AtlasBuilder::new(bpp, Size::new(bytes[4] as u32, bytes[5] as u32))
+            .glyph_table(&bytes[HEADER_LEN..table_end])
+            .bitmaps(&bytes[table_end..])
+            .build()
+    }
+
+    pub fn bpp(&self) -> AtlasBpp {
+        self.bpp
+    }
+
+    /// Number of glyphs in the atlas.
+    pub fn len(&self) -> usize {
+        self.table.len() / ENTRY_LEN
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.table.is_empty()
+    }
+
+    fn entry(&self, index: usize) -> Entry {
+        let e = &self.table[index * ENTRY_LEN..(index + 1) * ENTRY_LEN];
+        Entry {
+            c: u32::from_be_bytes([e[0], e[1], e[2], e[3]]),
+            bounds: Rectangle::new(Point::new(e[4] as i32, e[5] as i32), Size::new(e[6] as u32, e[7] as u32)),
+            offset: u32::from_be_bytes([e[8], e[9], e[10], e[11]]) as usize,
+        }
+    }
+
+    fn stride(&self, width: u32) -> usize {
+        (width * self.bpp.bits()).div_ceil(8) as usize
+    }
+
+    fn find(&self, c: char) -> Option<Entry> {
+        let (mut lo, mut hi) = (0, self.len());
+        while lo < hi {
+            let mid = (lo + hi) / 2;
+            let entry = self.entry(mid);
+            match entry.c.cmp(&(c as u32)) {
+                core::cmp::Ordering::Equal => return Some(entry),
+                core::cmp::Ordering::Less => lo = mid + 1,
+                core::cmp::Ordering::Greater => hi = mid,
+            }
+        }
+        None
+    }
+
+    /// Checks every entry, so drawing never reads out of bounds.
+    fn validate(&self) -> Result<(), AtlasError> {
+        let cell = Rectangle::new(Point::zero(), self.cell);
+        let mut previous = None;
+        for index in 0..self.len() {
+            let entry = self.entry(index);
+            let len = self.stride(entry.bounds.size.width) * entry.bounds.size.height as usize;
+            let index = index as u16;
+
+            let inside = entry.bounds.is_zero_sized() || cell.contains(entry.bounds.bottom_right().unwrap_or(Point::zero()));
+            if !inside || entry.offset + len > self.bitmaps.len() {
+                return Err(AtlasError::GlyphOutOfBounds { index });
+            }
+            if previous.is_some_and(|c| c >= entry.c) {
+                return Err(AtlasError::Unsorted { index });
+            }
+            previous = Some(entry.c);
+        }
+        Ok(())
+    }
+}
+
+/// Assembles a `FontAtlas` from separately stored parts, e.g. a glyph table and a bitmap
+/// blob each brought in with `include_bytes!`, in the entry and bitmap layout of `FontAtlas`.
+pub struct AtlasBuilder<'a> {
+    bpp: AtlasBpp,
+    cell: Size,
+    table: &'a [u8],
+    bitmaps: &'a [u8],
+}
+
+impl<'a> AtlasBuilder<'a> {
+    pub const fn new(bpp: AtlasBpp, cell: Size) -> Self {
+        Self { bpp, cell, table: &[], bitmaps: &[] }
+    }
+
+    /// Sets the 12-byte glyph entries, sorted by character.
+    pub const fn glyph_table(mut self, table: &'a [u8]) -> Self {
+        self.table = table;
+        self
+    }
+
+    /// Sets the bitmap data the entries' offsets point into.
+    pub const fn bitmaps(mut self, bitmaps: &'a [u8]) -> Self {
+        self.bitmaps = bitmaps;
+        self
+    }
+
+    /// Validates every glyph and returns the atlas.
+    pub fn build(self) -> Result<FontAtlas<'a>, AtlasError> {
+        if !self.table.len().is_multiple_of(ENTRY_LEN) {
+            return Err(AtlasError::Truncated);
+        }
+        let atlas = FontAtlas {
+            bpp: self.bpp,
+            cell: self.cell,
+            table: self.table,
+            bitmaps: self.bitmaps,
+        };
+        atlas.validate()?;
+        Ok(atlas)
+    }
+}
+
+impl GlyphSource for FontAtlas<'_> {
+    fn cell_size(&self) -> Size {
+        self.cell
+    }
+
+    fn has_glyph(&self, c: char) -> bool {
+        self.find(c).is_some()
+    }
+
+    fn draw_glyph<D>(&self, c: char, top_left: Point, fg: Rgb565, bg: Rgb565, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Rgb565>,
+    {
+        let area = Rectangle::new(top_left, self.cell);
+        let Some(entry) = self.find(c) else {
+            return target.fill_solid(&area, bg);
+        };
+
+        // 1 bpp uses the first and last shade only
+        let mut shades = [bg; 16];
+        for (alpha, shade) in shades.iter_mut().enumerate() {
+            *shade = blend(fg, bg, alpha as u8 * 17);
+        }
+
+        let bitmap = &self.bitmaps[entry.offset..];
+        let stride = self.stride(entry.bounds.size.width);
+        let bpp = self.bpp;
+        let pixels = area.points().map(move |p| {
+            let p = p - top_left;
+            if !entry.bounds.contains(p) {
+                return bg;
+            }
+            let (x, y) = ((p.x - entry.bounds.top_left.x) as usize, (p.y - entry.bounds.top_left.y) as usize);
+            let alpha = match bpp {
+                AtlasBpp::One => (bitmap[y * stride + x / 8] & (0x80 >> (x % 8)) != 0) as u8 * 15,
+                AtlasBpp::Four => {
+                    let byte = bitmap[y * stride + x / 2];
+                    if x % 2 == 0 { byte >> 4 } else { byte & 0x0F }
+                }
+            };
+            shades[alpha as usize]
+        });
+        target.fill_contiguous(&area, pixels)
+    }
+}
@@ -0,0 +1,107 @@
+use crate::st7789v2::ui::theme::{Theme, Themed};
+use embedded_graphics::{pixelcolor::Rgb565, prelude::*, primitives::Rectangle};
+
+/// Bar graph of N columns growing up from the bottom of its area, e.g. an audio spectrum.
+/// `render()` only touches bars whose height changed, and only the strip between the old
+/// and new height: a bar that grew gets the difference filled, one that shrank gets it
+/// cleared. A spectrum where every bar moves a few pixels per frame sends a small fraction
+/// of the area, which is what keeps it at 30+ fps over SPI.
+pub struct BarGraph<const N: usize> {
+    area: Rectangle,
+    gap: u32,
+    bar: Rgb565,
+    background: Rgb565,
+    /// Heights in pixels to show on the next render.
+    heights: [u32; N],
+    /// Heights as last drawn, `None` forces a full redraw.
+    drawn: Option<[u32; N]>,
+}
+
+impl<const N: usize> BarGraph<N> {
+    /// Creates an empty graph filling `area`, with `gap` pixels between bars. Any width left
+    /// over after dividing by N stays on the right in the background color.
+    pub fn new(area: Rectangle, gap: u32, bar: Rgb565, background: Rgb565) -> Self {
+        Self {
+            area,
+            gap,
+            bar,
+            background,
+            heights: [0; N],
+            drawn: None,
+        }
+    }
+
+    /// Sets bar `index` to `value` out of `max`, scaled to the area height.
+    pub fn set(&mut self, index: usize, value: u32, max: u32) {
+        if let Some(height) = self.heights.get_mut(index) {
+            *height = (value.min(max) as u64 * self.area.size.height as u64 / max.max(1) as u64) as u32;
+        }
+    }
+
+    /// Sets the first bars from `values` out of `max`, e.g. the bins of an FFT.
+    pub fn set_all(&mut self, values: &[u32], max: u32) {
+        for (index, &value) in values.iter().enumerate().take(N) {
+            self.set(index, value, max);
+        }
+    }
+
+    /// Returns the height in pixels bar `index` will have after the next render.
+    pub fn height(&self, index: usize) -> u32 {
+        self.heights.get(index).copied().unwrap_or(0)
+    }
+
+    pub fn set_colors(&mut self, bar: Rgb565, background: Rgb565) {
+        self.bar = bar;
+        self.background = background;
+        self.invalidate();
+    }
+
+    /// Makes the next `render()` redraw the whole area.
+    pub fn invalidate(&mut self) {
+        self.drawn = None;
+    }
+
+    /// Draws the bars whose height changed since the last render.
+    pub fn render<D>(&mut self, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Rgb565>,
+    {
+        let drawn = match self.drawn {
+            Some(drawn) => drawn,
+            None => {
+                target.fill_solid(&self.area, self.background)?;
+                [0; N]
+            }
+        };
+
+        for (index, (&height, &old)) in self.heights.iter().zip(drawn.iter()).enumerate() {
+            if height == old {
+                continue;
+            }
+            let (low, high, color) = if height > old {
+                (old, height, self.bar)
+            } else {
+                (height, old, self.background)
+            };
+            target.fill_solid(&self.strip(index, low, high), color)?;
+        }
+
+        self.drawn = Some(self.heights);
+        Ok(())
+    }
+
+    /// Returns the part of bar `index` between `low` and `high` pixels above the bottom.
+    fn strip(&self, index: usize, low: u32, high: u32) -> Rectangle {
+        let pitch = self.area.size.width / N.max(1) as u32;
+        let width = pitch.saturating_sub(self.gap).max(1);
+        let bottom = self.area.top_left.y + self.area.size.height as i32;
+        let top_left = Point::new(self.area.top_left.x + (pitch * index as u32) as i32, bottom - high as i32);
+        Rectangle::new(top_left, Size::new(width, high - low))
+    }
+}
+
+impl<const N: usize> Themed for BarGraph<N> {
+    fn apply_theme(&mut self, theme: &Theme) {
+        self.set_colors(theme.primary, theme.background);
+    }
+}
@@ -0,0 +1,152 @@
+use crate::st7789v2::ui::theme::{Theme, Themed};
+use embedded_graphics::{
+    pixelcolor::Rgb565,
+    prelude::*,
+    primitives::{Circle, Line, PrimitiveStyle, Rectangle},
+};
+
+/// sin(i * 6°) * 1024 for the first quadrant of the 60 clock positions.
+const SIN_TABLE: [i32; 16] = [0, 107, 213, 316, 416, 512, 602, 685, 761, 828, 887, 935, 974, 1002, 1018, 1024];
+
+/// Returns (sin, cos) * 1024 of clock position `pos` (0..60, 0 is 12 o'clock, clockwise).
+fn sin_cos(pos: u32) -> (i32, i32) {
+    let sin = |p: u32| -> i32 {
+        let p = p % 60;
+        match p {
+            0..=15 => SIN_TABLE[p as usize],
+            16..=30 => SIN_TABLE[(30 - p) as usize],
+            31..=45 => -SIN_TABLE[(p - 30) as usize],
+            _ => -SIN_TABLE[(60 - p) as usize],
+        }
+    };
+    (sin(pos), sin(pos + 15))
+}
+
+/// Colors of a `ClockFace`.
+#[derive(Debug, Clone, Copy)]
+pub struct ClockStyle {
+    pub background: Rgb565,
+    pub ticks: Rgb565,
+    pub hour_hand: Rgb565,
+    pub minute_hand: Rgb565,
+    pub second_hand: Rgb565,
+}
+
+/// Analog clock face.
+/// The dial is drawn once; on every update only the hands that moved are erased, by drawing
+/// them over in the background color, and the ticks and hands inside that area are redrawn,
+/// so a seconds tick costs a couple of thin line updates instead of a full redraw.
+pub struct ClockFace {
+    center: Point,
+    radius: u32,
+    style: ClockStyle,
+    /// Clock positions (0..60) of the hour, minute and second hands as last drawn.
+    drawn: Option<[u32; 3]>,
+    hands: [u32; 3],
+}
+
+impl ClockFace {
+    pub fn new(center: Point, radius: u32, style: ClockStyle) -> Self {
+        Self {
+            center,
+            radius,
+            style,
+            drawn: None,
+            hands: [0; 3],
+        }
+    }
+
+    /// Sets the time shown by the hands.
+    pub fn set_time(&mut self, hours: u32, minutes: u32, seconds: u32) {
+        self.hands = [(hours % 12) * 5 + (minutes % 60) / 12, minutes % 60, seconds % 60];
+    }
+
+    /// Forces the next `render()` to redraw the whole dial.
+    pub fn invalidate(&mut self) {
+        self.drawn = None;
+    }
+
+    pub fn bounding_box(&self) -> Rectangle {
+        Circle::with_center(self.center, self.radius * 2 + 1).bounding_box()
+    }
+
+    /// Redraws the dial on the first call and only the moved hands afterwards.
+    pub fn render<D>(&mut self, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Rgb565>,
+    {
+        match self.drawn {
+            None => {
+                target.fill_solid(&self.bounding_box(), self.style.background)?;
+                self.draw_ticks(target, None)?;
+            }
+            Some(old) => {
+                for (hand, &old_pos) in old.iter().enumerate() {
+                    if old_pos != self.hands[hand] {
+                        let line = self.hand_line(hand, old_pos);
+                        line.into_styled(PrimitiveStyle::with_stroke(self.style.background, Self::hand_width(hand)))
+                            .draw(target)?;
+                        let erased = line.bounding_box().offset(Self::hand_width(hand) as i32);
+                        self.draw_ticks(target, Some(&erased))?;
+                    }
+                }
+            }
+        }
+
+        for hand in 0..3 {
+            let color = [self.style.hour_hand, self.style.minute_hand, self.style.second_hand][hand];
+            self.hand_line(hand, self.hands[hand])
+                .into_styled(PrimitiveStyle::with_stroke(color, Self::hand_width(hand)))
+                .draw(target)?;
+        }
+
+        self.drawn = Some(self.hands);
+        Ok(())
+    }
+
+    fn hand_width(hand: usize) -> u32 {
+        [4, 3, 1][hand]
+    }
+
+    fn hand_line(&self, hand: usize, pos: u32) -> Line {
+        let length = self.radius as i32 * [50, 75, 85][hand] / 100;
+        Line::new(self.center, self.point_at(pos, length))
+    }
+
+    fn point_at(&self, pos: u32, length: i32) -> Point {
+        let (sin, cos) = sin_cos(pos);
+        self.center + Point::new(sin * length / 1024, -cos * length / 1024)
+    }
+
+    /// Draws the 12 hour ticks, or only those overlapping `area`.
+    fn draw_ticks<D>(&self, target: &mut D, area: Option<&Rectangle>) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Rgb565>,
+    {
+        let outer = self.radius as i32;
+        for hour in 0..12 {
+            let tick = Line::new(self.point_at(hour * 5, outer * 88 / 100), self.point_at(hour * 5, outer));
+            let visible = match area {
+                Some(area) => !tick.bounding_box().intersection(area).is_zero_sized(),
+                None => true,
+            };
+            if visible {
+                tick.into_styled(PrimitiveStyle::with_stroke(self.style.ticks, 2)).draw(target)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Themed for ClockFace {
+    fn apply_theme(&mut self, theme: &Theme) {
+        self.style = ClockStyle {
+            background: theme.surface,
+            ticks: theme.text,
+            hour_hand: theme.text,
+            minute_hand: theme.text,
+            second_hand: theme.primary,
+        };
+        self.invalidate();
+    }
+}
@@ -0,0 +1,216 @@
+use embedded_graphics::{pixelcolor::Rgb565, prelude::*, primitives::Rectangle};
+
+use crate::st7789v2::image::Image;
+use crate::st7789v2::ui::surface::Surface;
+
+/// What the sprites are drawn over.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Background<'a> {
+    Color(Rgb565),
+    /// An image with its top left corner at the screen origin. Raw565 images decode
+    /// fastest, every recomposited region walks the image from its start.
+    Image(Image<'a>),
+}
+
+/// A movable image managed by a `Compositor`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Sprite<'a> {
+    pub image: Image<'a>,
+    pub position: Point,
+    pub visible: bool,
+    /// Higher z is drawn on top; equal z keeps the order the sprites were added in.
+    pub z: i8,
+    /// Pixels of this color are left out, showing what's below.
+    pub transparent: Option<Rgb565>,
+}
+
+impl<'a> Sprite<'a> {
+    pub const fn new(image: Image<'a>, position: Point) -> Self {
+        Self {
+            image,
+            position,
+            visible: true,
+            z: 0,
+            transparent: None,
+        }
+    }
+
+    pub fn bounding_box(&self) -> Rectangle {
+        Rectangle::new(self.position, self.image.size())
+    }
+}
+
+/// Up to N sprites over a background, redrawn incrementally.
+/// `update()` finds the sprites that changed since the last update, and for each one
+/// recomposites the area it left and the area it now covers (background, then every sprite
+/// overlapping it in z-order) in a scratch `Surface` and sends it in one transfer, so
+/// nothing flickers and untouched parts of the screen aren't resent.
+pub struct Compositor<'a, const N: usize> {
+    background: Background<'a>,
+    sprites: [Option<Sprite<'a>>; N],
+    /// Each sprite as last drawn.
+    drawn: [Option<Sprite<'a>>; N],
+    full_redraw: bool,
+}
+
+impl<'a, const N: usize> Compositor<'a, N> {
+    pub const fn new(background: Background<'a>) -> Self {
+        Self {
+            background,
+            sprites: [None; N],
+            drawn: [None; N],
+            full_redraw: true,
+        }
+    }
+
+    /// Adds a sprite, returning its handle, or `None` if all N slots are taken.
+    pub fn add(&mut self, sprite: Sprite<'a>) -> Option<usize> {
+        let slot = self.sprites.iter().position(Option::is_none)?;
+        self.sprites[slot] = Some(sprite);
+        Some(slot)
+    }
+
+    /// Removes a sprite; its area is restored on the next `update()`.
+    pub fn remove(&mut self, handle: usize) -> Option<Sprite<'a>> {
+        self.sprites.get_mut(handle)?.take()
+    }
+
+    pub fn sprite(&self, handle: usize) -> Option<&Sprite<'a>> {
+        self.sprites.get(handle)?.as_ref()
+    }
+
+    /// Gives access to a sprite to move it, swap its image, hide it or change its z.
+    pub fn sprite_mut(&mut self, handle: usize) -> Option<&mut Sprite<'a>> {
+        self.sprites.get_mut(handle)?.as_mut()
+    }
+
+    pub fn set_background(&mut self, background: Background<'a>) {
+        self.background = background;
+        self.full_redraw = true;
+    }
+
+    /// Forces the next `update()` to recomposite the whole screen.
+    pub fn invalidate(&mut self) {
+        self.full_redraw = true;
+    }
+
+    /// Redraws what changed since the last update, see `Compositor`.
+    /// Regions taller than the surface holds are sent in bands; the surface must hold at
+    /// least one row of the widest region (the screen width covers every case).
+    pub fn update<const P: usize, D>(&mut self, surface: &mut Surface<P>, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Rgb565>,
+    {
+        let screen = target.bounding_box();
+        let mut regions = [None; N];
+        let mut count = 0;
+
+        if self.full_redraw {
+            regions[0] = Some(screen);
+            count = 1;
+        } else {
+            for (current, drawn) in self.sprites.iter().zip(&self.drawn) {
+                if current == drawn {
+                    continue;
+                }
+                let old = drawn.filter(|s| s.visible).map(|s| s.bounding_box());
+                let new = current.filter(|s| s.visible).map(|s| s.bounding_box());
+                let region = match (old, new) {
+                    (Some(old), Some(new)) => envelope(old, new),
+                    (Some(area), None) | (None, Some(area)) => area,
+                    (None, None) => continue,
+                };
+                merge_region(&mut regions, &mut count, region.intersection(&screen));
+            }
+        }
+
+        for region in regions[..count].iter().flatten() {
+            self.composite(*region, surface, target)?;
+        }
+
+        self.drawn = self.sprites;
+        self.full_redraw = false;
+        Ok(())
+    }
+
+    fn composite<const P: usize, D>(&self, region: Rectangle, surface: &mut Surface<P>, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Rgb565>,
+    {
+        if region.is_zero_sized() {
+            return Ok(());
+        }
+
+        // sprites in drawing order
+        let mut order = [0usize; N];
+        for (i, slot) in order.iter_mut().enumerate() {
+            *slot = i;
+        }
+        order.sort_unstable_by_key(|&i| (self.sprites[i].map_or(i8::MIN, |s| s.z), i));
+
+        let band_rows = (P as u32 / region.size.width).max(1);
+        let mut top = 0;
+        while top < region.size.height {
+            let rows = band_rows.min(region.size.height - top);
+            let band = Rectangle::new(region.top_left + Point::new(0, top as i32), Size::new(region.size.width, rows));
+            top += rows;
+
+            let fill = match self.background {
+                Background::Color(color) => color,
+                Background::Image(_) => Rgb565::BLACK,
+            };
+            if !surface.reshape(band, fill) {
+                // the surface can't hold a single row, fall back to clearing the region
+                return target.fill_solid(&region, fill);
+            }
+            if let Background::Image(image) = self.background {
+                image.draw_at(surface, 0, 0).ok();
+            }
+
+            for sprite in order.iter().filter_map(|&i| self.sprites[i]) {
+                if !sprite.visible || sprite.bounding_box().intersection(&band).is_zero_sized() {
+                    continue;
+                }
+                let pixels = sprite
+                    .bounding_box()
+                    .points()
+                    .zip(sprite.image.pixels())
+                    .filter(|(_, color)| Some(*color) != sprite.transparent)
+                    .map(|(point, color)| Pixel(point, color));
+                surface.draw_iter(pixels).ok();
+            }
+
+            surface.blit(target)?;
+        }
+        Ok(())
+    }
+}
+
+/// Adds `region` to the list, merging it with any region it overlaps.
+fn merge_region<const N: usize>(regions: &mut [Option<Rectangle>; N], count: &mut usize, mut region: Rectangle) {
+    if region.is_zero_sized() {
+        return;
+    }
+    let mut i = 0;
+    while i < *count {
+        let other = regions[i].unwrap();
+        if other.intersection(&region).is_zero_sized() {
+            i += 1;
+            continue;
+        }
+        // absorb it and start over, the grown region may overlap earlier ones
+        region = envelope(region, other);
+        *count -= 1;
+        regions[i] = regions[*count].take();
+        i = 0;
+    }
+    regions[*count] = Some(region);
+    *count += 1;
+}
+
+/// Returns the smallest rectangle containing both `a` and `b`.
+fn envelope(a: Rectangle, b: Rectangle) -> Rectangle {
+    let top_left = a.top_left.component_min(b.top_left);
+    let bottom_right = a.bottom_right().unwrap_or(a.top_left).component_max(b.bottom_right().unwrap_or(b.top_left));
+    Rectangle::with_corners(top_left, bottom_right)
+}
@@ -0,0 +1,242 @@
+use embedded_graphics::{pixelcolor::Rgb565, prelude::*, primitives::Rectangle};
+
+use crate::st7789v2::ui::{
+    glyph::GlyphSource,
+    theme::{Theme, Themed},
+};
+
+/// Incremental UTF-8 decoder for byte streams (e.g. a UART feeding the console).
+/// Invalid or truncated sequences decode to U+FFFD, one per maximal invalid part as in the
+/// Unicode standard: a byte that cuts a sequence short ends it with U+FFFD and then starts
+/// over as a lead byte, so `[0xC3, b'A']` gives U+FFFD then 'A'. Overlong forms, surrogates
+/// and code points past U+10FFFF are rejected at their second byte.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Utf8Decoder {
+    code_point: u32,
+    needed: u8,
+    /// Range the next continuation byte must be in.
+    lower: u8,
+    upper: u8,
+}
+
+impl Utf8Decoder {
+    pub const fn new() -> Self {
+        Self { code_point: 0, needed: 0, lower: 0x80, upper: 0xBF }
+    }
+
+    /// Feeds one byte.
+    /// # Returns
+    /// The characters it completes: none, one, or U+FFFD for a cut-short sequence followed
+    /// by the character this byte is on its own.
+    pub fn push(&mut self, byte: u8) -> impl Iterator<Item = char> + use<> {
+        let mut replaced = None;
+        if self.needed > 0 {
+            if (self.lower..=self.upper).contains(&byte) {
+                self.code_point = (self.code_point << 6) | (byte & 0x3F) as u32;
+                self.needed -= 1;
+                (self.lower, self.upper) = (0x80, 0xBF);
+                let done = (self.needed == 0)
+                    .then(|| char::from_u32(self.code_point).unwrap_or(char::REPLACEMENT_CHARACTER));
+                return done.into_iter().chain(None);
+            }
+            self.needed = 0;
+            replaced = Some(char::REPLACEMENT_CHARACTER);
+        }
+
+        replaced.into_iter().chain(self.lead(byte))
+    }
+
+    /// Starts a sequence with `byte`.
+    /// # Returns
+    /// The character if `byte` is one on its own: ASCII, or U+FFFD if it can't start a sequence.
+    fn lead(&mut self, byte: u8) -> Option<char> {
+        // the second byte's range rules out overlong forms, surrogates and values past U+10FFFF
+        let (code_point, needed, lower, upper) = match byte {
+            0x00..=0x7F => return Some(byte as char),
+            0xC2..=0xDF => (byte & 0x1F, 1, 0x80, 0xBF),
+            0xE0 => (byte & 0x0F, 2, 0xA0, 0xBF),
+            0xE1..=0xEC | 0xEE..=0xEF => (byte & 0x0F, 2, 0x80, 0xBF),
+            0xED => (byte & 0x0F, 2, 0x80, 0x9F),
+            0xF0 => (byte & 0x07, 3, 0x90, 0xBF),
+            0xF1..=0xF3 => (byte & 0x07, 3, 0x80, 0xBF),
+            0xF4 => (byte & 0x07, 3, 0x80, 0x8F),
+            _ => return Some(char::REPLACEMENT_CHARACTER),
+        };
+        self.code_point = code_point as u32;
+        self.needed = needed;
+        (self.lower, self.upper) = (lower, upper);
+        None
+    }
+}
+
+/// Scrolling text console of COLS x ROWS character cells.
+/// Glyphs come from a `GlyphSource`, so any font (or chain of fonts) can be used; characters
+/// no source can draw are shown as the replacement character, or left blank if that is
+/// missing too. Each character is drawn as one cell-sized window as it is written.
+pub struct Console<G, const COLS: usize, const ROWS: usize> {
+    glyphs: G,
+    origin: Point,
+    fg: Rgb565,
+    bg: Rgb565,
+    replacement: char,
+    cells: [[char; COLS]; ROWS],
+    col: usize,
+    row: usize,
+    decoder: Utf8Decoder,
+    dirty: bool,
+}
+
+impl<G: GlyphSource, const COLS: usize, const ROWS: usize> Console<G, COLS, ROWS> {
+    pub fn new(glyphs: G, origin: Point, fg: Rgb565, bg: Rgb565) -> Self {
+        Self {
+            glyphs,
+            origin,
+            fg,
+            bg,
+            replacement: '?',
+            cells: [[' '; COLS]; ROWS],
+            col: 0,
+            row: 0,
+            decoder: Utf8Decoder::new(),
+            dirty: true,
+        }
+    }
+
+    /// Sets the character drawn in place of glyphs no source has (default '?').
+    pub fn set_replacement(&mut self, replacement: char) {
+        self.replacement = replacement;
+    }
+
+    /// Changes the colors; call `redraw()` to repaint the existing text.
+    pub fn set_colors(&mut self, fg: Rgb565, bg: Rgb565) {
+        self.fg = fg;
+        self.bg = bg;
+        self.dirty = true;
+    }
+
+    /// Returns true if the colors changed since the last full `redraw()`.
+    pub fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    pub fn glyphs(&self) -> &G {
+        &self.glyphs
+    }
+
+    /// Area covered by the console.
+    pub fn bounding_box(&self) -> Rectangle {
+        let cell = self.glyphs.cell_size();
+        Rectangle::new(self.origin, Size::new(cell.width * COLS as u32, cell.height * ROWS as u32))
+    }
+
+    /// Clears the console and moves the cursor home.
+    pub fn clear<D>(&mut self, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Rgb565>,
+    {
+        self.cells = [[' '; COLS]; ROWS];
+        self.col = 0;
+        self.row = 0;
+        target.fill_solid(&self.bounding_box(), self.bg)
+    }
+
+    /// Prints a string, wrapping at the right edge and scrolling at the bottom.
+    pub fn print<D>(&mut self, target: &mut D, text: &str) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Rgb565>,
+    {
+        for c in text.chars() {
+            self.put_char(target, c)?;
+        }
+        Ok(())
+    }
+
+    /// Prints raw UTF-8 bytes; sequences split across calls are reassembled.
+    pub fn print_bytes<D>(&mut self, target: &mut D, bytes: &[u8]) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Rgb565>,
+    {
+        for &byte in bytes {
+            for c in self.decoder.push(byte) {
+                self.put_char(target, c)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Prints one character, handling `\n` and `\r`.
+    pub fn put_char<D>(&mut self, target: &mut D, c: char) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Rgb565>,
+    {
+        match c {
+            '\n' => return self.new_line(target),
+            '\r' => {
+                self.col = 0;
+                return Ok(());
+            }
+            _ => {}
+        }
+
+        if self.col == COLS {
+            self.new_line(target)?;
+        }
+
+        self.cells[self.row][self.col] = c;
+        self.draw_cell(target, self.row, self.col)?;
+        self.col += 1;
+        Ok(())
+    }
+
+    /// Redraws every cell, e.g. after the area was drawn over.
+    pub fn redraw<D>(&mut self, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Rgb565>,
+    {
+        for row in 0..ROWS {
+            for col in 0..COLS {
+                self.draw_cell(target, row, col)?;
+            }
+        }
+        self.dirty = false;
+        Ok(())
+    }
+
+    fn new_line<D>(&mut self, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Rgb565>,
+    {
+        self.col = 0;
+        if self.row + 1 < ROWS {
+            self.row += 1;
+            return Ok(());
+        }
+
+        self.cells.copy_within(1.., 0);
+        self.cells[ROWS - 1] = [' '; COLS];
+        self.redraw(target)
+    }
+
+    fn draw_cell<D>(&self, target: &mut D, row: usize, col: usize) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Rgb565>,
+    {
+        let cell = self.glyphs.cell_size();
+        let top_left = self.origin + Point::new((cell.width * col as u32) as i32, (cell.height * row as u32) as i32);
+
+        let c = self.cells[row][col];
+        let c = if self.glyphs.has_glyph(c) { c } else { self.replacement };
+
+        if self.glyphs.has_glyph(c) {
+            self.glyphs.draw_glyph(c, top_left, self.fg, self.bg, target)
+        } else {
+            target.fill_solid(&Rectangle::new(top_left, cell), self.bg)
+        }
+    }
+}
+
+impl<G: GlyphSource, const COLS: usize, const ROWS: usize> Themed for Console<G, COLS, ROWS> {
+    fn apply_theme(&mut self, theme: &Theme) {
+        self.set_colors(theme.text, theme.background);
+    }
+}
@@ -0,0 +1,97 @@
+use crate::st7789v2::{image::Image, ui::surface::Surface};
+use embedded_graphics::{pixelcolor::Rgb565, prelude::*, primitives::Rectangle};
+
+/// Largest cursor image, in pixels: 16x16.
+pub const CURSOR_PIXELS: usize = 16 * 16;
+
+/// Software pointer for encoder or touch driven UIs. The pixels under the cursor are saved
+/// before it is drawn and put back when it moves, so the screen below is never redrawn and
+/// every move costs two windows of at most 16x16 pixels.
+/// Like `Overlay`, the cursor can't read the screen: `show()`/`move_to()` take a `save`
+/// function that fills a buffer with the current pixels of an area, e.g.
+/// `|display, area, out| { display.read_pixels(area, out); }` with SDO wired, or copying
+/// from `Framebuffer::region()`.
+pub struct Cursor<'a> {
+    image: Image<'a>,
+    hotspot: Point,
+    transparent: Option<Rgb565>,
+    position: Point,
+    saved: Surface<CURSOR_PIXELS>,
+    composed: Surface<CURSOR_PIXELS>,
+    shown: bool,
+}
+
+impl<'a> Cursor<'a> {
+    /// Creates a hidden cursor. `hotspot` is the point of `image` that sits at the position,
+    /// pixels of color `transparent` are left out. Images over 16x16 are cut to their top
+    /// left 16x16 pixels.
+    pub fn new(image: Image<'a>, hotspot: Point, transparent: Option<Rgb565>) -> Self {
+        Self {
+            image,
+            hotspot,
+            transparent,
+            position: Point::zero(),
+            saved: Surface::new(),
+            composed: Surface::new(),
+            shown: false,
+        }
+    }
+
+    pub fn position(&self) -> Point {
+        self.position
+    }
+
+    pub fn is_shown(&self) -> bool {
+        self.shown
+    }
+
+    /// Draws the cursor at the current position, saving what it covers.
+    pub fn show<D, S>(&mut self, target: &mut D, save: S) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Rgb565>,
+        S: FnOnce(&mut D, &Rectangle, &mut [Rgb565]),
+    {
+        self.move_to(self.position, target, save)
+    }
+
+    /// Restores what was under the cursor and hides it.
+    pub fn hide<D>(&mut self, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Rgb565>,
+    {
+        if !core::mem::take(&mut self.shown) {
+            return Ok(());
+        }
+        self.saved.blit(target)
+    }
+
+    /// Moves the cursor to `position` and shows it there, restoring the old spot first.
+    pub fn move_to<D, S>(&mut self, position: Point, target: &mut D, save: S) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Rgb565>,
+        S: FnOnce(&mut D, &Rectangle, &mut [Rgb565]),
+    {
+        self.hide(target)?;
+        self.position = position;
+
+        let max = Size::new(16, 16).component_min(self.image.size());
+        let sprite = Rectangle::new(position - self.hotspot, self.image.size());
+        let area = Rectangle::new(sprite.top_left, max).intersection(&target.bounding_box());
+        if area.is_zero_sized() || !self.saved.reshape(area, Rgb565::BLACK) {
+            return Ok(());
+        }
+        save(target, &area, self.saved.pixels_mut());
+        self.shown = true;
+
+        self.composed.reshape(area, Rgb565::BLACK);
+        self.composed.fill_contiguous(&area, self.saved.pixels().iter().copied()).ok();
+        let transparent = self.transparent;
+        let pixels = sprite
+            .points()
+            .zip(self.image.pixels())
+            .filter(|(point, color)| area.contains(*point) && Some(*color) != transparent)
+            .map(|(point, color)| Pixel(point, color));
+        self.composed.draw_iter(pixels).ok();
+        self.composed.blit(target)
+    }
+}
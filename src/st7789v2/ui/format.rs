@@ -0,0 +1,164 @@
+use core::fmt;
+
+#[cfg(feature = "fast-numbers")]
+use crate::st7789v2::ui::glyph::GlyphSource;
+#[cfg(feature = "fast-numbers")]
+use embedded_graphics::{pixelcolor::Rgb565, prelude::*, primitives::Rectangle};
+
+/// Longest text a `NumStr` holds: a sign, 20 digits of u64 and a decimal point.
+const NUM_STR_LEN: usize = 22;
+
+/// Most decimals `NumStr::from_f32()` writes.
+pub const MAX_DECIMALS: u8 = 6;
+
+/// A number converted to text on the stack with plain integer math, without going through
+/// `core::fmt`'s formatting machinery, which costs several KB of flash and a lot of cycles
+/// per float. Derefs to `&str` and implements `Display`, so it drops into `Label`, `Console`
+/// or a `heapless::String`.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct NumStr {
+    buf: [u8; NUM_STR_LEN],
+    start: u8,
+}
+
+impl NumStr {
+    pub fn from_u32(value: u32) -> Self {
+        Self::from_u64(value as u64)
+    }
+
+    pub fn from_i32(value: i32) -> Self {
+        let mut num = Self::from_u64(value.unsigned_abs() as u64);
+        if value < 0 {
+            num.push_front(b'-');
+        }
+        num
+    }
+
+    pub fn from_u64(mut value: u64) -> Self {
+        let mut num = Self {
+            buf: [0; NUM_STR_LEN],
+            start: NUM_STR_LEN as u8,
+        };
+        loop {
+            num.push_front(b'0' + (value % 10) as u8);
+            value /= 10;
+            if value == 0 {
+                return num;
+            }
+        }
+    }
+
+    /// Formats `value` with `decimals` digits after the point (at most `MAX_DECIMALS`),
+    /// rounded half away from zero. Values past the u32 range saturate, NaN gives "NaN".
+    pub fn from_f32(value: f32, decimals: u8) -> Self {
+        if value.is_nan() {
+            return Self::from_ascii(b"NaN");
+        }
+        let decimals = decimals.min(MAX_DECIMALS) as u32;
+        let scale = 10u64.pow(decimals);
+        // integer and fraction apart, so the math stays in single precision (the FPU's)
+        let magnitude = value.abs();
+        let mut integer = magnitude as u32 as u64;
+        let mut fraction = ((magnitude - integer as f32) * scale as f32 + 0.5) as u64;
+        if fraction >= scale {
+            integer += 1;
+            fraction -= scale;
+        }
+
+        let negative = value.is_sign_negative() && (integer != 0 || fraction != 0);
+
+        let mut num = Self::from_u64(integer);
+        if decimals > 0 {
+            // append the fraction, zero-padded to `decimals` digits
+            let end = NUM_STR_LEN;
+            num.buf.copy_within(num.start as usize..end, num.start as usize - decimals as usize - 1);
+            num.start -= decimals as u8 + 1;
+            for i in (end - decimals as usize..end).rev() {
+                num.buf[i] = b'0' + (fraction % 10) as u8;
+                fraction /= 10;
+            }
+            num.buf[end - decimals as usize - 1] = b'.';
+        }
+        if negative {
+            num.push_front(b'-');
+        }
+        num
+    }
+
+    pub fn as_str(&self) -> &str {
+        // only ASCII digits, '-', '.' and "NaN" are ever written
+        core::str::from_utf8(&self.buf[self.start as usize..]).unwrap_or("")
+    }
+
+    fn from_ascii(text: &[u8]) -> Self {
+        let mut num = Self {
+            buf: [0; NUM_STR_LEN],
+            start: (NUM_STR_LEN - text.len()) as u8,
+        };
+        num.buf[num.start as usize..].copy_from_slice(text);
+        num
+    }
+
+    fn push_front(&mut self, byte: u8) {
+        self.start -= 1;
+        self.buf[self.start as usize] = byte;
+    }
+}
+
+impl core::ops::Deref for NumStr {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl fmt::Display for NumStr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl fmt::Debug for NumStr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Appends `text` to `out`. Returns false, leaving `out` unchanged, if it doesn't fit.
+#[cfg(feature = "heapless")]
+pub fn push<const N: usize>(out: &mut heapless::String<N>, text: &str) -> bool {
+    out.push_str(text).is_ok()
+}
+
+/// Appends `value` to `out`, see `push()`.
+#[cfg(feature = "heapless")]
+pub fn push_i32<const N: usize>(out: &mut heapless::String<N>, value: i32) -> bool {
+    push(out, &NumStr::from_i32(value))
+}
+
+/// Appends `value` with `decimals` digits after the point to `out`, see `push()`.
+#[cfg(feature = "heapless")]
+pub fn push_f32<const N: usize>(out: &mut heapless::String<N>, value: f32, decimals: u8) -> bool {
+    push(out, &NumStr::from_f32(value, decimals))
+}
+
+/// Draws `num` cell by cell from `origin`, straight from the digits to the glyphs: no
+/// `Write` adapter and no per-character `has_glyph()` fallback, for readouts updated every
+/// frame. Characters the source can't draw come out however `draw_glyph()` handles them.
+/// # Returns
+/// The area covered.
+#[cfg(feature = "fast-numbers")]
+pub fn draw_number<G, D>(glyphs: &G, num: &NumStr, origin: Point, fg: Rgb565, bg: Rgb565, target: &mut D) -> Result<Rectangle, D::Error>
+where
+    G: GlyphSource,
+    D: DrawTarget<Color = Rgb565>,
+{
+    let cell = glyphs.cell_size();
+    let mut top_left = origin;
+    for &byte in num.as_bytes() {
+        glyphs.draw_glyph(byte as char, top_left, fg, bg, target)?;
+        top_left.x += cell.width as i32;
+    }
+    Ok(Rectangle::new(origin, Size::new(cell.width * num.len() as u32, cell.height)))
+}
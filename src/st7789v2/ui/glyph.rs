@@ -0,0 +1,217 @@
+use crate::st7789v2::color::blend;
+use embedded_graphics::{
+    mono_font::{MonoFont, MonoTextStyleBuilder},
+    pixelcolor::Rgb565,
+    prelude::*,
+    primitives::Rectangle,
+    text::{Baseline, Text},
+};
+
+/// Pluggable glyph lookup for the text modules.
+/// Implement it for your own font tables (e.g. a CJK subset in flash) and chain sources
+/// with `Fallback` so characters missing from one font come from the next.
+pub trait GlyphSource {
+    /// Size of one character cell.
+    fn cell_size(&self) -> Size;
+
+    /// Returns true if this source can draw `c`.
+    fn has_glyph(&self, c: char) -> bool;
+
+    /// Draws `c` into the cell at `top_left`, filling the whole cell (background included).
+    fn draw_glyph<D>(&self, c: char, top_left: Point, fg: Rgb565, bg: Rgb565, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Rgb565>;
+}
+
+/// An embedded-graphics `MonoFont` covering the characters `first..=last`.
+#[derive(Clone, Copy)]
+pub struct MonoGlyphs<'a> {
+    font: &'a MonoFont<'a>,
+    first: char,
+    last: char,
+}
+
+impl<'a> MonoGlyphs<'a> {
+    pub const fn new(font: &'a MonoFont<'a>, first: char, last: char) -> Self {
+        Self { font, first, last }
+    }
+
+    /// A font covering printable ASCII, like the fonts in `embedded_graphics::mono_font::ascii`.
+    pub const fn ascii(font: &'a MonoFont<'a>) -> Self {
+        Self::new(font, ' ', '~')
+    }
+}
+
+impl GlyphSource for MonoGlyphs<'_> {
+    fn cell_size(&self) -> Size {
+        self.font.character_size
+    }
+
+    fn has_glyph(&self, c: char) -> bool {
+        (self.first..=self.last).contains(&c)
+    }
+
+    fn draw_glyph<D>(&self, c: char, top_left: Point, fg: Rgb565, bg: Rgb565, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Rgb565>,
+    {
+        // with a background color set, the glyph is drawn as one contiguous window
+        let style = MonoTextStyleBuilder::new()
+            .font(self.font)
+            .text_color(fg)
+            .background_color(bg)
+            .build();
+        let mut utf8 = [0u8; 4];
+        Text::with_baseline(c.encode_utf8(&mut utf8), top_left, style, Baseline::Top).draw(target)?;
+        Ok(())
+    }
+}
+
+/// A user-supplied table of 1 bpp glyphs, e.g. a CJK subset stored in flash.
+/// `chars` must be sorted; `bitmaps` holds one glyph per char, rows padded to whole bytes,
+/// MSB first.
+#[derive(Clone, Copy)]
+pub struct BitmapGlyphs<'a> {
+    size: Size,
+    chars: &'a [char],
+    bitmaps: &'a [u8],
+}
+
+impl<'a> BitmapGlyphs<'a> {
+    pub const fn new(size: Size, chars: &'a [char], bitmaps: &'a [u8]) -> Self {
+        Self { size, chars, bitmaps }
+    }
+
+    fn bytes_per_glyph(&self) -> usize {
+        self.size.width.div_ceil(8) as usize * self.size.height as usize
+    }
+
+    fn bitmap(&self, c: char) -> Option<&'a [u8]> {
+        let index = self.chars.binary_search(&c).ok()?;
+        let len = self.bytes_per_glyph();
+        self.bitmaps.get(index * len..(index + 1) * len)
+    }
+}
+
+impl GlyphSource for BitmapGlyphs<'_> {
+    fn cell_size(&self) -> Size {
+        self.size
+    }
+
+    fn has_glyph(&self, c: char) -> bool {
+        self.bitmap(c).is_some()
+    }
+
+    fn draw_glyph<D>(&self, c: char, top_left: Point, fg: Rgb565, bg: Rgb565, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Rgb565>,
+    {
+        let area = Rectangle::new(top_left, self.size);
+        let Some(bitmap) = self.bitmap(c) else {
+            return target.fill_solid(&area, bg);
+        };
+
+        let stride = self.size.width.div_ceil(8) as usize;
+        let width = self.size.width as usize;
+        let pixels = (0..self.size.height as usize).flat_map(move |y| {
+            (0..width).map(move |x| {
+                let on = bitmap[y * stride + x / 8] & (0x80 >> (x % 8)) != 0;
+                if on { fg } else { bg }
+            })
+        });
+        target.fill_contiguous(&area, pixels)
+    }
+}
+
+/// A user-supplied table of 4 bpp alpha-coverage glyphs for anti-aliased text.
+/// `chars` must be sorted; `bitmaps` holds one glyph per char, two pixels per byte
+/// (high nibble first), rows padded to whole bytes. Coverage is blended against the solid
+/// background color as the pixels are packed, so smooth text needs no framebuffer.
+#[derive(Clone, Copy)]
+pub struct AlphaGlyphs<'a> {
+    size: Size,
+    chars: &'a [char],
+    bitmaps: &'a [u8],
+}
+
+impl<'a> AlphaGlyphs<'a> {
+    pub const fn new(size: Size, chars: &'a [char], bitmaps: &'a [u8]) -> Self {
+        Self { size, chars, bitmaps }
+    }
+
+    fn bytes_per_glyph(&self) -> usize {
+        self.size.width.div_ceil(2) as usize * self.size.height as usize
+    }
+
+    fn bitmap(&self, c: char) -> Option<&'a [u8]> {
+        let index = self.chars.binary_search(&c).ok()?;
+        let len = self.bytes_per_glyph();
+        self.bitmaps.get(index * len..(index + 1) * len)
+    }
+}
+
+impl GlyphSource for AlphaGlyphs<'_> {
+    fn cell_size(&self) -> Size {
+        self.size
+    }
+
+    fn has_glyph(&self, c: char) -> bool {
+        self.bitmap(c).is_some()
+    }
+
+    fn draw_glyph<D>(&self, c: char, top_left: Point, fg: Rgb565, bg: Rgb565, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Rgb565>,
+    {
+        let area = Rectangle::new(top_left, self.size);
+        let Some(bitmap) = self.bitmap(c) else {
+            return target.fill_solid(&area, bg);
+        };
+
+        // the 16 possible blends, so packing is a table lookup per pixel
+        let mut shades = [bg; 16];
+        for (alpha, shade) in shades.iter_mut().enumerate() {
+            *shade = blend(fg, bg, alpha as u8 * 17);
+        }
+
+        let stride = self.size.width.div_ceil(2) as usize;
+        let width = self.size.width as usize;
+        let pixels = (0..self.size.height as usize).flat_map(move |y| {
+            (0..width).map(move |x| {
+                let byte = bitmap[y * stride + x / 2];
+                let alpha = if x % 2 == 0 { byte >> 4 } else { byte & 0x0F };
+                shades[alpha as usize]
+            })
+        });
+        target.fill_contiguous(&area, pixels)
+    }
+}
+
+/// Tries `primary` first and falls back to `secondary` for characters it lacks.
+#[derive(Clone, Copy)]
+pub struct Fallback<A, B> {
+    pub primary: A,
+    pub secondary: B,
+}
+
+impl<A: GlyphSource, B: GlyphSource> GlyphSource for Fallback<A, B> {
+    fn cell_size(&self) -> Size {
+        let (a, b) = (self.primary.cell_size(), self.secondary.cell_size());
+        Size::new(a.width.max(b.width), a.height.max(b.height))
+    }
+
+    fn has_glyph(&self, c: char) -> bool {
+        self.primary.has_glyph(c) || self.secondary.has_glyph(c)
+    }
+
+    fn draw_glyph<D>(&self, c: char, top_left: Point, fg: Rgb565, bg: Rgb565, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Rgb565>,
+    {
+        if self.primary.has_glyph(c) {
+            self.primary.draw_glyph(c, top_left, fg, bg, target)
+        } else {
+            self.secondary.draw_glyph(c, top_left, fg, bg, target)
+        }
+    }
+}
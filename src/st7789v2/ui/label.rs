@@ -0,0 +1,187 @@
+use core::fmt::{self, Display, Write};
+
+use embedded_graphics::{pixelcolor::Rgb565, prelude::*, primitives::Rectangle};
+
+use crate::st7789v2::ui::glyph::GlyphSource;
+use crate::st7789v2::ui::surface::Surface;
+use crate::st7789v2::ui::theme::{Theme, Themed};
+
+/// A formatted value at a fixed position.
+/// Remembers the area its last value covered and clears exactly that area before drawing the
+/// new one, so callers don't need to track and blank the old text themselves.
+pub struct Label<G, T> {
+    glyphs: G,
+    origin: Point,
+    value: T,
+    fg: Rgb565,
+    bg: Rgb565,
+    /// Area covered by the value as last drawn.
+    drawn: Option<Rectangle>,
+    dirty: bool,
+}
+
+impl<G: GlyphSource, T: Display> Label<G, T> {
+    pub fn new(glyphs: G, origin: Point, value: T, fg: Rgb565, bg: Rgb565) -> Self {
+        Self {
+            glyphs,
+            origin,
+            value,
+            fg,
+            bg,
+            drawn: None,
+            dirty: true,
+        }
+    }
+
+    /// Sets the value shown by the next `render()`.
+    pub fn set(&mut self, value: T) {
+        self.value = value;
+        self.dirty = true;
+    }
+
+    pub fn value(&self) -> &T {
+        &self.value
+    }
+
+    pub fn set_colors(&mut self, fg: Rgb565, bg: Rgb565) {
+        self.fg = fg;
+        self.bg = bg;
+        self.dirty = true;
+    }
+
+    pub fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    /// Returns the area covered by the value as last drawn.
+    pub fn bounding_box(&self) -> Rectangle {
+        self.drawn.unwrap_or(Rectangle::new(self.origin, Size::zero()))
+    }
+
+    /// Clears the previous value's area and draws the current value, if it changed.
+    pub fn render<D>(&mut self, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Rgb565>,
+    {
+        if !self.dirty {
+            return Ok(());
+        }
+
+        if let Some(area) = self.drawn.take() {
+            target.fill_solid(&area, self.bg)?;
+        }
+
+        let (cols, error) = self.draw_value(target);
+        self.drawn = Some(self.area_for(cols));
+        if let Some(e) = error {
+            return Err(e);
+        }
+
+        self.dirty = false;
+        Ok(())
+    }
+
+    /// Like `render()`, but composes the erase and the new value in `surface` and sends both
+    /// in one windowed transfer, so the old digits are never visibly blanked.
+    /// Falls back to `render()` if the old and new areas together don't fit the surface.
+    pub fn render_composed<const N: usize, D>(&mut self, surface: &mut Surface<N>, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Rgb565>,
+    {
+        if !self.dirty {
+            return Ok(());
+        }
+
+        let mut counter = CharCount(0);
+        let _ = write!(counter, "{}", self.value);
+        let new = self.area_for(counter.0);
+        let old = self.bounding_box();
+        let area = Rectangle::new(self.origin, Size::new(new.size.width.max(old.size.width), new.size.height));
+        if !surface.reshape(area, self.bg) {
+            return self.render(target);
+        }
+
+        // drawing into the surface can't fail
+        let _ = self.draw_value(surface);
+        surface.blit(target)?;
+
+        self.drawn = Some(new);
+        self.dirty = false;
+        Ok(())
+    }
+
+    fn area_for(&self, cols: u32) -> Rectangle {
+        let cell = self.glyphs.cell_size();
+        Rectangle::new(self.origin, Size::new(cell.width * cols, cell.height))
+    }
+
+    /// Draws the value, returning the number of cells written and the first draw error.
+    fn draw_value<D>(&self, target: &mut D) -> (u32, Option<D::Error>)
+    where
+        D: DrawTarget<Color = Rgb565>,
+    {
+        let mut writer = GlyphWriter {
+            glyphs: &self.glyphs,
+            origin: self.origin,
+            fg: self.fg,
+            bg: self.bg,
+            target,
+            col: 0,
+            error: None,
+        };
+        let _ = write!(writer, "{}", self.value);
+        (writer.col, writer.error)
+    }
+}
+
+impl<G: GlyphSource, T: Display> Themed for Label<G, T> {
+    fn apply_theme(&mut self, theme: &Theme) {
+        self.set_colors(theme.text, theme.background);
+    }
+}
+
+/// Counts the characters of formatted text.
+struct CharCount(u32);
+
+impl Write for CharCount {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.0 += s.chars().count() as u32;
+        Ok(())
+    }
+}
+
+/// Draws formatted text cell by cell; characters without a glyph are left blank.
+struct GlyphWriter<'w, G, D: DrawTarget> {
+    glyphs: &'w G,
+    origin: Point,
+    fg: Rgb565,
+    bg: Rgb565,
+    target: &'w mut D,
+    col: u32,
+    error: Option<D::Error>,
+}
+
+impl<G, D> Write for GlyphWriter<'_, G, D>
+where
+    G: GlyphSource,
+    D: DrawTarget<Color = Rgb565>,
+{
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let cell = self.glyphs.cell_size();
+        for c in s.chars() {
+            let top_left = self.origin + Point::new((cell.width * self.col) as i32, 0);
+            let drawn = if self.glyphs.has_glyph(c) {
+                self.glyphs.draw_glyph(c, top_left, self.fg, self.bg, self.target)
+            } else {
+                self.target.fill_solid(&Rectangle::new(top_left, cell), self.bg)
+            };
+            self.col += 1;
+
+            if let Err(e) = drawn {
+                self.error = Some(e);
+                return Err(fmt::Error);
+            }
+        }
+        Ok(())
+    }
+}
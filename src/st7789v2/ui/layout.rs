@@ -0,0 +1,115 @@
+use embedded_graphics::{pixelcolor::Rgb565, prelude::*};
+
+use crate::st7789v2::ui::glyph::GlyphSource;
+
+/// One laid out line: the text slice, plus whether a hyphen follows it because a long token
+/// was split.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WrappedLine<'a> {
+    pub text: &'a str,
+    pub hyphen: bool,
+}
+
+/// Greedy word-wrapping line breaker for monospaced text.
+/// Breaks at spaces and `\n`; tokens longer than a line are split, with a trailing hyphen
+/// when hyphenation is on.
+pub struct WrapLines<'a> {
+    rest: &'a str,
+    max_chars: usize,
+    hyphenate: bool,
+}
+
+impl<'a> WrapLines<'a> {
+    pub fn new(text: &'a str, max_chars: usize, hyphenate: bool) -> Self {
+        Self {
+            rest: text,
+            max_chars: max_chars.max(1),
+            hyphenate: hyphenate && max_chars >= 2,
+        }
+    }
+}
+
+/// Byte index of the `n`th char of `s`, or `s.len()` if it is shorter.
+fn byte_index(s: &str, n: usize) -> usize {
+    s.char_indices().nth(n).map(|(i, _)| i).unwrap_or(s.len())
+}
+
+impl<'a> Iterator for WrapLines<'a> {
+    type Item = WrappedLine<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.rest.is_empty() {
+            return None;
+        }
+
+        // the candidate line plus one char, to see whether a space follows it
+        let window = &self.rest[..byte_index(self.rest, self.max_chars + 1)];
+
+        if let Some(newline) = window.find('\n') {
+            let line = &self.rest[..newline];
+            self.rest = &self.rest[newline + 1..];
+            return Some(WrappedLine { text: line, hyphen: false });
+        }
+
+        let fits = byte_index(self.rest, self.max_chars);
+        if fits == self.rest.len() {
+            let line = self.rest;
+            self.rest = "";
+            return Some(WrappedLine { text: line, hyphen: false });
+        }
+
+        if let Some(space) = window.rfind(' ') {
+            let line = self.rest[..space].trim_end_matches(' ');
+            self.rest = self.rest[space + 1..].trim_start_matches(' ');
+            return Some(WrappedLine { text: line, hyphen: false });
+        }
+
+        // a single token longer than the line
+        let split = if self.hyphenate { byte_index(self.rest, self.max_chars - 1) } else { fits };
+        let line = &self.rest[..split];
+        self.rest = &self.rest[split..];
+        Some(WrappedLine { text: line, hyphen: self.hyphenate })
+    }
+}
+
+/// Lays out `text` within `width` pixels starting at `top_left` and draws it glyph by glyph
+/// in `colors` (foreground, background).
+/// # Returns
+/// The height in pixels consumed, for stacking the next UI element below.
+pub fn draw_wrapped<G, D>(
+    glyphs: &G,
+    text: &str,
+    top_left: Point,
+    width: u32,
+    hyphenate: bool,
+    colors: (Rgb565, Rgb565),
+    target: &mut D,
+) -> Result<u32, D::Error>
+where
+    G: GlyphSource,
+    D: DrawTarget<Color = Rgb565>,
+{
+    let (fg, bg) = colors;
+    let cell = glyphs.cell_size();
+    let max_chars = (width / cell.width.max(1)) as usize;
+    let mut y = top_left.y;
+
+    for line in WrapLines::new(text, max_chars, hyphenate) {
+        let mut x = top_left.x;
+        let hyphen = if line.hyphen { Some('-') } else { None };
+        for c in line.text.chars().chain(hyphen) {
+            glyphs.draw_glyph(c, Point::new(x, y), fg, bg, target)?;
+            x += cell.width as i32;
+        }
+        y += cell.height as i32;
+    }
+
+    Ok((y - top_left.y) as u32)
+}
+
+/// Returns the height `draw_wrapped` would consume, without drawing.
+pub fn wrapped_height<G: GlyphSource>(glyphs: &G, text: &str, width: u32, hyphenate: bool) -> u32 {
+    let cell = glyphs.cell_size();
+    let max_chars = (width / cell.width.max(1)) as usize;
+    WrapLines::new(text, max_chars, hyphenate).count() as u32 * cell.height
+}
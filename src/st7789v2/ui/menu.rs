@@ -0,0 +1,296 @@
+use crate::st7789v2::ui::theme::{Theme, Themed};
+use embedded_graphics::{
+    mono_font::{MonoFont, MonoTextStyle},
+    pixelcolor::Rgb565,
+    prelude::*,
+    primitives::Rectangle,
+    text::{Baseline, Text},
+};
+
+/// Fixed-point scale of the kinetic scroll velocity (1/16 item per step).
+const VELOCITY_SCALE: i32 = 16;
+
+/// Colors and font of a `Menu`.
+#[derive(Clone, Copy)]
+pub struct MenuStyle<'a> {
+    pub font: &'a MonoFont<'a>,
+    pub text: Rgb565,
+    pub background: Rgb565,
+    pub selected_text: Rgb565,
+    pub selected_background: Rgb565,
+}
+
+/// A DrawTarget that can scroll a band of its rows in hardware, like the panel's
+/// VSCRDEF/VSCSAD. See `Menu::render_scrolled()`.
+pub trait VerticalScroll: DrawTarget<Color = Rgb565> {
+    /// Makes rows `top..top + height` the scrolled band, the rows above and below stay put.
+    /// # Returns
+    /// `false`, changing nothing, if this band can't be scrolled in the current orientation.
+    fn set_scroll_band(&mut self, top: u32, height: u32) -> bool;
+
+    /// Shows the band's row `offset` at the top of the band, the rows above it wrap to the bottom.
+    fn scroll_band(&mut self, offset: u32);
+
+    /// Puts the whole screen back in place: the full scroll area at offset 0.
+    fn reset_scroll(&mut self);
+}
+
+/// Scrollable selection menu.
+/// Renders row by row: moving the selection redraws only the two rows involved, and scrolling
+/// redraws only the visible rows, each as its own strip-sized window instead of the full area.
+/// `render_scrolled()` scrolls the rows in hardware instead and draws only the ones coming into view.
+pub struct Menu<'a> {
+    items: &'a [&'a str],
+    area: Rectangle,
+    row_height: u32,
+    style: MenuStyle<'a>,
+    selected: usize,
+    top: usize,
+    /// (selected, top) as last drawn, `None` forces a full redraw.
+    drawn: Option<(usize, usize)>,
+    /// The rows are drawn into a hardware-scrolled band, item `i` at slot `i % visible_rows()`.
+    scrolled: bool,
+    velocity: i32,
+    travel: i32,
+    friction: i32,
+}
+
+impl<'a> Menu<'a> {
+    /// Creates a menu listing `items` inside `area`, one row per item.
+    pub fn new(items: &'a [&'a str], area: Rectangle, style: MenuStyle<'a>) -> Self {
+        Self {
+            items,
+            area,
+            row_height: style.font.character_size.height + 4,
+            style,
+            selected: 0,
+            top: 0,
+            drawn: None,
+            scrolled: false,
+            velocity: 0,
+            travel: 0,
+            friction: 2,
+        }
+    }
+
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    pub fn selected_item(&self) -> Option<&'a str> {
+        self.items.get(self.selected).copied()
+    }
+
+    /// Number of rows that fit in the menu area.
+    pub fn visible_rows(&self) -> usize {
+        (self.area.size.height / self.row_height).max(1) as usize
+    }
+
+    /// Selects `index` (clamped to the item count), scrolling it into view.
+    pub fn select(&mut self, index: usize) {
+        if self.items.is_empty() {
+            return;
+        }
+        self.selected = index.min(self.items.len() - 1);
+
+        let rows = self.visible_rows();
+        if self.selected < self.top {
+            self.top = self.selected;
+        } else if self.selected >= self.top + rows {
+            self.top = self.selected + 1 - rows;
+        }
+    }
+
+    pub fn select_next(&mut self) {
+        self.select(self.selected + 1);
+    }
+
+    pub fn select_prev(&mut self) {
+        self.select(self.selected.saturating_sub(1));
+    }
+
+    /// Starts a kinetic scroll, e.g. from a touch swipe, of `items_per_step` items per `step()`
+    /// (negative scrolls up). The speed decays by the friction on every step.
+    pub fn fling(&mut self, items_per_step: i32) {
+        self.velocity = items_per_step * VELOCITY_SCALE;
+        self.travel = 0;
+    }
+
+    /// Sets how fast a fling slows down, in 1/16 item per step per step (default 2).
+    pub fn set_friction(&mut self, friction: i32) {
+        self.friction = friction.max(1);
+    }
+
+    /// Advances a kinetic scroll by one step.
+    /// # Returns
+    /// `true` while the menu is still moving.
+    pub fn step(&mut self) -> bool {
+        if self.velocity == 0 {
+            return false;
+        }
+
+        self.travel += self.velocity;
+        while self.travel >= VELOCITY_SCALE {
+            self.travel -= VELOCITY_SCALE;
+            self.select_next();
+        }
+        while self.travel <= -VELOCITY_SCALE {
+            self.travel += VELOCITY_SCALE;
+            self.select_prev();
+        }
+
+        let at_end = (self.velocity > 0 && self.selected + 1 == self.items.len())
+            || (self.velocity < 0 && self.selected == 0);
+        if at_end || self.velocity.abs() <= self.friction {
+            self.velocity = 0;
+            self.travel = 0;
+        } else {
+            self.velocity -= self.friction * self.velocity.signum();
+        }
+
+        self.velocity != 0
+    }
+
+    /// Forces the next `render()` to redraw every row.
+    pub fn invalidate(&mut self) {
+        self.drawn = None;
+    }
+
+    /// Redraws the rows that changed since the last render.
+    /// After `render_scrolled()`, call `release_scroll()` first.
+    pub fn render<D>(&mut self, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Rgb565>,
+    {
+        match self.drawn {
+            Some((selected, top)) if top == self.top => {
+                if selected != self.selected {
+                    self.draw_row(target, selected)?;
+                    self.draw_row(target, self.selected)?;
+                }
+            }
+            _ => {
+                for row in 0..self.visible_rows() {
+                    self.draw_row(target, self.top + row)?;
+                }
+            }
+        }
+
+        self.drawn = Some((self.selected, self.top));
+        Ok(())
+    }
+
+    /// Like `render()`, but scrolls with the target's hardware scroll: the menu rows become the
+    /// scrolled band, item `i` is drawn once into band row `i % visible_rows()`, and moving the
+    /// list by a few rows only shifts the band offset (VSCSAD on the panel) and draws the rows
+    /// coming into view. The band spans whole screen rows, so the menu must be as wide as the
+    /// screen; otherwise, or if the target can't scroll in its orientation, this falls back to
+    /// `render()`. Call `release_scroll()` before drawing elsewhere over the band.
+    pub fn render_scrolled<D>(&mut self, target: &mut D) -> Result<(), D::Error>
+    where
+        D: VerticalScroll,
+    {
+        let rows = self.visible_rows();
+        let screen = target.bounding_box();
+        let full_width = self.area.top_left.x == screen.top_left.x && self.area.size.width == screen.size.width;
+
+        if !self.scrolled {
+            let band = rows as u32 * self.row_height;
+            if !full_width || self.area.top_left.y < 0 || !target.set_scroll_band(self.area.top_left.y as u32, band) {
+                return self.render(target);
+            }
+            self.scrolled = true;
+            self.drawn = None;
+        }
+
+        target.scroll_band((self.top % rows) as u32 * self.row_height);
+
+        match self.drawn {
+            Some((selected, top)) if self.top.abs_diff(top) < rows => {
+                // rows still on screen keep their slot, draw the ones that scrolled in
+                let entering = if self.top > top { top + rows..self.top + rows } else { self.top..top };
+                for index in entering {
+                    self.draw_slot(target, index)?;
+                }
+                if selected != self.selected {
+                    self.draw_slot(target, selected)?;
+                    self.draw_slot(target, self.selected)?;
+                }
+            }
+            _ => {
+                for index in self.top..self.top + rows {
+                    self.draw_slot(target, index)?;
+                }
+            }
+        }
+
+        self.drawn = Some((self.selected, self.top));
+        Ok(())
+    }
+
+    /// Gives the screen back after `render_scrolled()`: resets the target's scroll and forces
+    /// the next render to redraw every row.
+    pub fn release_scroll<D>(&mut self, target: &mut D)
+    where
+        D: VerticalScroll,
+    {
+        if self.scrolled {
+            target.reset_scroll();
+            self.scrolled = false;
+            self.drawn = None;
+        }
+    }
+
+    fn draw_row<D>(&self, target: &mut D, index: usize) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Rgb565>,
+    {
+        self.draw_at(target, index, index - self.top)
+    }
+
+    /// Draws `index` into its band slot, if it is on screen; an item that scrolled out would
+    /// share its slot with one in view.
+    fn draw_slot<D>(&self, target: &mut D, index: usize) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Rgb565>,
+    {
+        let rows = self.visible_rows();
+        if !(self.top..self.top + rows).contains(&index) {
+            return Ok(());
+        }
+        self.draw_at(target, index, index % rows)
+    }
+
+    fn draw_at<D>(&self, target: &mut D, index: usize, row: usize) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Rgb565>,
+    {
+        let top_left = self.area.top_left + Point::new(0, (row as u32 * self.row_height) as i32);
+        let strip = Rectangle::new(top_left, Size::new(self.area.size.width, self.row_height));
+
+        let (fg, bg) = if index == self.selected {
+            (self.style.selected_text, self.style.selected_background)
+        } else {
+            (self.style.text, self.style.background)
+        };
+
+        target.fill_solid(&strip, bg)?;
+
+        if let Some(item) = self.items.get(index) {
+            let style = MonoTextStyle::new(self.style.font, fg);
+            Text::with_baseline(item, top_left + Point::new(4, 2), style, Baseline::Top).draw(target)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Themed for Menu<'_> {
+    fn apply_theme(&mut self, theme: &Theme) {
+        self.style.text = theme.text;
+        self.style.background = theme.surface;
+        self.style.selected_text = theme.text_on_primary;
+        self.style.selected_background = theme.primary;
+        self.invalidate();
+    }
+}
@@ -0,0 +1,18 @@
+pub mod scene;
+pub mod menu;
+pub mod segment;
+pub mod clock;
+pub mod glyph;
+pub mod atlas;
+pub mod console;
+pub mod layout;
+pub mod theme;
+pub mod text_cursor;
+pub mod label;
+pub mod surface;
+pub mod compositor;
+pub mod overlay;
+pub mod cursor;
+pub mod bars;
+pub mod format;
+pub mod watch;
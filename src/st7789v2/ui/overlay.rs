@@ -0,0 +1,123 @@
+use crate::st7789v2::ui::{
+    surface::Surface,
+    theme::{Theme, Themed},
+};
+use embedded_graphics::{
+    mono_font::{MonoFont, MonoTextStyle},
+    pixelcolor::Rgb565,
+    prelude::*,
+    primitives::Rectangle,
+    text::{Alignment, Baseline, Text, TextStyleBuilder},
+};
+
+/// Transient layer over the screen, e.g. a toast or a banner. `show()` saves the pixels
+/// under its area first and `dismiss()` puts them back, so the screen below needs no redraw.
+/// The overlay can't read the screen itself: pass what is there now, from the `Framebuffer`
+/// the UI draws into (`Framebuffer::region()`) or read back from the panel
+/// (`ST7789V2DMA::read_pixels()`). Holds up to N pixels.
+pub struct Overlay<const N: usize> {
+    saved: Surface<N>,
+    shown: bool,
+}
+
+impl<const N: usize> Overlay<N> {
+    pub const fn new() -> Self {
+        Self {
+            saved: Surface::new(),
+            shown: false,
+        }
+    }
+
+    /// Saves `background`, the current pixels of `area` in row-major order, then runs `draw`
+    /// on `target`. A shown overlay is dismissed first. Returns false, drawing nothing, if
+    /// `area` holds more than N pixels.
+    pub fn show<D, B, F>(&mut self, area: Rectangle, background: B, target: &mut D, draw: F) -> Result<bool, D::Error>
+    where
+        D: DrawTarget<Color = Rgb565>,
+        B: IntoIterator<Item = Rgb565>,
+        F: FnOnce(&mut D) -> Result<(), D::Error>,
+    {
+        self.dismiss(target)?;
+        if !self.saved.reshape(area, Rgb565::BLACK) {
+            return Ok(false);
+        }
+        self.saved.fill_contiguous(&area, background).ok();
+        self.shown = true;
+        draw(target)?;
+        Ok(true)
+    }
+
+    /// Shows `toast` over `area`, see `show()`.
+    pub fn show_toast<D, B>(&mut self, area: Rectangle, background: B, target: &mut D, toast: &Toast<'_>) -> Result<bool, D::Error>
+    where
+        D: DrawTarget<Color = Rgb565>,
+        B: IntoIterator<Item = Rgb565>,
+    {
+        self.show(area, background, target, |target| toast.draw(area, target))
+    }
+
+    /// Restores what was under the overlay. Does nothing if it isn't shown.
+    pub fn dismiss<D>(&mut self, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Rgb565>,
+    {
+        if !self.shown {
+            return Ok(());
+        }
+        self.shown = false;
+        self.saved.blit(target)
+    }
+
+    pub fn is_shown(&self) -> bool {
+        self.shown
+    }
+
+    /// Returns the covered area, if shown.
+    pub fn area(&self) -> Option<Rectangle> {
+        self.shown.then(|| self.saved.bounding_box())
+    }
+}
+
+impl<const N: usize> Default for Overlay<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// One line of text centered on a filled box.
+#[derive(Clone, Copy)]
+pub struct Toast<'a> {
+    pub text: &'a str,
+    pub font: &'a MonoFont<'a>,
+    pub text_color: Rgb565,
+    pub background: Rgb565,
+}
+
+impl<'a> Toast<'a> {
+    pub fn new(text: &'a str, font: &'a MonoFont<'a>, text_color: Rgb565, background: Rgb565) -> Self {
+        Self { text, font, text_color, background }
+    }
+
+    /// Fills `area` and draws the text centered in it.
+    pub fn draw<D>(&self, area: Rectangle, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Rgb565>,
+    {
+        target.fill_solid(&area, self.background)?;
+
+        let style = MonoTextStyle::new(self.font, self.text_color);
+        let layout = TextStyleBuilder::new()
+            .alignment(Alignment::Center)
+            .baseline(Baseline::Middle)
+            .build();
+        Text::with_text_style(self.text, area.center(), style, layout).draw(target)?;
+        Ok(())
+    }
+}
+
+impl Themed for Toast<'_> {
+    fn apply_theme(&mut self, theme: &Theme) {
+        self.text_color = theme.text_on_primary;
+        self.background = theme.primary;
+    }
+}
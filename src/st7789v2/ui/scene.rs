@@ -0,0 +1,228 @@
+use crate::st7789v2::ui::theme::{Theme, Themed};
+use embedded_graphics::{
+    draw_target::DrawTargetExt,
+    image::{Image, ImageRaw},
+    mono_font::MonoTextStyle,
+    pixelcolor::Rgb565,
+    prelude::*,
+    primitives::{PrimitiveStyle, Rectangle},
+    text::{Baseline, Text},
+};
+
+/// Handle to a node in a `Scene`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NodeId(usize);
+
+/// What a scene node draws.
+#[derive(Clone, Copy)]
+pub enum NodeKind<'a> {
+    /// A line of text, drawn with its top left corner at the node position.
+    Label {
+        text: &'a str,
+        style: MonoTextStyle<'a, Rgb565>,
+    },
+    /// A raw RGB565 (big endian) image.
+    Image { image: &'a ImageRaw<'a, Rgb565> },
+    /// A horizontal bar filled to `value` percent.
+    Bar {
+        size: Size,
+        value: u8,
+        fg: Rgb565,
+        bg: Rgb565,
+    },
+}
+
+#[derive(Clone, Copy)]
+struct Node<'a> {
+    kind: NodeKind<'a>,
+    position: Point,
+    visible: bool,
+    dirty: bool,
+    /// Area covered on screen by the last render, if the node was drawn.
+    drawn: Option<Rectangle>,
+}
+
+impl Node<'_> {
+    fn bounds(&self) -> Rectangle {
+        match self.kind {
+            NodeKind::Label { text, style } => {
+                Text::with_baseline(text, self.position, style, Baseline::Top).bounding_box()
+            }
+            NodeKind::Image { image } => Rectangle::new(self.position, image.size()),
+            NodeKind::Bar { size, .. } => Rectangle::new(self.position, size),
+        }
+    }
+
+    fn draw<D>(&self, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Rgb565>,
+    {
+        match self.kind {
+            NodeKind::Label { text, style } => {
+                Text::with_baseline(text, self.position, style, Baseline::Top).draw(target)?;
+            }
+            NodeKind::Image { image } => {
+                Image::new(image, self.position).draw(target)?;
+            }
+            NodeKind::Bar { size, value, fg, bg } => {
+                let filled = size.width * value.min(100) as u32 / 100;
+                Rectangle::new(self.position, size)
+                    .into_styled(PrimitiveStyle::with_fill(bg))
+                    .draw(target)?;
+                Rectangle::new(self.position, Size::new(filled, size.height))
+                    .into_styled(PrimitiveStyle::with_fill(fg))
+                    .draw(target)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Retained-mode UI layer.
+/// The application declares up to N nodes (labels, images, bars), mutates their properties
+/// and calls `render()`, which redraws only the regions that changed: the old and new
+/// bounds of every modified node, with overlapping nodes redrawn in insertion (z) order.
+pub struct Scene<'a, const N: usize> {
+    nodes: [Option<Node<'a>>; N],
+    len: usize,
+    background: Rgb565,
+}
+
+impl<'a, const N: usize> Scene<'a, N> {
+    pub const fn new(background: Rgb565) -> Self {
+        Self {
+            nodes: [None; N],
+            len: 0,
+            background,
+        }
+    }
+
+    /// Adds a node on top of the existing ones.
+    /// # Returns
+    /// The node's handle, or `None` if the scene is full.
+    pub fn add(&mut self, kind: NodeKind<'a>, position: Point) -> Option<NodeId> {
+        if self.len == N {
+            return None;
+        }
+        self.nodes[self.len] = Some(Node {
+            kind,
+            position,
+            visible: true,
+            dirty: true,
+            drawn: None,
+        });
+        self.len += 1;
+        Some(NodeId(self.len - 1))
+    }
+
+    pub fn add_label(&mut self, text: &'a str, style: MonoTextStyle<'a, Rgb565>, position: Point) -> Option<NodeId> {
+        self.add(NodeKind::Label { text, style }, position)
+    }
+
+    pub fn add_image(&mut self, image: &'a ImageRaw<'a, Rgb565>, position: Point) -> Option<NodeId> {
+        self.add(NodeKind::Image { image }, position)
+    }
+
+    pub fn add_bar(&mut self, size: Size, value: u8, fg: Rgb565, bg: Rgb565, position: Point) -> Option<NodeId> {
+        self.add(NodeKind::Bar { size, value, fg, bg }, position)
+    }
+
+    fn node_mut(&mut self, id: NodeId) -> &mut Node<'a> {
+        let node = self.nodes[id.0].as_mut().unwrap();
+        node.dirty = true;
+        node
+    }
+
+    /// Replaces what the node draws.
+    pub fn set_kind(&mut self, id: NodeId, kind: NodeKind<'a>) {
+        self.node_mut(id).kind = kind;
+    }
+
+    /// Changes a label's text. Does nothing for other node kinds.
+    pub fn set_text(&mut self, id: NodeId, new_text: &'a str) {
+        if let NodeKind::Label { text, .. } = &mut self.node_mut(id).kind {
+            *text = new_text;
+        }
+    }
+
+    /// Changes a bar's fill percentage. Does nothing for other node kinds.
+    pub fn set_value(&mut self, id: NodeId, new_value: u8) {
+        if let NodeKind::Bar { value, .. } = &mut self.node_mut(id).kind {
+            *value = new_value;
+        }
+    }
+
+    pub fn set_position(&mut self, id: NodeId, position: Point) {
+        self.node_mut(id).position = position;
+    }
+
+    pub fn set_visible(&mut self, id: NodeId, visible: bool) {
+        self.node_mut(id).visible = visible;
+    }
+
+    /// Changes the scene background and marks every node dirty, forcing a full redraw.
+    pub fn set_background(&mut self, background: Rgb565) {
+        self.background = background;
+        self.invalidate_all();
+    }
+
+    /// Marks every node dirty, so the next `render()` redraws the whole scene.
+    pub fn invalidate_all(&mut self) {
+        for node in self.nodes[..self.len].iter_mut().flatten() {
+            node.dirty = true;
+            node.drawn = None;
+        }
+    }
+
+    /// Redraws the regions touched by modified nodes since the last render.
+    pub fn render<D>(&mut self, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Rgb565>,
+    {
+        for i in 0..self.len {
+            let node = self.nodes[i].unwrap();
+            if !node.dirty {
+                continue;
+            }
+
+            let old = node.drawn;
+            let new = if node.visible { Some(node.bounds()) } else { None };
+
+            for region in [old, new].into_iter().flatten() {
+                self.redraw_region(target, &region)?;
+            }
+        }
+
+        for node in self.nodes[..self.len].iter_mut().flatten() {
+            node.dirty = false;
+            node.drawn = if node.visible { Some(node.bounds()) } else { None };
+        }
+
+        Ok(())
+    }
+
+    /// Clears `region` to the background and redraws every visible node overlapping it,
+    /// clipped to the region.
+    fn redraw_region<D>(&self, target: &mut D, region: &Rectangle) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Rgb565>,
+    {
+        target.fill_solid(region, self.background)?;
+
+        let mut clipped = target.clipped(region);
+        for node in self.nodes[..self.len].iter().flatten() {
+            if node.visible && !node.bounds().intersection(region).is_zero_sized() {
+                node.draw(&mut clipped)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<const N: usize> Themed for Scene<'_, N> {
+    /// Takes the background from the theme; node colors stay as declared.
+    fn apply_theme(&mut self, theme: &Theme) {
+        self.set_background(theme.background);
+    }
+}
@@ -0,0 +1,425 @@
+use crate::st7789v2::ui::theme::{Theme, Themed};
+use embedded_graphics::{
+    pixelcolor::Rgb565,
+    prelude::*,
+    primitives::{Line, PrimitiveStyle, Rectangle},
+};
+
+/// Segment bits, in the usual a..g order.
+const SEG_A: u8 = 1 << 0;
+const SEG_B: u8 = 1 << 1;
+const SEG_C: u8 = 1 << 2;
+const SEG_D: u8 = 1 << 3;
+const SEG_E: u8 = 1 << 4;
+const SEG_F: u8 = 1 << 5;
+const SEG_G: u8 = 1 << 6;
+
+/// Extra segments of the 14-segment style, on top of a..f: the middle bar split in two
+/// halves, the upper and lower center bars and the four diagonals.
+const SEG_G1: u16 = 1 << 6;
+const SEG_G2: u16 = 1 << 7;
+const SEG_UL: u16 = 1 << 8;
+const SEG_UM: u16 = 1 << 9;
+const SEG_UR: u16 = 1 << 10;
+const SEG_LL: u16 = 1 << 11;
+const SEG_LM: u16 = 1 << 12;
+const SEG_LR: u16 = 1 << 13;
+
+/// Why a segment readout's geometry was rejected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, defmt::Format)]
+pub enum SegmentError {
+    /// The strokes don't fit the digit cell: it must be wider than 2 strokes (3 for the
+    /// 14-segment style) and taller than 3, and the stroke at least 1 pixel.
+    TooThick { thickness: u32 },
+}
+
+/// Returns the lit segments for a character, unknown characters are blank.
+pub const fn segments(c: char) -> u8 {
+    match c {
+        '0' | 'O' | 'o' => SEG_A | SEG_B | SEG_C | SEG_D | SEG_E | SEG_F,
+        '1' => SEG_B | SEG_C,
+        '2' => SEG_A | SEG_B | SEG_G | SEG_E | SEG_D,
+        '3' => SEG_A | SEG_B | SEG_G | SEG_C | SEG_D,
+        '4' => SEG_F | SEG_G | SEG_B | SEG_C,
+        '5' | 'S' | 's' => SEG_A | SEG_F | SEG_G | SEG_C | SEG_D,
+        '6' => SEG_A | SEG_F | SEG_G | SEG_E | SEG_C | SEG_D,
+        '7' => SEG_A | SEG_B | SEG_C,
+        '8' => SEG_A | SEG_B | SEG_C | SEG_D | SEG_E | SEG_F | SEG_G,
+        '9' => SEG_A | SEG_B | SEG_C | SEG_D | SEG_F | SEG_G,
+        'A' | 'a' => SEG_A | SEG_B | SEG_C | SEG_E | SEG_F | SEG_G,
+        'B' | 'b' => SEG_C | SEG_D | SEG_E | SEG_F | SEG_G,
+        'C' | 'c' => SEG_A | SEG_D | SEG_E | SEG_F,
+        'D' | 'd' => SEG_B | SEG_C | SEG_D | SEG_E | SEG_G,
+        'E' | 'e' => SEG_A | SEG_D | SEG_E | SEG_F | SEG_G,
+        'F' | 'f' => SEG_A | SEG_E | SEG_F | SEG_G,
+        'H' | 'h' => SEG_B | SEG_C | SEG_E | SEG_F | SEG_G,
+        'L' | 'l' => SEG_D | SEG_E | SEG_F,
+        'P' | 'p' => SEG_A | SEG_B | SEG_E | SEG_F | SEG_G,
+        'U' | 'u' => SEG_B | SEG_C | SEG_D | SEG_E | SEG_F,
+        '-' => SEG_G,
+        '_' => SEG_D,
+        _ => 0,
+    }
+}
+
+/// Returns the lit segments of the 14-segment style for a character, letters in either case
+/// look the same; unknown characters are blank.
+pub const fn segments14(c: char) -> u16 {
+    const A: u16 = SEG_A as u16;
+    const B: u16 = SEG_B as u16;
+    const C: u16 = SEG_C as u16;
+    const D: u16 = SEG_D as u16;
+    const E: u16 = SEG_E as u16;
+    const F: u16 = SEG_F as u16;
+    const G: u16 = SEG_G1 | SEG_G2;
+    match c.to_ascii_uppercase() {
+        '0' | 'O' => A | B | C | D | E | F,
+        '1' => B | C | SEG_UR,
+        '2' => A | B | G | E | D,
+        '3' => A | B | SEG_G2 | C | D,
+        '4' => F | G | B | C,
+        '5' | 'S' => A | F | G | C | D,
+        '6' => A | F | G | E | C | D,
+        '7' => A | B | C,
+        '8' => A | B | C | D | E | F | G,
+        '9' => A | B | C | D | F | G,
+        'A' => A | B | C | E | F | G,
+        'B' => A | B | C | D | SEG_G2 | SEG_UM | SEG_LM,
+        'C' => A | D | E | F,
+        'D' => A | B | C | D | SEG_UM | SEG_LM,
+        'E' => A | D | E | F | SEG_G1,
+        'F' => A | E | F | SEG_G1,
+        'G' => A | C | D | E | F | SEG_G2,
+        'H' => B | C | E | F | G,
+        'I' => A | D | SEG_UM | SEG_LM,
+        'J' => B | C | D | E,
+        'K' => E | F | SEG_G1 | SEG_UR | SEG_LR,
+        'L' => D | E | F,
+        'M' => B | C | E | F | SEG_UL | SEG_UR,
+        'N' => B | C | E | F | SEG_UL | SEG_LR,
+        'P' => A | B | E | F | G,
+        'Q' => A | B | C | D | E | F | SEG_LR,
+        'R' => A | B | E | F | G | SEG_LR,
+        'T' => A | SEG_UM | SEG_LM,
+        'U' => B | C | D | E | F,
+        'V' => E | F | SEG_LL | SEG_UR,
+        'W' => B | C | E | F | SEG_LL | SEG_LR,
+        'X' => SEG_UL | SEG_UR | SEG_LL | SEG_LR,
+        'Y' => SEG_UL | SEG_UR | SEG_LM,
+        'Z' => A | D | SEG_UR | SEG_LL,
+        '-' => G,
+        '+' => G | SEG_UM | SEG_LM,
+        '*' => G | SEG_UL | SEG_UM | SEG_UR | SEG_LL | SEG_LM | SEG_LR,
+        '/' => SEG_UR | SEG_LL,
+        '\\' => SEG_UL | SEG_LR,
+        '_' => D,
+        _ => 0,
+    }
+}
+
+/// Procedural 7-segment readout of N digits, no font bitmaps needed.
+/// Each segment is a plain rectangle fill, and `render()` only touches segments whose
+/// state changed since the last render, so updating one digit of a clock or meter
+/// costs a handful of small windowed fills.
+pub struct SevenSegment<const N: usize> {
+    position: Point,
+    digit_size: Size,
+    thickness: u32,
+    spacing: u32,
+    on: Rgb565,
+    off: Rgb565,
+    value: [u8; N],
+    drawn: [Option<u8>; N],
+}
+
+impl<const N: usize> SevenSegment<N> {
+    /// Creates a readout with its top left corner at `position`.
+    /// `digit_size` is the size of one digit cell and `thickness` the segment stroke width.
+    /// # Errors
+    /// `SegmentError::TooThick` unless `2 * thickness < width` and `3 * thickness < height`.
+    pub fn new(position: Point, digit_size: Size, thickness: u32, on: Rgb565, off: Rgb565) -> Result<Self, SegmentError> {
+        check_thickness(digit_size, thickness, 2)?;
+        Ok(Self {
+            position,
+            digit_size,
+            thickness,
+            spacing: thickness,
+            on,
+            off,
+            value: [0; N],
+            drawn: [None; N],
+        })
+    }
+
+    /// Sets the gap between digits (defaults to the segment thickness).
+    pub fn set_spacing(&mut self, spacing: u32) {
+        self.spacing = spacing;
+        self.invalidate();
+    }
+
+    /// Sets the displayed characters, right-aligned; extra leading characters are dropped.
+    pub fn set_text(&mut self, text: &str) {
+        self.value = [0; N];
+        let count = text.chars().count();
+        for (i, c) in text.chars().skip(count.saturating_sub(N)).enumerate() {
+            self.value[N - count.min(N) + i] = segments(c);
+        }
+    }
+
+    /// Sets the displayed value as a right-aligned decimal number.
+    pub fn set_number(&mut self, value: u32) {
+        self.value = [0; N];
+        let mut v = value;
+        for digit in self.value.iter_mut().rev() {
+            *digit = segments((b'0' + (v % 10) as u8) as char);
+            v /= 10;
+            if v == 0 {
+                break;
+            }
+        }
+    }
+
+    /// Sets the raw segment mask of one digit (bit 0 = a ... bit 6 = g).
+    pub fn set_segments(&mut self, digit: usize, mask: u8) {
+        self.value[digit] = mask;
+    }
+
+    /// Forces the next `render()` to redraw every segment.
+    pub fn invalidate(&mut self) {
+        self.drawn = [None; N];
+    }
+
+    /// Total area covered by the readout.
+    pub fn bounding_box(&self) -> Rectangle {
+        let width = self.digit_size.width * N as u32 + self.spacing * (N as u32).saturating_sub(1);
+        Rectangle::new(self.position, Size::new(width, self.digit_size.height))
+    }
+
+    /// Draws the segments that changed since the last render.
+    pub fn render<D>(&mut self, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Rgb565>,
+    {
+        for digit in 0..N {
+            let new = self.value[digit];
+            let changed = match self.drawn[digit] {
+                Some(old) => old ^ new,
+                None => 0x7F,
+            };
+
+            for segment in 0..7 {
+                let bit = 1 << segment;
+                if changed & bit != 0 {
+                    let color = if new & bit != 0 { self.on } else { self.off };
+                    target.fill_solid(&self.segment_rect(digit, bit), color)?;
+                }
+            }
+
+            self.drawn[digit] = Some(new);
+        }
+
+        Ok(())
+    }
+
+    fn segment_rect(&self, digit: usize, segment: u8) -> Rectangle {
+        let origin = digit_origin(self.position, self.digit_size, self.spacing, digit);
+        let (w, t) = (self.digit_size.width, self.thickness);
+        let gy = middle_top(self.digit_size, t);
+        match segment {
+            SEG_G => Rectangle::new(origin + Point::new(t as i32, gy as i32), Size::new(w - 2 * t, t)),
+            _ => outer_rect(origin, self.digit_size, t, segment),
+        }
+    }
+}
+
+/// Checks that `thickness` leaves room for `columns` vertical strokes side by side and the
+/// three horizontal ones, so the segment sizes can't underflow.
+fn check_thickness(digit_size: Size, thickness: u32, columns: u32) -> Result<(), SegmentError> {
+    if thickness == 0 || columns * thickness >= digit_size.width || 3 * thickness >= digit_size.height {
+        return Err(SegmentError::TooThick { thickness });
+    }
+    Ok(())
+}
+
+fn digit_origin(position: Point, digit_size: Size, spacing: u32, digit: usize) -> Point {
+    position + Point::new(((digit_size.width + spacing) * digit as u32) as i32, 0)
+}
+
+/// Top of the middle bar.
+fn middle_top(digit_size: Size, t: u32) -> u32 {
+    digit_size.height / 2 - t / 2
+}
+
+/// One of the outer segments a..f, shared by both styles.
+fn outer_rect(origin: Point, digit_size: Size, t: u32, segment: u8) -> Rectangle {
+    let (w, h) = (digit_size.width, digit_size.height);
+    let gy = middle_top(digit_size, t);
+
+    let (x, y, width, height) = match segment {
+        SEG_A => (t, 0, w - 2 * t, t),
+        SEG_B => (w - t, t, t, gy - t),
+        SEG_C => (w - t, gy + t, t, h - 2 * t - gy),
+        SEG_D => (t, h - t, w - 2 * t, t),
+        SEG_E => (0, gy + t, t, h - 2 * t - gy),
+        _ => (0, t, t, gy - t), // SEG_F
+    };
+
+    Rectangle::new(origin + Point::new(x as i32, y as i32), Size::new(width, height))
+}
+
+impl<const N: usize> Themed for SevenSegment<N> {
+    /// Lit segments use the primary color and unlit ones the background.
+    fn apply_theme(&mut self, theme: &Theme) {
+        self.on = theme.primary;
+        self.off = theme.background;
+        self.invalidate();
+    }
+}
+
+/// Procedural 14-segment readout of N characters, for letters as well as digits: the
+/// 7-segment outline with the middle bar split in two, center bars and four diagonals.
+/// Redraws only the segments that changed, like `SevenSegment`; the diagonals are stroked
+/// lines kept inside their quarter of the cell, so they never touch a neighbor.
+pub struct FourteenSegment<const N: usize> {
+    position: Point,
+    digit_size: Size,
+    thickness: u32,
+    spacing: u32,
+    on: Rgb565,
+    off: Rgb565,
+    value: [u16; N],
+    drawn: [Option<u16>; N],
+}
+
+/// A 14-segment shape: a bar, or a diagonal stroke between two points.
+enum Shape {
+    Bar(Rectangle),
+    Stroke(Line),
+}
+
+impl<const N: usize> FourteenSegment<N> {
+    /// Creates a readout with its top left corner at `position`.
+    /// `digit_size` is the size of one character cell and `thickness` the segment stroke width.
+    /// # Errors
+    /// `SegmentError::TooThick` unless `3 * thickness` is less than both the width and the height.
+    pub fn new(position: Point, digit_size: Size, thickness: u32, on: Rgb565, off: Rgb565) -> Result<Self, SegmentError> {
+        check_thickness(digit_size, thickness, 3)?;
+        Ok(Self {
+            position,
+            digit_size,
+            thickness,
+            spacing: thickness,
+            on,
+            off,
+            value: [0; N],
+            drawn: [None; N],
+        })
+    }
+
+    /// Sets the gap between characters (defaults to the segment thickness).
+    pub fn set_spacing(&mut self, spacing: u32) {
+        self.spacing = spacing;
+        self.invalidate();
+    }
+
+    /// Sets the displayed characters, right-aligned; extra leading characters are dropped.
+    pub fn set_text(&mut self, text: &str) {
+        self.value = [0; N];
+        let count = text.chars().count();
+        for (i, c) in text.chars().skip(count.saturating_sub(N)).enumerate() {
+            self.value[N - count.min(N) + i] = segments14(c);
+        }
+    }
+
+    /// Sets the raw segment mask of one character: bits 0..5 = a..f, 6/7 = left/right half
+    /// of the middle bar, 8..10 = upper left diagonal, center, right diagonal, 11..13 = the
+    /// lower ones.
+    pub fn set_segments(&mut self, digit: usize, mask: u16) {
+        self.value[digit] = mask;
+    }
+
+    /// Forces the next `render()` to redraw every segment.
+    pub fn invalidate(&mut self) {
+        self.drawn = [None; N];
+    }
+
+    /// Total area covered by the readout.
+    pub fn bounding_box(&self) -> Rectangle {
+        let width = self.digit_size.width * N as u32 + self.spacing * (N as u32).saturating_sub(1);
+        Rectangle::new(self.position, Size::new(width, self.digit_size.height))
+    }
+
+    /// Draws the segments that changed since the last render.
+    pub fn render<D>(&mut self, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Rgb565>,
+    {
+        for digit in 0..N {
+            let new = self.value[digit];
+            let changed = match self.drawn[digit] {
+                Some(old) => old ^ new,
+                None => 0x3FFF,
+            };
+
+            for segment in 0..14 {
+                let bit = 1 << segment;
+                if changed & bit != 0 {
+                    let color = if new & bit != 0 { self.on } else { self.off };
+                    match self.shape(digit, bit) {
+                        Shape::Bar(rect) => target.fill_solid(&rect, color)?,
+                        Shape::Stroke(line) => {
+                            line.into_styled(PrimitiveStyle::with_stroke(color, self.thickness)).draw(target)?
+                        }
+                    }
+                }
+            }
+
+            self.drawn[digit] = Some(new);
+        }
+
+        Ok(())
+    }
+
+    fn shape(&self, digit: usize, segment: u16) -> Shape {
+        let origin = digit_origin(self.position, self.digit_size, self.spacing, digit);
+        let (w, h, t) = (self.digit_size.width, self.digit_size.height, self.thickness);
+        let gy = middle_top(self.digit_size, t);
+        let cx = w / 2 - t / 2; // left of the center bars
+        let bar = |x: u32, y: u32, width: u32, height: u32| {
+            Shape::Bar(Rectangle::new(origin + Point::new(x as i32, y as i32), Size::new(width, height)))
+        };
+        // a diagonal across the open box between the bars, inset by a stroke on every side
+        // so its width stays clear of them
+        let diagonal = |left: u32, top: u32, right: u32, bottom: u32, falling: bool| {
+            let (l, r) = ((left + t) as i32, right.saturating_sub(t).max(left + t) as i32);
+            let (tp, bt) = ((top + t) as i32, bottom.saturating_sub(t).max(top + t) as i32);
+            let (start, end) = if falling {
+                (Point::new(l, tp), Point::new(r, bt))
+            } else {
+                (Point::new(r, tp), Point::new(l, bt))
+            };
+            Shape::Stroke(Line::new(origin + start, origin + end))
+        };
+
+        match segment {
+            SEG_G1 => bar(t, gy, cx - t, t),
+            SEG_G2 => bar(cx + t, gy, w - 2 * t - cx, t),
+            SEG_UM => bar(cx, t, t, gy - t),
+            SEG_LM => bar(cx, gy + t, t, h - 2 * t - gy),
+            SEG_UL => diagonal(t, t, cx, gy, true),
+            SEG_UR => diagonal(cx + t, t, w - t, gy, false),
+            SEG_LL => diagonal(t, gy + t, cx, h - t, false),
+            SEG_LR => diagonal(cx + t, gy + t, w - t, h - t, true),
+            _ => Shape::Bar(outer_rect(origin, self.digit_size, t, segment as u8)),
+        }
+    }
+}
+
+impl<const N: usize> Themed for FourteenSegment<N> {
+    /// Lit segments use the primary color and unlit ones the background.
+    fn apply_theme(&mut self, theme: &Theme) {
+        self.on = theme.primary;
+        self.off = theme.background;
+        self.invalidate();
+    }
+}
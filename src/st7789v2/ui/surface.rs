@@ -0,0 +1,98 @@
+use embedded_graphics::{pixelcolor::Rgb565, prelude::*, primitives::Rectangle};
+
+/// Small off-screen pixel buffer of up to N pixels, shaped per use.
+/// Widgets compose their next content here and send it to the display with a single
+/// `fill_contiguous`, so the panel never shows the erased-but-not-yet-redrawn state.
+/// N = 16 * 24 * 8 (3 KB) holds eight 16x24 cells.
+pub struct Surface<const N: usize> {
+    pixels: [Rgb565; N],
+    area: Rectangle,
+}
+
+impl<const N: usize> Surface<N> {
+    pub const fn new() -> Self {
+        Self {
+            pixels: [Rgb565::new(0, 0, 0); N],
+            area: Rectangle::new(Point::zero(), Size::zero()),
+        }
+    }
+
+    /// Returns true if an area of `size` fits in the buffer.
+    pub fn fits(size: Size) -> bool {
+        size.width as usize * size.height as usize <= N
+    }
+
+    /// Makes the surface cover `area` (display coordinates) and fills it with `color`.
+    /// Returns false, leaving the surface empty, if the area doesn't fit.
+    pub fn reshape(&mut self, area: Rectangle, color: Rgb565) -> bool {
+        if !Self::fits(area.size) {
+            self.area = Rectangle::new(area.top_left, Size::zero());
+            return false;
+        }
+        self.area = area;
+        let len = self.len();
+        self.pixels[..len].fill(color);
+        true
+    }
+
+    /// Returns the composed pixels in row-major order, as `fill_contiguous` expects them.
+    pub fn pixels(&self) -> &[Rgb565] {
+        &self.pixels[..self.len()]
+    }
+
+    /// Same as `pixels()`, for filling the surface from a buffer, e.g. a readback.
+    pub fn pixels_mut(&mut self) -> &mut [Rgb565] {
+        let len = self.len();
+        &mut self.pixels[..len]
+    }
+
+    /// Sends the composed area to `target` in one windowed transfer.
+    pub fn blit<D>(&self, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Rgb565>,
+    {
+        target.fill_contiguous(&self.area, self.pixels().iter().copied())
+    }
+
+    fn len(&self) -> usize {
+        self.area.size.width as usize * self.area.size.height as usize
+    }
+}
+
+impl<const N: usize> Default for Surface<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> Dimensions for Surface<N> {
+    fn bounding_box(&self) -> Rectangle {
+        self.area
+    }
+}
+
+impl<const N: usize> DrawTarget for Surface<N> {
+    type Color = Rgb565;
+    type Error = core::convert::Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let width = self.area.size.width as i32;
+        let height = self.area.size.height as i32;
+        for Pixel(point, color) in pixels {
+            let p = point - self.area.top_left;
+            if (0..width).contains(&p.x) && (0..height).contains(&p.y) {
+                self.pixels[(p.y * width + p.x) as usize] = color;
+            }
+        }
+        Ok(())
+    }
+
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        let len = self.len();
+        self.pixels[..len].fill(color);
+        Ok(())
+    }
+}
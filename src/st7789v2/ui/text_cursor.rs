@@ -0,0 +1,156 @@
+use core::fmt;
+
+use embedded_graphics::{pixelcolor::Rgb565, prelude::*, primitives::Rectangle};
+
+use crate::st7789v2::ui::glyph::GlyphSource;
+use crate::st7789v2::ui::theme::{Theme, Themed};
+
+/// A fixed spot on screen for a changing line of text, e.g. a sensor reading.
+/// Write to it through `cursor()`; each write overdraws the previous value cell by cell
+/// (digits keep their width, so nothing flickers) and only erases the cells the new value
+/// no longer covers.
+pub struct TextField<G> {
+    glyphs: G,
+    origin: Point,
+    fg: Rgb565,
+    bg: Rgb565,
+    replacement: char,
+    drawn: u32, // cells covered by the previous value
+}
+
+impl<G: GlyphSource> TextField<G> {
+    pub fn new(glyphs: G, origin: Point, fg: Rgb565, bg: Rgb565) -> Self {
+        Self {
+            glyphs,
+            origin,
+            fg,
+            bg,
+            replacement: '?',
+            drawn: 0,
+        }
+    }
+
+    /// Sets the character drawn in place of glyphs the source lacks (default '?').
+    pub fn set_replacement(&mut self, replacement: char) {
+        self.replacement = replacement;
+    }
+
+    /// Changes the colors, used from the next write on.
+    pub fn set_colors(&mut self, fg: Rgb565, bg: Rgb565) {
+        self.fg = fg;
+        self.bg = bg;
+    }
+
+    /// Returns the area covered by the last value written.
+    pub fn bounding_box(&self) -> Rectangle {
+        let cell = self.glyphs.cell_size();
+        Rectangle::new(self.origin, Size::new(cell.width * self.drawn, cell.height))
+    }
+
+    /// Starts a new value: `write!(field.cursor(&mut display), "T={}°C", t)`.
+    /// Leftover cells of the previous value are erased when the cursor is dropped or `finish()`ed.
+    pub fn cursor<'t, D>(&'t mut self, target: &'t mut D) -> TextCursor<'t, G, D>
+    where
+        D: DrawTarget<Color = Rgb565>,
+    {
+        TextCursor {
+            field: self,
+            target,
+            col: 0,
+            error: None,
+            finished: false,
+        }
+    }
+}
+
+impl<G: GlyphSource> Themed for TextField<G> {
+    fn apply_theme(&mut self, theme: &Theme) {
+        self.set_colors(theme.text, theme.background);
+    }
+}
+
+/// `core::fmt::Write` sink that renders into a `TextField`, see `TextField::cursor()`.
+pub struct TextCursor<'t, G, D>
+where
+    G: GlyphSource,
+    D: DrawTarget<Color = Rgb565>,
+{
+    field: &'t mut TextField<G>,
+    target: &'t mut D,
+    col: u32,
+    error: Option<D::Error>,
+    finished: bool,
+}
+
+impl<G, D> TextCursor<'_, G, D>
+where
+    G: GlyphSource,
+    D: DrawTarget<Color = Rgb565>,
+{
+    /// Erases what is left of the previous value and reports the first draw error, if any
+    /// (`write!` can only report it as `fmt::Error`).
+    pub fn finish(mut self) -> Result<(), D::Error> {
+        self.erase_tail();
+        match self.error.take() {
+            Some(e) => Err(e),
+            None => Ok(()),
+        }
+    }
+
+    fn erase_tail(&mut self) {
+        if self.finished {
+            return;
+        }
+        self.finished = true;
+
+        let field = &mut *self.field;
+        if self.col < field.drawn {
+            let cell = field.glyphs.cell_size();
+            let top_left = field.origin + Point::new((cell.width * self.col) as i32, 0);
+            let area = Rectangle::new(top_left, Size::new(cell.width * (field.drawn - self.col), cell.height));
+            if let Err(e) = self.target.fill_solid(&area, field.bg) {
+                self.error.get_or_insert(e);
+            }
+        }
+        field.drawn = self.col;
+    }
+}
+
+impl<G, D> fmt::Write for TextCursor<'_, G, D>
+where
+    G: GlyphSource,
+    D: DrawTarget<Color = Rgb565>,
+{
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let field = &*self.field;
+        let cell = field.glyphs.cell_size();
+
+        for c in s.chars() {
+            let c = if field.glyphs.has_glyph(c) { c } else { field.replacement };
+            let top_left = field.origin + Point::new((cell.width * self.col) as i32, 0);
+            let drawn = if field.glyphs.has_glyph(c) {
+                field.glyphs.draw_glyph(c, top_left, field.fg, field.bg, self.target)
+            } else {
+                self.target.fill_solid(&Rectangle::new(top_left, cell), field.bg)
+            };
+            self.col += 1;
+
+            if let Err(e) = drawn {
+                self.error.get_or_insert(e);
+                return Err(fmt::Error);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<G, D> Drop for TextCursor<'_, G, D>
+where
+    G: GlyphSource,
+    D: DrawTarget<Color = Rgb565>,
+{
+    fn drop(&mut self) {
+        self.erase_tail();
+    }
+}
@@ -0,0 +1,86 @@
+use embedded_graphics::pixelcolor::Rgb565;
+use embedded_graphics::prelude::RgbColor;
+
+/// Color palette shared by the UI components.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Theme {
+    /// Screen background.
+    pub background: Rgb565,
+    /// Background of raised elements (bars, dial, unselected rows).
+    pub surface: Rgb565,
+    /// Accent color (selection, bar fill, lit segments).
+    pub primary: Rgb565,
+    /// Text on background or surface.
+    pub text: Rgb565,
+    /// Text on the primary color.
+    pub text_on_primary: Rgb565,
+}
+
+impl Theme {
+    pub const DARK: Theme = Theme {
+        background: Rgb565::BLACK,
+        surface: Rgb565::new(4, 8, 4),
+        primary: Rgb565::new(0, 40, 31),
+        text: Rgb565::WHITE,
+        text_on_primary: Rgb565::BLACK,
+    };
+
+    pub const LIGHT: Theme = Theme {
+        background: Rgb565::WHITE,
+        surface: Rgb565::new(27, 55, 27),
+        primary: Rgb565::new(0, 20, 20),
+        text: Rgb565::BLACK,
+        text_on_primary: Rgb565::WHITE,
+    };
+}
+
+/// Which theme of a `ThemePair` is active.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ThemeMode {
+    #[default]
+    Dark,
+    Light,
+}
+
+/// A dark/light pair of themes with the active mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ThemePair {
+    pub dark: Theme,
+    pub light: Theme,
+    pub mode: ThemeMode,
+}
+
+impl Default for ThemePair {
+    fn default() -> Self {
+        Self {
+            dark: Theme::DARK,
+            light: Theme::LIGHT,
+            mode: ThemeMode::Dark,
+        }
+    }
+}
+
+impl ThemePair {
+    /// Returns the active theme.
+    pub fn current(&self) -> &Theme {
+        match self.mode {
+            ThemeMode::Dark => &self.dark,
+            ThemeMode::Light => &self.light,
+        }
+    }
+
+    /// Switches between dark and light, returning the new theme.
+    pub fn toggle(&mut self) -> &Theme {
+        self.mode = match self.mode {
+            ThemeMode::Dark => ThemeMode::Light,
+            ThemeMode::Light => ThemeMode::Dark,
+        };
+        self.current()
+    }
+}
+
+/// Implemented by components that take their colors from a `Theme`.
+/// Applying a theme marks the whole component dirty, so the next render redraws it fully.
+pub trait Themed {
+    fn apply_theme(&mut self, theme: &Theme);
+}
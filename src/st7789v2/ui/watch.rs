@@ -0,0 +1,169 @@
+use crate::st7789v2::ui::theme::{Theme, Themed};
+use embedded_graphics::{
+    mono_font::{MonoFont, MonoTextStyleBuilder},
+    pixelcolor::Rgb565,
+    prelude::*,
+    primitives::Rectangle,
+    text::{Baseline, Text},
+};
+
+/// Most bytes a `WatchWindow` mirrors.
+pub const WATCH_BYTES: usize = 256;
+
+/// Most bytes on one line of the panel.
+const MAX_PER_ROW: usize = 16;
+
+/// "aaaaaaaa: ", then "hh " and one ASCII character per byte.
+const LINE_LEN: usize = 10 + 4 * MAX_PER_ROW;
+
+const HEX: &[u8; 16] = b"0123456789ABCDEF";
+
+/// A live hex/ASCII dump of a RAM range on part of the screen, for bring-up on boards
+/// without a serial console: point it at a state struct or a receive buffer and watch it
+/// change. `poll()` rereads the range every `period_ms` and redraws only the lines whose
+/// bytes changed. As many bytes per line as fit the area width (up to 16), as many lines
+/// as fit its height; bytes past that or past `WATCH_BYTES` aren't shown.
+pub struct WatchWindow<'a> {
+    area: Rectangle,
+    font: &'a MonoFont<'a>,
+    text: Rgb565,
+    background: Rgb565,
+    address: *const u8,
+    len: usize,
+    period_ms: u32,
+    last_poll_ms: Option<u32>,
+    /// The bytes as last drawn.
+    shown: [u8; WATCH_BYTES],
+    drawn: bool,
+}
+
+impl<'a> WatchWindow<'a> {
+    /// Creates a window showing `len` bytes from `address` in `area`, white on black.
+    /// # Safety
+    /// `address..address + len` must stay readable for as long as the window is polled.
+    /// The bytes are read with volatile reads, so memory written by interrupts or DMA is fine.
+    pub unsafe fn new(area: Rectangle, font: &'a MonoFont<'a>, address: usize, len: usize, period_ms: u32) -> Self {
+        Self {
+            area,
+            font,
+            text: Rgb565::WHITE,
+            background: Rgb565::BLACK,
+            address: address as *const u8,
+            len,
+            period_ms,
+            last_poll_ms: None,
+            shown: [0; WATCH_BYTES],
+            drawn: false,
+        }
+    }
+
+    pub fn set_colors(&mut self, text: Rgb565, background: Rgb565) {
+        self.text = text;
+        self.background = background;
+        self.invalidate();
+    }
+
+    /// Makes the next render redraw every line.
+    pub fn invalidate(&mut self) {
+        self.drawn = false;
+    }
+
+    /// Bytes per line at the area width.
+    pub fn bytes_per_row(&self) -> usize {
+        let columns = self.area.size.width / (self.font.character_size.width + self.font.character_spacing);
+        ((columns as usize).saturating_sub(10) / 4).clamp(1, MAX_PER_ROW)
+    }
+
+    /// Number of bytes on screen.
+    pub fn visible_len(&self) -> usize {
+        let rows = (self.area.size.height / self.font.character_size.height) as usize;
+        self.len.min(WATCH_BYTES).min(rows * self.bytes_per_row())
+    }
+
+    /// Rerenders if `period_ms` has passed since the last poll, e.g. from the main loop.
+    /// # Returns
+    /// `true` if anything was drawn.
+    pub fn poll<D>(&mut self, now_ms: u32, target: &mut D) -> Result<bool, D::Error>
+    where
+        D: DrawTarget<Color = Rgb565>,
+    {
+        if let Some(last) = self.last_poll_ms
+            && now_ms.wrapping_sub(last) < self.period_ms
+        {
+            return Ok(false);
+        }
+        self.last_poll_ms = Some(now_ms);
+        self.render(target)
+    }
+
+    /// Rereads the range and redraws the lines that changed since the last render.
+    /// # Returns
+    /// `true` if anything was drawn.
+    pub fn render<D>(&mut self, target: &mut D) -> Result<bool, D::Error>
+    where
+        D: DrawTarget<Color = Rgb565>,
+    {
+        let len = self.visible_len();
+        let mut now = [0u8; WATCH_BYTES];
+        for (offset, byte) in now[..len].iter_mut().enumerate() {
+            // SAFETY: the caller of `new()` guarantees the range is readable.
+            *byte = unsafe { self.address.add(offset).read_volatile() };
+        }
+
+        let redraw_all = !self.drawn;
+        if redraw_all {
+            target.fill_solid(&self.area, self.background)?;
+        }
+
+        let per_row = self.bytes_per_row();
+        let mut changed = redraw_all;
+        for (row, bytes) in now[..len].chunks(per_row).enumerate() {
+            let start = row * per_row;
+            if !redraw_all && self.shown[start..start + bytes.len()] == *bytes {
+                continue;
+            }
+            self.draw_line(row, bytes, target)?;
+            changed = true;
+        }
+
+        self.shown = now;
+        self.drawn = true;
+        Ok(changed)
+    }
+
+    fn draw_line<D>(&self, row: usize, bytes: &[u8], target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Rgb565>,
+    {
+        let per_row = self.bytes_per_row();
+        let mut line = [b' '; LINE_LEN];
+
+        let address = self.address as usize + row * per_row;
+        for (digit, shift) in line[..8].iter_mut().zip((0..8).rev()) {
+            *digit = HEX[(address >> (shift * 4)) & 0xF];
+        }
+        line[8] = b':';
+        for (i, &byte) in bytes.iter().enumerate() {
+            line[10 + 3 * i] = HEX[(byte >> 4) as usize];
+            line[11 + 3 * i] = HEX[(byte & 0xF) as usize];
+            line[10 + 3 * per_row + i] = if byte.is_ascii_graphic() { byte } else { b'.' };
+        }
+
+        // the background color makes each line overwrite the previous one in a single window
+        let style = MonoTextStyleBuilder::new()
+            .font(self.font)
+            .text_color(self.text)
+            .background_color(self.background)
+            .build();
+        let text = core::str::from_utf8(&line[..10 + 4 * per_row]).unwrap_or_default();
+        let top_left = self.area.top_left + Point::new(0, (row as u32 * self.font.character_size.height) as i32);
+        Text::with_baseline(text, top_left, style, Baseline::Top).draw(target)?;
+        Ok(())
+    }
+}
+
+impl Themed for WatchWindow<'_> {
+    fn apply_theme(&mut self, theme: &Theme) {
+        self.set_colors(theme.text, theme.background);
+    }
+}